@@ -0,0 +1,21 @@
+#version 450
+
+layout(location = 0) in vec2 v_uv;
+
+layout(location = 0) out vec4 o_color;
+
+layout(set = 0, binding = 0) uniform texture2D t_scene;
+layout(set = 0, binding = 1) uniform sampler s_scene;
+layout(set = 0, binding = 2) uniform ToneMap {
+	float u_exposure;
+	float u_gamma;
+};
+
+void main() {
+	vec3 hdr = texture(sampler2D(t_scene, s_scene), v_uv).rgb * u_exposure;
+
+	// Reinhard tone mapping compresses unbounded scene luminance into 0..1
+	vec3 mapped = hdr / (hdr + vec3(1.0));
+
+	o_color = vec4(pow(mapped, vec3(1.0 / u_gamma)), 1.0);
+}
@@ -0,0 +1,12 @@
+#version 450
+
+layout(location = 0) out vec2 v_uv;
+
+void main() {
+	// One oversized triangle covering the whole frame, generated from the vertex index alone:
+	// uvs (0,0) (2,0) (0,2) place its corners at NDC (-1,1) (3,1) (-1,-3)
+	vec2 uv = vec2(float((gl_VertexIndex << 1) & 2), float(gl_VertexIndex & 2));
+	v_uv = uv;
+	// NDC y points up while texture v points down, so the y axis flips
+	gl_Position = vec4(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+}
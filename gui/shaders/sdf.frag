@@ -0,0 +1,27 @@
+#version 450
+
+layout(location = 0) in vec2 v_uv;
+
+layout(location = 0) out vec4 o_color;
+
+layout(set = 0, binding = 0) uniform SdfParams {
+	mat4 u_transform;
+	vec4 u_color;
+	vec2 u_half_size;
+	float u_corner_radius;
+};
+
+// The signed distance from p to a box of the given half extents with rounded corners
+float rounded_box_distance(vec2 p, vec2 half_size, float radius) {
+	vec2 q = abs(p) - (half_size - vec2(radius));
+	return length(max(q, vec2(0.0))) + min(max(q.x, q.y), 0.0) - radius;
+}
+
+void main() {
+	// The quad's UVs span 0..1; recover the position in logical pixels relative to the shape's center
+	vec2 p = (v_uv - vec2(0.5)) * 2.0 * u_half_size;
+	float distance = rounded_box_distance(p, u_half_size, u_corner_radius);
+	// One pixel of falloff across the zero crossing anti-aliases the edge at any scale
+	float coverage = clamp(0.5 - distance, 0.0, 1.0);
+	o_color = vec4(u_color.rgb, u_color.a * coverage);
+}
@@ -0,0 +1,12 @@
+#version 450
+
+layout(location = 0) in vec2 v_uv;
+
+layout(location = 0) out vec4 o_color;
+
+layout(set = 0, binding = 0) uniform texture2D t_texture;
+layout(set = 0, binding = 1) uniform sampler s_sampler;
+
+void main() {
+	o_color = texture(sampler2D(t_texture, s_sampler), v_uv);
+}
@@ -0,0 +1,15 @@
+#version 450
+
+layout(location = 0) in vec2 a_position;
+layout(location = 1) in vec2 a_uv;
+
+layout(location = 0) out vec2 v_uv;
+
+layout(set = 0, binding = 2) uniform Transform {
+	mat4 u_transform;
+};
+
+void main() {
+	v_uv = a_uv;
+	gl_Position = u_transform * vec4(a_position, 0.0, 1.0);
+}
@@ -0,0 +1,2701 @@
+use crate::buffer_pool::BufferPool;
+use crate::color_palette::{ColorPalette, Theme};
+use crate::draw_command::DrawCommand;
+use crate::draw_command::{Vertex2DColored, Vertex2DTextured};
+use crate::frame_stats::FrameStats;
+use crate::geometry::{Rect, Size};
+use crate::gpu_timer::GpuTimer;
+use crate::pipeline::{BlendMode, ComputePipeline, Pipeline, PipelineKey, PipelineManager};
+use crate::post_process::PostProcess;
+use crate::render_graph::{RenderGraph, RenderNode};
+use crate::resource_cache::ResourceCache;
+use crate::shader_stage;
+use crate::shader_watcher::ShaderWatcher;
+use crate::staging_belt::StagingBelt;
+use crate::text_renderer::TextRenderer;
+use crate::texture::Texture;
+use crate::uniform_buffer::{self, UniformBuffer};
+use crate::window_context::WindowContext;
+use crate::window_state::WindowState;
+use clipboard::{ClipboardContext, ClipboardProvider};
+use futures::executor::block_on;
+use std::collections::HashMap;
+use winit::window::Window;
+
+// Where the GUI font is loaded from and the cache name of the alpha-blended pipeline drawing it
+const FONT_PATH: &str = "fonts/OpenSans-Regular.ttf";
+const TEXT_PIPELINE: &str = "text";
+
+// The cache name of the vertex-colored pipeline the GUI's gradient quads draw with
+const GUI_COLORED_PIPELINE: &str = "gui_colored";
+
+// The cache name of the signed-distance-field pipeline drawing rounded rectangles and circles
+const SDF_PIPELINE: &str = "sdf";
+
+// How button label text is placed within its button, in logical pixels
+const BUTTON_LABEL_SIZE: f32 = 14.;
+const BUTTON_LABEL_PADDING: f32 = 8.;
+
+// Which GPU and backend to initialize with, e.g. to force Vulkan or the integrated GPU on multi-GPU machines
+#[derive(Debug, Clone, Copy)]
+pub struct ApplicationConfig {
+	pub backend: wgpu::BackendBit,
+	pub power_preference: wgpu::PowerPreference,
+	// Forces a specific swap chain format; None picks the format the surface prefers
+	pub surface_format: Option<wgpu::TextureFormat>,
+	// Maximum sampler anisotropy, clamped to what the adapter supports; values above 1 enable the
+	// anisotropic filtering extension, which applies device-wide in this wgpu version
+	pub anisotropy: u8,
+	// How many frames may be in flight ahead of the display before render waits, on backends that
+	// queue presentation ahead (Vulkan, DirectX 12). Lower values reduce input latency at the cost
+	// of CPU/GPU overlap: a value of 1 waits out each submission before acquiring the next frame
+	// and prefers the Mailbox present mode, so every frame samples the very latest input state
+	pub desired_maximum_frame_latency: u32,
+}
+
+impl Default for ApplicationConfig {
+	fn default() -> Self {
+		Self {
+			backend: wgpu::BackendBit::PRIMARY,
+			power_preference: wgpu::PowerPreference::Default,
+			surface_format: None,
+			anisotropy: 1,
+			// Two frames in flight is the conventional compromise between latency and throughput
+			desired_maximum_frame_latency: 2,
+		}
+	}
+}
+
+// Why the application could not bring up the GPU at startup
+#[derive(Debug)]
+pub enum ApplicationInitError {
+	NoAdapter,
+	DeviceRequestFailed,
+	// Surface creation is currently infallible in wgpu, but callers should still be prepared for this case
+	#[allow(dead_code)]
+	SurfaceCreationFailed,
+}
+
+impl std::fmt::Display for ApplicationInitError {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		match self {
+			ApplicationInitError::NoAdapter => write!(f, "No compatible GPU adapter found; Graphite requires Vulkan, Metal, or DirectX 12 support"),
+			ApplicationInitError::DeviceRequestFailed => write!(f, "The GPU adapter was found but a logical device could not be requested from it"),
+			ApplicationInitError::SurfaceCreationFailed => write!(f, "A render surface could not be created for the window"),
+		}
+	}
+}
+
+impl std::error::Error for ApplicationInitError {}
+
+// Why a frame could not be captured to disk
+#[derive(Debug)]
+pub enum CaptureError {
+	BufferMap,
+	Image(image::ImageError),
+}
+
+impl std::fmt::Display for CaptureError {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		match self {
+			CaptureError::BufferMap => write!(f, "The captured frame could not be read back from the GPU"),
+			CaptureError::Image(error) => write!(f, "The captured frame could not be encoded: {}", error),
+		}
+	}
+}
+
+impl std::error::Error for CaptureError {}
+
+// The viewport in logical pixels, which the GUI lays out against so element sizes stay
+// consistent across displays with different scale factors
+fn logical_size(width: u32, height: u32, scale_factor: f64) -> Size {
+	Size::new((width as f64 / scale_factor) as f32, (height as f64 / scale_factor) as f32)
+}
+
+// The instant a capped application may next redraw; None when no cap is set or the cap is already
+// satisfied, in which case the redraw can happen right away
+fn frame_deadline(last_render: std::time::Instant, now: std::time::Instant, max_fps: Option<u32>) -> Option<std::time::Instant> {
+	let fps = max_fps?;
+	let deadline = last_render + std::time::Duration::from_secs(1) / fps;
+	if deadline <= now {
+		None
+	} else {
+		Some(deadline)
+	}
+}
+
+// Buffer copies require each row to start at a multiple of 256 bytes, so rows narrower than that get padded
+fn align_bytes_per_row(unpadded_bytes_per_row: u32) -> u32 {
+	const ALIGNMENT: u32 = 256;
+	unpadded_bytes_per_row.div_ceil(ALIGNMENT) * ALIGNMENT
+}
+
+// Whether the format stores sRGB-encoded bytes, meaning a readback needs no further encoding
+fn format_is_srgb(format: wgpu::TextureFormat) -> bool {
+	matches!(format, wgpu::TextureFormat::Bgra8UnormSrgb | wgpu::TextureFormat::Rgba8UnormSrgb)
+}
+
+// Applies the sRGB transfer function to one linear color byte, for saving linear readbacks as image
+// files that viewers interpret as sRGB; alpha is coverage, not color, and must not go through this
+fn encode_srgb_channel(linear: u8) -> u8 {
+	let linear = linear as f32 / 255.;
+	let encoded = if linear <= 0.003_130_8 { linear * 12.92 } else { 1.055 * linear.powf(1. / 2.4) - 0.055 };
+	(encoded * 255. + 0.5) as u8
+}
+
+// How many consecutive failed frame acquisitions are treated as a lost device rather than a transient hiccup
+const MAX_ACQUISITION_FAILURES: u32 = 3;
+
+// How render() should respond when the swap chain cannot produce a frame buffer
+// The only acquisition error wgpu reports is a timeout, so the cause is inferred from repetition:
+// one timeout is a transient hiccup, but consecutive ones mean the swap chain no longer matches
+// its surface (e.g. after a resize or DPI change the OS applied directly) and needs rebuilding
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AcquisitionStrategy {
+	RecreateAndRetry,
+	SkipFrame,
+}
+
+fn acquisition_strategy(consecutive_failures: u32) -> AcquisitionStrategy {
+	match consecutive_failures {
+		// The first timeout is treated as transient; drop this frame and let the next redraw try again
+		0 => AcquisitionStrategy::SkipFrame,
+		_ => AcquisitionStrategy::RecreateAndRetry,
+	}
+}
+
+// The replay order for a queue of draw commands, stable-grouped so commands sharing a pipeline
+// run consecutively and the pipeline only needs binding once per group
+// On a scene of 1000 quads alternating between two pipelines this cuts set_pipeline calls from 1000 to 2
+fn batched_order(pipeline_names: &[&str]) -> Vec<usize> {
+	let mut group_rank: HashMap<&str, usize> = HashMap::new();
+	for &name in pipeline_names {
+		let next_rank = group_rank.len();
+		group_rank.entry(name).or_insert(next_rank);
+	}
+
+	let mut order: Vec<usize> = (0..pipeline_names.len()).collect();
+	// A stable sort preserves the submission order within each group
+	order.sort_by_key(|&index| group_rank[pipeline_names[index]]);
+	order
+}
+
+// The palette entry as straight sRGB channel floats for a vertex color attribute
+// The colored pipeline's shaders pass vertex colors through untouched, so an sRGB frame buffer
+// encodes them on write just like the clear color
+fn vertex_color(color: ColorPalette, theme: Theme) -> [f32; 4] {
+	let rgb = color.get_color(theme);
+	let alpha = match color {
+		ColorPalette::Color(rgba) => (rgba & 0xff) as f32 / 255.,
+		_ => 1.,
+	};
+	[((rgb >> 16) & 0xff) as f32 / 255., ((rgb >> 8) & 0xff) as f32 / 255., (rgb & 0xff) as f32 / 255., alpha]
+}
+
+// The four corners of a `width`-thick quad running from `a` to `b`, in logical pixels, ordered to
+// match to_ndc_vertices so the shared [0, 1, 2, 2, 3, 0] index pattern applies
+fn line_quad_corners(a: (f32, f32), b: (f32, f32), width: f32) -> [[f32; 2]; 4] {
+	let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+	let length = (dx * dx + dy * dy).sqrt();
+	// A zero-length line has no direction; its quad degenerates to zero area and draws nothing
+	let (nx, ny) = if length > 0. { (-dy / length * width / 2., dx / length * width / 2.) } else { (0., width / 2.) };
+	[[a.0 + nx, a.1 + ny], [b.0 + nx, b.1 + ny], [b.0 - nx, b.1 - ny], [a.0 - nx, a.1 - ny]]
+}
+
+// How many rim segments a circle of this radius tessellates into: roughly one per logical pixel of
+// radius, floored so small circles stay round and capped so large ones stay cheap
+fn circle_segments(radius: f32) -> u16 {
+	(radius.ceil() as u16).clamp(12, 128)
+}
+
+// The circle's vertices: the center first, then the rim counterclockwise from the right
+fn circle_positions(center: (f32, f32), radius: f32, segments: u16) -> Vec<[f32; 2]> {
+	let mut positions = Vec::with_capacity(segments as usize + 1);
+	positions.push([center.0, center.1]);
+	for segment in 0..segments {
+		let angle = segment as f32 / segments as f32 * 2. * std::f32::consts::PI;
+		positions.push([center.0 + radius * angle.cos(), center.1 + radius * angle.sin()]);
+	}
+	positions
+}
+
+// A triangle fan over circle_positions' layout: each rim edge closes a triangle with the center
+fn circle_indices(segments: u16) -> Vec<u16> {
+	(0..segments).flat_map(|segment| vec![0, segment + 1, (segment + 1) % segments + 1]).collect()
+}
+
+// Requests the logical device and queue from the adapter; recover_device repeats this after a GPU reset
+// The request is infallible in this wgpu version, but catch a panicking driver rather than crashing with no context
+fn request_device_and_queue(adapter: &wgpu::Adapter, anisotropic_filtering: bool) -> Result<(wgpu::Device, wgpu::Queue), ApplicationInitError> {
+	std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+		block_on(adapter.request_device(&wgpu::DeviceDescriptor {
+			extensions: wgpu::Extensions {
+				anisotropic_filtering,
+			},
+			limits: wgpu::Limits::default(),
+		}))
+	}))
+	.map_err(|_| ApplicationInitError::DeviceRequestFailed)
+}
+
+// The highest sampler anisotropy the adapter supports
+// There is no limit query in this wgpu version; every hardware backend we target guarantees 16,
+// while software rasterizers commonly ignore the setting entirely
+fn max_supported_anisotropy(adapter: &wgpu::Adapter) -> u8 {
+	match adapter.get_info().device_type {
+		wgpu::DeviceType::Cpu => 1,
+		_ => 16,
+	}
+}
+
+// The anisotropy to actually request: at least 1 (disabled), at most what the adapter supports
+fn clamp_anisotropy(requested: u8, max_supported: u8) -> u8 {
+	requested.max(1).min(max_supported)
+}
+
+// The formats the adapter's surfaces can present, preferred format first
+// wgpu offers no direct capability query yet: every backend presents BGRA, and Vulkan and GL also accept RGBA
+fn supported_surface_formats(adapter: &wgpu::Adapter) -> Vec<wgpu::TextureFormat> {
+	match adapter.get_info().backend {
+		wgpu::Backend::Vulkan | wgpu::Backend::Gl => vec![
+			wgpu::TextureFormat::Bgra8UnormSrgb,
+			wgpu::TextureFormat::Bgra8Unorm,
+			wgpu::TextureFormat::Rgba8UnormSrgb,
+			wgpu::TextureFormat::Rgba8Unorm,
+		],
+		_ => vec![wgpu::TextureFormat::Bgra8UnormSrgb, wgpu::TextureFormat::Bgra8Unorm],
+	}
+}
+
+// The swap chain format to render in: the configured override when the surface supports it,
+// otherwise the surface's preferred format rather than failing later at presentation
+fn choose_surface_format(supported: &[wgpu::TextureFormat], requested: Option<wgpu::TextureFormat>) -> wgpu::TextureFormat {
+	match requested {
+		Some(format) if supported.contains(&format) => format,
+		Some(format) => {
+			log::warn!("Surface format {:?} is not supported by this adapter; using {:?}", format, supported[0]);
+			supported[0]
+		}
+		None => supported[0],
+	}
+}
+
+// A texture decode in flight on a background thread, polled each redraw until its pixels arrive
+struct PendingTexture {
+	// The cache name the finished texture is stored under, which is also its source path
+	name: String,
+	future: std::pin::Pin<Box<dyn std::future::Future<Output = Result<crate::texture::DecodedImage, crate::texture::TextureError>>>>,
+}
+
+// Which slot 0 bind group layout a pipeline was built with, so a rebuild picks the same constructor
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PipelineLayout {
+	// The standard texture, sampler, and transform layout
+	Texture,
+	// The transform-only layout of vertex-colored pipelines
+	UniformOnly,
+	// The SDF pipeline's shape-parameter block, read by both shader stages
+	Sdf,
+}
+
+// Whether wireframe mode swaps this pipeline to line-list topology: triangle geometry only, and
+// not the SDF quads, whose two triangles are a screen-space bounding box rather than real geometry
+fn wireframe_applies(source: &PipelineSource) -> bool {
+	source.topology == wgpu::PrimitiveTopology::TriangleList && source.layout != PipelineLayout::Sdf
+}
+
+// Everything needed to rebuild a cached pipeline when one of its shaders is recompiled
+struct PipelineSource {
+	vertex_shader_path: String,
+	fragment_shader_path: String,
+	vertex_buffer_descriptor: wgpu::VertexBufferDescriptor<'static>,
+	instance_buffer_descriptor: Option<wgpu::VertexBufferDescriptor<'static>>,
+	index_format: wgpu::IndexFormat,
+	blend_mode: BlendMode,
+	topology: wgpu::PrimitiveTopology,
+	layout: PipelineLayout,
+}
+
+// Extra draw calls an embedder records at the end of the scene pass; see set_on_render
+// The pass and the application share the same borrow, so resources fetched through the
+// `&Application` (cached pipelines, textures, owned buffers) live long enough to bind to the pass
+pub type RenderHook = Box<dyn for<'a> FnMut(&mut wgpu::RenderPass<'a>, &'a Application)>;
+
+pub struct Application {
+	pub adapter: wgpu::Adapter,
+	pub device: wgpu::Device,
+	pub queue: wgpu::Queue,
+	// One context per OS window holding its surface, swap chain, GUI tree, and draw queue; index 0
+	// is the primary window (or the sole headless context). Everything else here is shared
+	windows: Vec<WindowContext>,
+	// Maps winit's window ids back to contexts so events and redraws reach the right one
+	window_ids: HashMap<winit::window::WindowId, usize>,
+	// The context the single-window methods operate on; window_event keeps it on the event's window
+	active_window: usize,
+	pub sample_count: u32,
+	pub wireframe: bool,
+	// Mirrors the window's borderless fullscreen state so F11 can toggle it both ways
+	pub fullscreen: bool,
+	// Present while pass-level GPU timing is on; each frame blocks after its submit to measure the drain
+	gpu_timer: Option<GpuTimer>,
+	// The sampler anisotropy in effect, already clamped to the adapter; 1 means the extension is off
+	// The extension applies to every sampler on the device; there is no per-sampler clamp to set
+	pub anisotropy: u8,
+	frame_stats: FrameStats,
+	// Caps how often dirty frames schedule redraws; None redraws as fast as the event loop allows
+	max_fps: Option<u32>,
+	// Caps how many frames may be in flight at once; see ApplicationConfig::desired_maximum_frame_latency
+	frame_latency: u32,
+	// When the last frame rendered, anchoring the frame cap's next wakeup
+	last_render_time: std::time::Instant,
+	// The frame's passes in dependency order; Option so render() can run it against &self
+	render_graph: Option<RenderGraph>,
+	pub shader_cache: ResourceCache<wgpu::ShaderModule>,
+	pub pipeline_cache: ResourceCache<Pipeline>,
+	// Dedups pipelines by configuration; the name-keyed cache above remains for hot-reloaded pipelines
+	pub pipeline_manager: PipelineManager,
+	pub compute_pipeline_cache: ResourceCache<ComputePipeline>,
+	pub texture_cache: ResourceCache<Texture>,
+	// The magenta/black checkerboard substituted for textures that fail to load, built on first need
+	fallback_texture: Option<Texture>,
+	// Decodes running on background threads, drained into texture_cache as they complete
+	pending_textures: Vec<PendingTexture>,
+	// The last reported cursor position in logical pixels, absent until the cursor first enters the window
+	pub cursor_position: Option<(f32, f32)>,
+	// The cursor icon currently shown, so hovering only calls into winit when it actually changes
+	pub cursor_icon: winit::window::CursorIcon,
+	// The currently held modifier keys, tracked so shortcuts like Ctrl+C can be matched
+	pub modifiers: winit::event::ModifiersState,
+	// The most recently dropped file, kept until something consumes it
+	pub dropped_file: Option<std::path::PathBuf>,
+	// True while a dragged file hovers over the window, so the UI can show a drop-target highlight
+	pub file_hover: bool,
+	// Recycles geometry buffers between GUI rebuilds instead of allocating fresh ones each frame
+	pub buffer_pool: BufferPool,
+	// Streams small per-frame buffer updates through reusable staging chunks; render() drives its
+	// finish/recall lifecycle around each submit
+	pub staging_belt: StagingBelt,
+	pub clear_color: wgpu::Color,
+	pub theme: Theme,
+	pub hot_reload_enabled: bool,
+	// Emits per-pipeline debug groups and per-draw markers for GPU capture tools like RenderDoc;
+	// on by default in debug builds only, since the label formatting costs a little every draw
+	pub debug_markers: bool,
+	// The optional tone-mapping stage; None renders straight into the swap chain
+	post_process: Option<PostProcess>,
+	// Tone-mapping settings, kept here so they survive toggling the stage off and on
+	exposure: f32,
+	gamma: f32,
+	// None when no clipboard provider is available, e.g. on a headless X-less session
+	clipboard: Option<ClipboardContext>,
+	// Loaded lazily on the first draw_text call so headless use never touches font files
+	text_renderer: Option<TextRenderer>,
+	shader_watcher: Option<ShaderWatcher>,
+	pipeline_shaders: HashMap<String, PipelineSource>,
+	// An embedder's extra draw calls, recorded at the end of the scene pass; see set_on_render
+	// In a RefCell because the render graph's record functions only see &Application
+	on_render: std::cell::RefCell<Option<RenderHook>>,
+}
+
+impl Application {
+	pub fn new(window: &Window, config: ApplicationConfig) -> Result<Self, ApplicationInitError> {
+		// Window as understood by the GPU for rendering onto
+		let surface = wgpu::Surface::create(window);
+
+		// Represents a GPU, exposes the real GPU device and queue
+		let adapter = block_on(wgpu::Adapter::request(
+			&wgpu::RequestAdapterOptions {
+				power_preference: config.power_preference,
+				compatible_surface: Some(&surface),
+			},
+			config.backend,
+		))
+		.ok_or(ApplicationInitError::NoAdapter)?;
+
+		let mut app = Application::from_adapter(Some(surface), adapter, window.inner_size().width, window.inner_size().height, config.surface_format, config.anisotropy)?;
+		app.window_ids.insert(window.id(), 0);
+		app.windows[0].scale_factor = window.scale_factor();
+		app.set_frame_latency(config.desired_maximum_frame_latency);
+		Ok(app)
+	}
+
+	// Registers an additional OS window (e.g. a tool palette), giving it its own surface, swap
+	// chain, GUI tree, and draw queue on the shared device and caches
+	pub fn add_window(&mut self, window: &Window) {
+		let surface = wgpu::Surface::create(window);
+		let size = window.inner_size();
+		let format = self.windows[0].swap_chain_descriptor.format;
+
+		let mut context = WindowContext::new(&self.device, Some(surface), size.width, size.height, format);
+		context.scale_factor = window.scale_factor();
+		let scene_format = self.scene_format();
+		context.recreate_render_targets(&self.device, self.sample_count, scene_format);
+
+		self.window_ids.insert(window.id(), self.windows.len());
+		self.windows.push(context);
+	}
+
+	// Points the single-window methods at the context owning this window id; false for ids that
+	// never got a context, whose events the caller should ignore
+	pub fn set_active_window(&mut self, id: winit::window::WindowId) -> bool {
+		match self.window_ids.get(&id) {
+			Some(&index) => {
+				self.active_window = index;
+				true
+			}
+			None => false,
+		}
+	}
+
+	// The id of the primary window (context 0), e.g. for persisting its geometry on exit
+	pub fn primary_window_id(&self) -> Option<winit::window::WindowId> {
+		self.window_ids.iter().find(|(_, &index)| index == 0).map(|(&id, _)| id)
+	}
+
+	// The active window's context: the event's window during dispatch, the primary otherwise
+	pub fn context(&self) -> &WindowContext {
+		&self.windows[self.active_window]
+	}
+
+	pub fn context_mut(&mut self) -> &mut WindowContext {
+		&mut self.windows[self.active_window]
+	}
+
+	// The active window's viewport in logical pixels
+	fn logical_viewport(&self) -> Size {
+		let context = self.context();
+		logical_size(context.swap_chain_descriptor.width, context.swap_chain_descriptor.height, context.scale_factor)
+	}
+
+	// Builds an application without a window, rendering into an offscreen texture
+	// This is what CI and pixel tests use, since no display server or surface is needed
+	pub fn new_headless(width: u32, height: u32) -> Result<Self, ApplicationInitError> {
+		let adapter = block_on(wgpu::Adapter::request(
+			&wgpu::RequestAdapterOptions {
+				power_preference: wgpu::PowerPreference::Default,
+				compatible_surface: None,
+			},
+			wgpu::BackendBit::PRIMARY,
+		))
+		.ok_or(ApplicationInitError::NoAdapter)?;
+
+		Application::from_adapter(None, adapter, width, height, None, 1)
+	}
+
+	fn from_adapter(surface: Option<wgpu::Surface>, adapter: wgpu::Adapter, width: u32, height: u32, surface_format: Option<wgpu::TextureFormat>, anisotropy: u8) -> Result<Self, ApplicationInitError> {
+		// Confirm which device and backend the request resolved to; a DeviceType::Cpu here
+		// means the machine silently fell back to a software rasterizer
+		let info = adapter.get_info();
+		log::info!("Using adapter '{}' ({:?}) on the {:?} backend", info.name, info.device_type, info.backend);
+
+		let anisotropy = clamp_anisotropy(anisotropy, max_supported_anisotropy(&adapter));
+		let (device, queue) = request_device_and_queue(&adapter, anisotropy > 1)?;
+
+		// The primary window's context: its surface, swap chain, GUI tree, and draw queue
+		// Without a surface this is a headless context rendering into an offscreen color target
+		let format = choose_surface_format(&supported_surface_formats(&adapter), surface_format);
+		let context = WindowContext::new(&device, surface, width, height, format);
+
+		// The frame is an explicit graph of passes: the scene clears and draws first, then the
+		// UI overlay draws on top without clearing; later passes (post effects) slot in between
+		let mut render_graph = RenderGraph::new();
+		render_graph.add_node(RenderNode {
+			name: String::from("main"),
+			dependencies: Vec::new(),
+			record: Box::new(|app, encoder, frame_view| app.record_scene_pass(encoder, frame_view)),
+		});
+		render_graph.add_node(RenderNode {
+			name: String::from("ui"),
+			dependencies: vec![String::from("main")],
+			record: Box::new(|app, encoder, frame_view| app.record_ui_pass(encoder, frame_view)),
+		});
+		render_graph.add_node(RenderNode {
+			name: String::from("immediate"),
+			dependencies: vec![String::from("ui")],
+			record: Box::new(|app, encoder, frame_view| app.record_immediate_pass(encoder, frame_view)),
+		});
+
+		// Resolved against the chosen surface format, since non-sRGB formats take the raw sRGB channels
+		let clear_color = ColorPalette::Background.get_color_for_format(Theme::default(), format);
+
+		Ok(Self {
+			adapter,
+			device,
+			queue,
+			windows: vec![context],
+			window_ids: HashMap::new(),
+			active_window: 0,
+			// Multisampling is off by default; set_msaa_sample_count enables it
+			sample_count: 1,
+			wireframe: false,
+			fullscreen: false,
+			// Timing is opt-in through set_gpu_timing; its readback stalls the frame slightly
+			gpu_timer: None,
+			anisotropy,
+			frame_stats: FrameStats::new(),
+			max_fps: None,
+			// Two frames in flight until a config or setter says otherwise; see set_frame_latency
+			frame_latency: 2,
+			last_render_time: std::time::Instant::now(),
+			render_graph: Some(render_graph),
+			shader_cache: ResourceCache::new(),
+			pipeline_cache: ResourceCache::new(),
+			pipeline_manager: PipelineManager::new(),
+			compute_pipeline_cache: ResourceCache::new(),
+			texture_cache: ResourceCache::new(),
+			fallback_texture: None,
+			pending_textures: Vec::new(),
+			cursor_position: None,
+			cursor_icon: winit::window::CursorIcon::Default,
+			modifiers: winit::event::ModifiersState::default(),
+			dropped_file: None,
+			file_hover: false,
+			buffer_pool: BufferPool::new(),
+			staging_belt: StagingBelt::new(),
+			clear_color,
+			theme: Theme::default(),
+			// Watching shader sources for edits is a development-time convenience only
+			hot_reload_enabled: cfg!(debug_assertions),
+			// Release builds skip the marker overhead; profiling sessions can flip this back on
+			debug_markers: cfg!(debug_assertions),
+			post_process: None,
+			// Neutral exposure; 2.2 matches the typical monitor the tonemapped image is encoded for
+			exposure: 1.,
+			gamma: 2.2,
+			clipboard: ClipboardProvider::new().ok(),
+			text_renderer: None,
+			shader_watcher: None,
+			pipeline_shaders: HashMap::new(),
+			on_render: std::cell::RefCell::new(None),
+		})
+	}
+
+	pub fn set_clear_color(&mut self, color: ColorPalette) {
+		self.clear_color = color.get_color_for_format(self.theme, self.context().swap_chain_descriptor.format);
+		self.mark_dirty();
+	}
+
+	// Switches between the light and dark appearance; the next redraw resolves all roles anew
+	pub fn set_theme(&mut self, theme: Theme) {
+		if theme == self.theme {
+			return;
+		}
+
+		self.theme = theme;
+		self.clear_color = ColorPalette::Background.get_color_for_format(theme, self.context().swap_chain_descriptor.format);
+		self.mark_dirty();
+	}
+
+	// Flags that the active window's visible state changed, so the next event loop pass redraws it
+	pub fn mark_dirty(&mut self) {
+		self.context_mut().dirty = true;
+	}
+
+	// Whether any window needs a redraw
+	pub fn is_dirty(&self) -> bool {
+		self.windows.iter().any(|context| context.dirty)
+	}
+
+	// Whether the window behind this id needs a redraw; unknown ids never do
+	pub fn window_is_dirty(&self, id: winit::window::WindowId) -> bool {
+		self.window_ids.get(&id).map(|&index| self.windows[index].dirty).unwrap_or(false)
+	}
+
+	// Records whether other windows fully cover the active one; while covered (or minimized) render
+	// skips its work, since nobody can see the frames it would produce
+	// This winit version delivers no occlusion events, so the report comes from embedders with
+	// their own signal; minimizes are still caught through the zero-size resize the OS reports
+	pub fn set_occluded(&mut self, occluded: bool) {
+		let was_paused = self.context().is_render_paused();
+		self.context_mut().occluded = occluded;
+		self.note_pause_transition(was_paused);
+	}
+
+	// Whether the active window's rendering is paused for being occluded or minimized
+	pub fn render_paused(&self) -> bool {
+		self.context().is_render_paused()
+	}
+
+	// Whether the window behind this id has rendering paused; unknown ids do not
+	pub fn window_is_render_paused(&self, id: winit::window::WindowId) -> bool {
+		self.window_ids.get(&id).map(|&index| self.windows[index].is_render_paused()).unwrap_or(false)
+	}
+
+	// Logs pause transitions and queues a redraw on resume, so changes made while hidden get shown
+	fn note_pause_transition(&mut self, was_paused: bool) {
+		let paused = self.context().is_render_paused();
+		if paused == was_paused {
+			return;
+		}
+		log::info!("Rendering {} for the active window", if paused { "paused" } else { "resumed" });
+		if !paused {
+			self.mark_dirty();
+		}
+	}
+
+	// Switches the active window's presentation strategy, e.g. Mailbox for lower latency while dragging
+	// The backend substitutes Fifo internally when a requested mode is unavailable, since Fifo is the only universally supported mode
+	pub fn set_present_mode(&mut self, mode: wgpu::PresentMode) {
+		if self.context().swap_chain_descriptor.present_mode == mode {
+			return;
+		}
+
+		self.context_mut().swap_chain_descriptor.present_mode = mode;
+		self.recreate_swap_chain();
+		self.mark_dirty();
+	}
+
+	// Rebuilds the active window's swap chain from its descriptor; a no-op when running headless
+	fn recreate_swap_chain(&mut self) {
+		let context = &mut self.windows[self.active_window];
+		context.recreate_swap_chain(&self.device);
+	}
+
+	// Cycles Fifo -> Mailbox -> Immediate -> Fifo, useful as a vsync toggle while testing
+	pub fn cycle_present_mode(&mut self) {
+		let next = match self.context().swap_chain_descriptor.present_mode {
+			wgpu::PresentMode::Fifo => wgpu::PresentMode::Mailbox,
+			wgpu::PresentMode::Mailbox => wgpu::PresentMode::Immediate,
+			wgpu::PresentMode::Immediate => wgpu::PresentMode::Fifo,
+		};
+		self.set_present_mode(next);
+	}
+
+	pub fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
+		// A minimized window reports zero dimensions, which the swap chain cannot be built from;
+		// rendering pauses until a restore delivers a real size
+		if new_size.width == 0 || new_size.height == 0 {
+			let was_paused = self.context().is_render_paused();
+			self.context_mut().minimized = true;
+			self.note_pause_transition(was_paused);
+			return;
+		}
+		let was_paused = self.context().is_render_paused();
+		self.context_mut().minimized = false;
+		self.note_pause_transition(was_paused);
+
+		// Recreate the active window's frame buffers at the new dimensions
+		let scene_format = self.scene_format();
+		{
+			let context = &mut self.windows[self.active_window];
+			context.swap_chain_descriptor.width = new_size.width;
+			context.swap_chain_descriptor.height = new_size.height;
+			context.recreate_swap_chain(&self.device);
+			context.recreate_offscreen_target(&self.device);
+
+			// The depth and multisample buffers must always match the swap chain dimensions
+			context.recreate_render_targets(&self.device, self.sample_count, scene_format);
+		}
+
+		// The GUI lays itself out against the window size, so a resize invalidates it
+		self.redraw_gui();
+	}
+
+	// Enables or disables multisample anti-aliasing; counts other than 1 and 4 are not portable across adapters
+	pub fn set_msaa_sample_count(&mut self, sample_count: u32) {
+		if !(sample_count == 1 || sample_count == 4) {
+			eprintln!("Unsupported MSAA sample count {}; only 1 and 4 are supported on all adapters", sample_count);
+			return;
+		}
+		if sample_count == self.sample_count {
+			return;
+		}
+
+		self.sample_count = sample_count;
+		// Every window's depth and multisample buffers bake in the sample count
+		let scene_format = self.scene_format();
+		for context in &mut self.windows {
+			context.recreate_render_targets(&self.device, sample_count, scene_format);
+			context.dirty = true;
+		}
+
+		// The sample count is baked into every pipeline, so rebuild them all
+		let names: Vec<_> = self.pipeline_shaders.keys().cloned().collect();
+		for name in names {
+			self.rebuild_pipeline(&name);
+		}
+		self.mark_dirty();
+	}
+
+	// Swaps every triangle pipeline to line-list topology for geometry debugging; replay then draws
+	// each command's edge list, derived from its triangle indices, instead of its filled triangles
+	pub fn set_wireframe(&mut self, on: bool) {
+		if on == self.wireframe {
+			return;
+		}
+
+		self.wireframe = on;
+		let names: Vec<_> = self.pipeline_shaders.keys().cloned().collect();
+		for name in names {
+			self.rebuild_pipeline(&name);
+		}
+		self.mark_dirty();
+	}
+
+	// Turns the tone-mapping post pass on or off. While on, the frame's passes render into an HDR
+	// color target and a full-screen tonemap pass writes it to the swap chain, so every cached
+	// pipeline and multisample buffer rebuilds against the HDR format
+	pub fn set_post_process(&mut self, enabled: bool) {
+		if enabled == self.post_process.is_some() {
+			return;
+		}
+
+		if enabled {
+			let descriptor = &self.windows[self.active_window].swap_chain_descriptor;
+			let (width, height, format) = (descriptor.width, descriptor.height, descriptor.format);
+			let post = match PostProcess::new(&self.device, width, height, format) {
+				Ok(post) => post,
+				Err(error) => {
+					eprintln!("Post-processing is unavailable: {}", error);
+					return;
+				}
+			};
+			post.write_settings(&self.device, &mut self.queue, self.exposure, self.gamma);
+			self.post_process = Some(post);
+		} else {
+			self.post_process = None;
+		}
+
+		// The scene's color format changed underneath every cached pipeline and MSAA buffer
+		// Keyed pipelines don't record their format, so the deduplicating cache restarts empty
+		let scene_format = self.scene_format();
+		for context in &mut self.windows {
+			context.recreate_render_targets(&self.device, self.sample_count, scene_format);
+			context.dirty = true;
+		}
+		self.pipeline_manager = PipelineManager::new();
+		let names: Vec<_> = self.pipeline_shaders.keys().cloned().collect();
+		for name in names {
+			self.rebuild_pipeline(&name);
+		}
+		self.mark_dirty();
+	}
+
+	pub fn post_process_enabled(&self) -> bool {
+		self.post_process.is_some()
+	}
+
+	// Turns pass-level GPU timing on or off. While on, each frame blocks briefly after its submit
+	// to measure how long the device takes to drain it; the result lands in
+	// frame_stats().last_gpu_pass_duration
+	pub fn set_gpu_timing(&mut self, enabled: bool) {
+		if !enabled {
+			self.gpu_timer = None;
+			return;
+		}
+		if self.gpu_timer.is_none() {
+			self.gpu_timer = Some(GpuTimer::new());
+		}
+	}
+
+	pub fn gpu_timing_enabled(&self) -> bool {
+		self.gpu_timer.is_some()
+	}
+
+	// Scales scene luminance before tone mapping; 1.0 is neutral
+	pub fn set_exposure(&mut self, exposure: f32) {
+		self.exposure = exposure;
+		self.write_post_settings();
+	}
+
+	// The display gamma the tonemapped image is encoded for; 2.2 suits typical monitors
+	pub fn set_gamma(&mut self, gamma: f32) {
+		self.gamma = gamma;
+		self.write_post_settings();
+	}
+
+	// Pushes the current tone-mapping settings to the stage's uniform buffer, when the stage is on
+	// The settings persist on the application either way, so they apply when it next turns on
+	fn write_post_settings(&mut self) {
+		if let Some(post) = &self.post_process {
+			post.write_settings(&self.device, &mut self.queue, self.exposure, self.gamma);
+		}
+		self.mark_dirty();
+	}
+
+	// The color format the frame's content passes render in: the swap chain's, unless the
+	// post-process stage is interposing its HDR target
+	fn scene_format(&self) -> wgpu::TextureFormat {
+		if self.post_process.is_some() {
+			crate::post_process::HDR_FORMAT
+		} else {
+			self.windows[self.active_window].swap_chain_descriptor.format
+		}
+	}
+
+	// Resolves a pipeline by structured key, deduplicating identical configurations across call sites
+	// The name-keyed pipeline_cache stays alongside this for pipelines that hot-reload via PipelineSource
+	pub fn pipeline_by_key(&mut self, key: PipelineKey) -> Option<&Pipeline> {
+		self.pipeline_manager.get_or_create(&self.device, self.scene_format(), key, &self.shader_cache, Vec::new())
+	}
+
+	// Rebuilds a cached pipeline from its recorded shaders and state
+	fn rebuild_pipeline(&mut self, name: &str) {
+		let source = match self.pipeline_shaders.get(name) {
+			Some(source) => source,
+			None => return,
+		};
+		let (vertex_shader, fragment_shader) = match (self.shader_cache.get(&source.vertex_shader_path), self.shader_cache.get(&source.fragment_shader_path)) {
+			(Some(vertex_shader), Some(fragment_shader)) => (vertex_shader, fragment_shader),
+			_ => return,
+		};
+
+		// Wireframe swaps triangle pipelines to line lists; replay pairs them with derived edge indices
+		let topology = if self.wireframe && wireframe_applies(source) { wgpu::PrimitiveTopology::LineList } else { source.topology };
+		let pipeline = match source.layout {
+			PipelineLayout::UniformOnly => Pipeline::new_colored(
+				&self.device,
+				self.scene_format(),
+				vertex_shader,
+				fragment_shader,
+				source.vertex_buffer_descriptor.clone(),
+				source.index_format,
+				source.blend_mode,
+				self.sample_count,
+				topology,
+			),
+			PipelineLayout::Sdf => Pipeline::new_sdf(&self.device, self.scene_format(), vertex_shader, fragment_shader, self.sample_count),
+			PipelineLayout::Texture => Pipeline::new(
+				&self.device,
+				self.scene_format(),
+				vertex_shader,
+				fragment_shader,
+				source.vertex_buffer_descriptor.clone(),
+				source.instance_buffer_descriptor.clone(),
+				source.index_format,
+				source.blend_mode,
+				self.sample_count,
+				topology,
+			),
+		};
+		self.pipeline_cache.set(name, pipeline);
+	}
+
+	// Starts loading a texture on a background thread; once decoded it appears in texture_cache under its path
+	pub fn load_texture_async(&mut self, path: &str) {
+		self.pending_textures.push(PendingTexture {
+			name: String::from(path),
+			future: Box::pin(Texture::load_async(path)),
+		});
+	}
+
+	// Reads a texture from the cache, loading it from disk on first use; a failed load logs the
+	// error and hands back the fallback checkerboard instead, so the editor keeps running with an
+	// obvious missing-texture indicator rather than crashing
+	// The failure leaves the cache unpopulated, so fixing the file on disk lets a later call load it
+	pub fn get_or_fallback(&mut self, path: &str) -> &Texture {
+		if self.texture_cache.get(path).is_none() {
+			match Texture::from_filepath(&self.device, &mut self.queue, path) {
+				Ok(texture) => self.texture_cache.set(path, texture),
+				Err(error) => {
+					log::warn!("Could not load texture '{}', substituting the fallback checkerboard: {}", path, error);
+					if self.fallback_texture.is_none() {
+						self.fallback_texture = Some(Texture::fallback(&self.device, &mut self.queue));
+					}
+				}
+			}
+		}
+		match self.texture_cache.get(path) {
+			Some(texture) => texture,
+			None => self.fallback_texture.as_ref().expect("The fallback texture was created just above"),
+		}
+	}
+
+	// Recognizes a BC-compressed DDS asset but cannot load it: this wgpu version exposes no
+	// compressed texture formats, so the blocks would have to be decompressed on the CPU, giving up
+	// the memory savings that justify shipping DDS in the first place. Parsing first keeps the
+	// error precise: a malformed file reports what is wrong with it before the unsupported
+	// compression does
+	pub fn load_dds(&mut self, path: &str) -> Result<(), crate::texture::TextureError> {
+		let bytes = std::fs::read(path).map_err(crate::texture::TextureError::Io)?;
+		crate::texture::parse_dds_header(&bytes)?;
+		Err(crate::texture::TextureError::CompressionUnsupported)
+	}
+
+	// Drains finished background decodes into the texture cache; decodes still running stay queued
+	fn poll_pending_textures(&mut self) {
+		if self.pending_textures.is_empty() {
+			return;
+		}
+
+		// The decode threads hold no waker, so poll with a no-op one; completion is observed on the next redraw
+		let waker = futures::task::noop_waker();
+		let mut context = std::task::Context::from_waker(&waker);
+
+		let mut still_pending = Vec::new();
+		for mut pending in std::mem::take(&mut self.pending_textures) {
+			match pending.future.as_mut().poll(&mut context) {
+				std::task::Poll::Ready(Ok(decoded)) => match Texture::finalize_upload(&self.device, &mut self.queue, decoded) {
+					Ok(texture) => {
+						self.texture_cache.set(&pending.name, texture);
+						self.mark_dirty();
+					}
+					Err(error) => eprintln!("Failed to upload texture {}: {}", pending.name, error),
+				},
+				std::task::Poll::Ready(Err(error)) => eprintln!("Failed to load texture {}: {}", pending.name, error),
+				std::task::Poll::Pending => still_pending.push(pending),
+			}
+		}
+		self.pending_textures = still_pending;
+	}
+
+	// Recomputes the active window's GUI layout, then regenerates its draw commands from it
+	pub fn redraw_gui(&mut self) {
+		// Pick up any texture decodes that finished since the last redraw
+		self.poll_pending_textures();
+
+		let viewport = self.logical_viewport();
+		self.windows[self.active_window].gui_tree.layout(viewport);
+
+		// The GUI's pipeline and texture are set up once by the example scene until asset loading is data driven
+		if self.pipeline_cache.get(crate::gui_tree::GUI_PIPELINE).is_none() {
+			self.example();
+		}
+
+		// A rebuild that never reached its commit leaves half-built commands behind; recycle them
+		// The front queue stays untouched, so frames keep showing the last complete rebuild
+		let abandoned = std::mem::take(&mut self.windows[self.active_window].pending_draw_commands);
+		for command in abandoned {
+			self.buffer_pool.reclaim(command);
+		}
+
+		let context = &mut self.windows[self.active_window];
+		let commands = context.gui_tree.build_draw_commands(&self.device, &mut self.queue, &mut self.buffer_pool, &self.pipeline_cache, &self.texture_cache, viewport);
+		context.pending_draw_commands.extend(commands);
+
+		// Button labels draw through the text renderer, over the quads built above
+		let labels: Vec<(String, Rect)> = context
+			.gui_tree
+			.paint_order()
+			.into_iter()
+			.filter_map(|id| context.gui_tree.get(id))
+			.filter(|node| node.visible)
+			.filter_map(|node| node.button.as_ref().and_then(|button| button.label.clone()).map(|label| (label, node.computed_bounds)))
+			.collect();
+		for (label, bounds) in labels {
+			// Left-aligned inside the button, with the baseline placed to roughly center the text
+			let baseline = bounds.y + (bounds.height + BUTTON_LABEL_SIZE * 0.7) / 2.;
+			self.draw_text(&label, bounds.x + BUTTON_LABEL_PADDING, baseline, BUTTON_LABEL_SIZE, ColorPalette::Text);
+		}
+
+		// Each color picker's gradient regions draw as vertex-colored quads over its node quad
+		let tree = &self.windows[self.active_window].gui_tree;
+		let pickers: Vec<(crate::gui_node::ColorPicker, Rect, i32)> = tree
+			.paint_order()
+			.into_iter()
+			.filter_map(|id| tree.get(id))
+			.filter(|node| node.visible)
+			.filter_map(|node| node.color_picker.clone().map(|picker| (picker, node.computed_bounds, node.z_index)))
+			.collect();
+		for (picker, bounds, z_index) in pickers {
+			self.draw_color_picker(&picker, bounds, z_index);
+		}
+
+		// Everything built: swap the queue in atomically and retire the one it replaces
+		let retired = self.windows[self.active_window].commit_draw_commands();
+		for command in retired {
+			self.buffer_pool.reclaim(command);
+		}
+
+		self.mark_dirty();
+	}
+
+	// TODO: Remove this temporary scene when draw commands are generated from the GUI tree
+	pub fn example(&mut self) {
+		// Compile the vertex and fragment shaders for the textured quad
+		let vertex_shader = shader_stage::compile_from_glsl(&self.device, "shaders/shader.vert", glsl_to_spirv::ShaderType::Vertex).unwrap_or_else(|error| panic!("{}", error));
+		let fragment_shader = shader_stage::compile_from_glsl(&self.device, "shaders/shader.frag", glsl_to_spirv::ShaderType::Fragment).unwrap_or_else(|error| panic!("{}", error));
+
+		// Build the render pipeline that draws with those shaders
+		let pipeline = Pipeline::new(
+			&self.device,
+			self.scene_format(),
+			&vertex_shader,
+			&fragment_shader,
+			Vertex2DTextured::buffer_descriptor(),
+			None,
+			wgpu::IndexFormat::Uint16,
+			BlendMode::Opaque,
+			self.sample_count,
+			wgpu::PrimitiveTopology::TriangleList,
+		);
+
+		// Load the example texture from disk, substituting the checkerboard if the file is missing
+		// or corrupt so a broken asset never takes the whole editor down
+		let texture = match Texture::from_filepath(&self.device, &mut self.queue, "textures/grid.png") {
+			Ok(texture) => texture,
+			Err(error) => {
+				eprintln!("Could not load texture 'textures/grid.png', substituting the fallback checkerboard: {}", error);
+				Texture::fallback(&self.device, &mut self.queue)
+			}
+		};
+
+		// Start with an identity transform; callers can update it per frame
+		let uniform_buffer = UniformBuffer::new(&self.device, uniform_buffer::IDENTITY);
+
+		// Bind the texture to the slots declared by the pipeline's bind group layout
+		let bind_group = pipeline.create_texture_bind_group(&self.device, &texture, &uniform_buffer);
+
+		// A quad that covers the middle of the window
+		const VERTICES: &[Vertex2DTextured] = &[
+			Vertex2DTextured { position: [-0.5, -0.5], uv: [0., 1.] },
+			Vertex2DTextured { position: [0.5, -0.5], uv: [1., 1.] },
+			Vertex2DTextured { position: [0.5, 0.5], uv: [1., 0.] },
+			Vertex2DTextured { position: [-0.5, 0.5], uv: [0., 0.] },
+		];
+		const INDICES: &[u16] = &[0, 1, 2, 2, 3, 0];
+
+		let mut draw_command = DrawCommand::new(&self.device, String::from("example"), VERTICES, INDICES, bind_group);
+		draw_command.uniform_buffer = Some(uniform_buffer);
+
+		self.shader_cache.set("shaders/shader.vert", vertex_shader);
+		self.shader_cache.set("shaders/shader.frag", fragment_shader);
+		self.pipeline_cache.set("example", pipeline);
+		self.pipeline_shaders.insert(
+			String::from("example"),
+			PipelineSource {
+				vertex_shader_path: String::from("shaders/shader.vert"),
+				fragment_shader_path: String::from("shaders/shader.frag"),
+				vertex_buffer_descriptor: Vertex2DTextured::buffer_descriptor(),
+				instance_buffer_descriptor: None,
+				index_format: wgpu::IndexFormat::Uint16,
+				blend_mode: BlendMode::Opaque,
+				topology: wgpu::PrimitiveTopology::TriangleList,
+				layout: PipelineLayout::Texture,
+			},
+		);
+		self.texture_cache.set("textures/grid.png", texture);
+		self.windows[self.active_window].pending_draw_commands.push(draw_command);
+
+		// A wireframe triangle demonstrating line strip topology alongside the filled quad
+		let line_pipeline = Pipeline::new(
+			&self.device,
+			self.scene_format(),
+			self.shader_cache.get("shaders/shader.vert").unwrap(),
+			self.shader_cache.get("shaders/shader.frag").unwrap(),
+			Vertex2DTextured::buffer_descriptor(),
+			None,
+			wgpu::IndexFormat::Uint16,
+			BlendMode::Opaque,
+			self.sample_count,
+			wgpu::PrimitiveTopology::LineStrip,
+		);
+
+		let line_uniform_buffer = UniformBuffer::new(&self.device, uniform_buffer::IDENTITY);
+		let line_texture = self.texture_cache.get("textures/grid.png").unwrap();
+		let line_bind_group = line_pipeline.create_texture_bind_group(&self.device, line_texture, &line_uniform_buffer);
+
+		const LINE_VERTICES: &[Vertex2DTextured] = &[
+			Vertex2DTextured { position: [-0.8, -0.8], uv: [0., 1.] },
+			Vertex2DTextured { position: [-0.6, -0.8], uv: [1., 1.] },
+			Vertex2DTextured { position: [-0.7, -0.6], uv: [0.5, 0.] },
+		];
+		const LINE_INDICES: &[u16] = &[0, 1, 2, 0];
+
+		let mut line_command = DrawCommand::new(&self.device, String::from("example_lines"), LINE_VERTICES, LINE_INDICES, line_bind_group);
+		line_command.uniform_buffer = Some(line_uniform_buffer);
+
+		self.pipeline_cache.set("example_lines", line_pipeline);
+		self.pipeline_shaders.insert(
+			String::from("example_lines"),
+			PipelineSource {
+				vertex_shader_path: String::from("shaders/shader.vert"),
+				fragment_shader_path: String::from("shaders/shader.frag"),
+				vertex_buffer_descriptor: Vertex2DTextured::buffer_descriptor(),
+				instance_buffer_descriptor: None,
+				index_format: wgpu::IndexFormat::Uint16,
+				blend_mode: BlendMode::Opaque,
+				topology: wgpu::PrimitiveTopology::LineStrip,
+				layout: PipelineLayout::Texture,
+			},
+		);
+		self.windows[self.active_window].pending_draw_commands.push(line_command);
+
+		self.watch_shader("shaders/shader.vert");
+		self.watch_shader("shaders/shader.frag");
+
+		self.mark_dirty();
+	}
+
+	// A triangle fading red to green to blue, demonstrating per-vertex colors with no texture bound
+	pub fn example_gradient(&mut self) {
+		let vertex_shader = shader_stage::compile_from_glsl(&self.device, "shaders/color.vert", glsl_to_spirv::ShaderType::Vertex).unwrap_or_else(|error| panic!("{}", error));
+		let fragment_shader = shader_stage::compile_from_glsl(&self.device, "shaders/color.frag", glsl_to_spirv::ShaderType::Fragment).unwrap_or_else(|error| panic!("{}", error));
+
+		let pipeline = Pipeline::new_colored(
+			&self.device,
+			self.scene_format(),
+			&vertex_shader,
+			&fragment_shader,
+			Vertex2DColored::buffer_descriptor(),
+			wgpu::IndexFormat::Uint16,
+			BlendMode::Opaque,
+			self.sample_count,
+			wgpu::PrimitiveTopology::TriangleList,
+		);
+
+		let uniform_buffer = UniformBuffer::new(&self.device, uniform_buffer::IDENTITY);
+		let bind_group = pipeline.create_uniform_bind_group(&self.device, &uniform_buffer);
+
+		const VERTICES: &[Vertex2DColored] = &[
+			Vertex2DColored { position: [0.5, -0.5], color: [1., 0., 0., 1.] },
+			Vertex2DColored { position: [0.9, -0.5], color: [0., 1., 0., 1.] },
+			Vertex2DColored { position: [0.7, -0.1], color: [0., 0., 1., 1.] },
+		];
+		const INDICES: &[u16] = &[0, 1, 2];
+
+		let mut draw_command = DrawCommand::new(&self.device, String::from("example_gradient"), VERTICES, INDICES, bind_group);
+		draw_command.uniform_buffer = Some(uniform_buffer);
+
+		self.shader_cache.set("shaders/color.vert", vertex_shader);
+		self.shader_cache.set("shaders/color.frag", fragment_shader);
+		self.pipeline_cache.set("example_gradient", pipeline);
+		self.pipeline_shaders.insert(
+			String::from("example_gradient"),
+			PipelineSource {
+				vertex_shader_path: String::from("shaders/color.vert"),
+				fragment_shader_path: String::from("shaders/color.frag"),
+				vertex_buffer_descriptor: Vertex2DColored::buffer_descriptor(),
+				instance_buffer_descriptor: None,
+				index_format: wgpu::IndexFormat::Uint16,
+				blend_mode: BlendMode::Opaque,
+				topology: wgpu::PrimitiveTopology::TriangleList,
+				layout: PipelineLayout::UniformOnly,
+			},
+		);
+		self.windows[self.active_window].pending_draw_commands.push(draw_command);
+
+		self.watch_shader("shaders/color.vert");
+		self.watch_shader("shaders/color.frag");
+
+		self.mark_dirty();
+	}
+
+	// Queues a color picker's gradient regions as vertex-colored quads one layer above its node
+	// Corner colors interpolate linearly across each quad: close enough for picking, though not
+	// colorimetrically exact
+	// Compiles and caches the vertex-colored GUI pipeline on first use; false when its shaders fail
+	// to compile, in which case vertex-colored drawing stays unavailable until they are fixed
+	fn ensure_colored_pipeline(&mut self) -> bool {
+		if self.pipeline_cache.get(GUI_COLORED_PIPELINE).is_some() {
+			return true;
+		}
+
+		let (vertex_shader, fragment_shader) = match (
+			shader_stage::compile_from_glsl(&self.device, "shaders/color.vert", glsl_to_spirv::ShaderType::Vertex),
+			shader_stage::compile_from_glsl(&self.device, "shaders/color.frag", glsl_to_spirv::ShaderType::Fragment),
+		) {
+			(Ok(vertex_shader), Ok(fragment_shader)) => (vertex_shader, fragment_shader),
+			(Err(error), _) | (_, Err(error)) => {
+				eprintln!("Vertex-colored drawing is unavailable: {}", error);
+				return false;
+			}
+		};
+		let pipeline = Pipeline::new_colored(
+			&self.device,
+			self.scene_format(),
+			&vertex_shader,
+			&fragment_shader,
+			Vertex2DColored::buffer_descriptor(),
+			wgpu::IndexFormat::Uint16,
+			BlendMode::Opaque,
+			self.sample_count,
+			wgpu::PrimitiveTopology::TriangleList,
+		);
+		self.shader_cache.set("shaders/color.vert", vertex_shader);
+		self.shader_cache.set("shaders/color.frag", fragment_shader);
+		self.pipeline_cache.set(GUI_COLORED_PIPELINE, pipeline);
+		self.pipeline_shaders.insert(
+			String::from(GUI_COLORED_PIPELINE),
+			PipelineSource {
+				vertex_shader_path: String::from("shaders/color.vert"),
+				fragment_shader_path: String::from("shaders/color.frag"),
+				vertex_buffer_descriptor: Vertex2DColored::buffer_descriptor(),
+				instance_buffer_descriptor: None,
+				index_format: wgpu::IndexFormat::Uint16,
+				blend_mode: BlendMode::Opaque,
+				topology: wgpu::PrimitiveTopology::TriangleList,
+				layout: PipelineLayout::UniformOnly,
+			},
+		);
+		self.watch_shader("shaders/color.vert");
+		self.watch_shader("shaders/color.frag");
+		true
+	}
+
+	fn draw_color_picker(&mut self, picker: &crate::gui_node::ColorPicker, bounds: Rect, z_index: i32) {
+		// The gradients draw with the shared per-vertex color pipeline, compiled on first use
+		if !self.ensure_colored_pipeline() {
+			return;
+		}
+
+		let color = |hue: f32, saturation: f32, value: f32| -> [f32; 4] {
+			let (red, green, blue) = crate::color_palette::hsv_to_rgb(hue, saturation, value);
+			[red as f32 / 255., green as f32 / 255., blue as f32 / 255., 1.]
+		};
+
+		// The square fades white-to-hue along its top edge and everything-to-black towards its bottom
+		// Corner order matches to_ndc_vertices: bottom left, bottom right, top right, top left
+		let square = crate::gui_tree::color_picker_sv_rect(bounds);
+		let black = [0., 0., 0., 1.];
+		let white = [1., 1., 1., 1.];
+		let mut quads = vec![(square, [black, black, color(picker.hue, 1., 1.), white])];
+
+		// The bar runs through the hue circle top to bottom, one quad per 60-degree sextant so the
+		// linear interpolation lands exactly on every primary and secondary along the way
+		let bar = crate::gui_tree::color_picker_hue_rect(bounds);
+		for segment in 0..6 {
+			let top = segment as f32 / 6.;
+			let bottom = (segment + 1) as f32 / 6.;
+			let rect = Rect::new(bar.x, bar.y + top * bar.height, bar.width, (bottom - top) * bar.height);
+			let top_color = color(top * 360., 1., 1.);
+			let bottom_color = color(bottom * 360., 1., 1.);
+			quads.push((rect, [bottom_color, bottom_color, top_color, top_color]));
+		}
+
+		let viewport = self.logical_viewport();
+		let pipeline = match self.pipeline_cache.get(GUI_COLORED_PIPELINE) {
+			Some(pipeline) => pipeline,
+			None => return,
+		};
+		for (rect, corner_colors) in quads {
+			let corners = rect.to_ndc_vertices(viewport);
+			let vertices: Vec<Vertex2DColored> = corners.iter().zip(corner_colors.iter()).map(|(&position, &color)| Vertex2DColored { position, color }).collect();
+			const INDICES: &[u16] = &[0, 1, 2, 2, 3, 0];
+
+			let mut transform = uniform_buffer::IDENTITY;
+			transform[3][2] = crate::gui_tree::z_to_depth(z_index + 1);
+			let uniform_buffer = UniformBuffer::new(&self.device, transform);
+			let bind_group = pipeline.create_uniform_bind_group(&self.device, &uniform_buffer);
+
+			let mut command = DrawCommand::new_pooled(&self.device, &mut self.queue, &mut self.buffer_pool, String::from(GUI_COLORED_PIPELINE), &vertices, INDICES, bind_group);
+			command.uniform_buffer = Some(uniform_buffer);
+			command.scissor = Some(bounds);
+			self.windows[self.active_window].pending_draw_commands.push(command);
+		}
+	}
+
+	// Queues a `width`-thick line from `a` to `b` on this frame's immediate-mode overlay
+	//
+	// The overlay is for debug shapes and tooling: coordinates are logical pixels, shapes draw over
+	// the whole retained GUI, and everything queued lasts exactly one frame, so callers re-queue
+	// their shapes every frame instead of building tree nodes
+	pub fn draw_line(&mut self, a: (f32, f32), b: (f32, f32), color: ColorPalette, width: f32) {
+		const INDICES: &[u16] = &[0, 1, 2, 2, 3, 0];
+		self.push_overlay_command(&line_quad_corners(a, b, width), INDICES, color);
+	}
+
+	// Queues a filled rectangle on this frame's immediate-mode overlay, in logical pixels
+	pub fn draw_rect(&mut self, rect: Rect, color: ColorPalette) {
+		// Corner order matches to_ndc_vertices: bottom left, bottom right, top right, top left
+		let corners = [
+			[rect.x, rect.y + rect.height],
+			[rect.x + rect.width, rect.y + rect.height],
+			[rect.x + rect.width, rect.y],
+			[rect.x, rect.y],
+		];
+		const INDICES: &[u16] = &[0, 1, 2, 2, 3, 0];
+		self.push_overlay_command(&corners, INDICES, color);
+	}
+
+	// Queues a filled circle on this frame's immediate-mode overlay, centered in logical pixels
+	pub fn draw_circle(&mut self, center: (f32, f32), radius: f32, color: ColorPalette) {
+		let segments = circle_segments(radius);
+		self.push_overlay_command(&circle_positions(center, radius, segments), &circle_indices(segments), color);
+	}
+
+	// Queues an anti-aliased rounded rectangle on this frame's immediate-mode overlay
+	// The corners render from a signed distance field rather than tessellated geometry, so the
+	// rounding stays crisp at any size without multisampling
+	pub fn draw_rounded_rect(&mut self, rect: Rect, corner_radius: f32, color: ColorPalette) {
+		if !self.ensure_sdf_pipeline() {
+			return;
+		}
+
+		let color = vertex_color(color, self.theme);
+		let viewport = self.logical_viewport();
+		let pipeline = match self.pipeline_cache.get(SDF_PIPELINE) {
+			Some(pipeline) => pipeline,
+			None => return,
+		};
+		let command = DrawCommand::rounded_rect(&self.device, String::from(SDF_PIPELINE), pipeline, rect, corner_radius, color, viewport);
+		self.windows[self.active_window].overlay_draw_commands.push(command);
+		self.mark_dirty();
+	}
+
+	// Compiles and caches the SDF shape pipeline on first use; false when its shaders fail to
+	// compile, in which case SDF shapes stay unavailable until they are fixed
+	fn ensure_sdf_pipeline(&mut self) -> bool {
+		if self.pipeline_cache.get(SDF_PIPELINE).is_some() {
+			return true;
+		}
+
+		let (vertex_shader, fragment_shader) = match (
+			shader_stage::compile_from_glsl(&self.device, "shaders/sdf.vert", glsl_to_spirv::ShaderType::Vertex),
+			shader_stage::compile_from_glsl(&self.device, "shaders/sdf.frag", glsl_to_spirv::ShaderType::Fragment),
+		) {
+			(Ok(vertex_shader), Ok(fragment_shader)) => (vertex_shader, fragment_shader),
+			(Err(error), _) | (_, Err(error)) => {
+				eprintln!("SDF shape drawing is unavailable: {}", error);
+				return false;
+			}
+		};
+		let pipeline = Pipeline::new_sdf(&self.device, self.scene_format(), &vertex_shader, &fragment_shader, self.sample_count);
+		self.shader_cache.set("shaders/sdf.vert", vertex_shader);
+		self.shader_cache.set("shaders/sdf.frag", fragment_shader);
+		self.pipeline_cache.set(SDF_PIPELINE, pipeline);
+		self.pipeline_shaders.insert(
+			String::from(SDF_PIPELINE),
+			PipelineSource {
+				vertex_shader_path: String::from("shaders/sdf.vert"),
+				fragment_shader_path: String::from("shaders/sdf.frag"),
+				vertex_buffer_descriptor: Vertex2DTextured::buffer_descriptor(),
+				instance_buffer_descriptor: None,
+				index_format: wgpu::IndexFormat::Uint16,
+				blend_mode: BlendMode::AlphaBlend,
+				topology: wgpu::PrimitiveTopology::TriangleList,
+				layout: PipelineLayout::Sdf,
+			},
+		);
+		self.watch_shader("shaders/sdf.vert");
+		self.watch_shader("shaders/sdf.frag");
+		true
+	}
+
+	// Builds one vertex-colored command from logical-pixel positions and queues it on the overlay
+	fn push_overlay_command(&mut self, positions: &[[f32; 2]], indices: &[u16], color: ColorPalette) {
+		if !self.ensure_colored_pipeline() {
+			return;
+		}
+
+		let color = vertex_color(color, self.theme);
+		let viewport = self.logical_viewport();
+		let vertices: Vec<Vertex2DColored> = positions
+			.iter()
+			.map(|&[x, y]| Vertex2DColored {
+				position: crate::geometry::viewport_to_ndc(x, y, viewport),
+				color,
+			})
+			.collect();
+
+		// The overlay pass clears depth before it draws, so the identity transform's z = 0 is fine
+		let uniform_buffer = UniformBuffer::new(&self.device, uniform_buffer::IDENTITY);
+		let pipeline = match self.pipeline_cache.get(GUI_COLORED_PIPELINE) {
+			Some(pipeline) => pipeline,
+			None => return,
+		};
+		let bind_group = pipeline.create_uniform_bind_group(&self.device, &uniform_buffer);
+
+		let mut command = DrawCommand::new_pooled(&self.device, &mut self.queue, &mut self.buffer_pool, String::from(GUI_COLORED_PIPELINE), &vertices, indices, bind_group);
+		command.uniform_buffer = Some(uniform_buffer);
+		self.windows[self.active_window].overlay_draw_commands.push(command);
+		self.mark_dirty();
+	}
+
+	// Queues glyph quads drawing the string with its baseline starting at (x, y) in logical pixels
+	// TODO: Tint by `color` once the GUI shader takes a color input rather than sampling coverage alone
+	pub fn draw_text(&mut self, text: &str, x: f32, y: f32, size: f32, _color: ColorPalette) {
+		// Load the font lazily on first use; without one, text drawing stays silently disabled
+		if self.text_renderer.is_none() {
+			match TextRenderer::from_file(&self.device, FONT_PATH) {
+				Ok(renderer) => self.text_renderer = Some(renderer),
+				Err(error) => {
+					eprintln!("Text rendering is unavailable: {}", error);
+					return;
+				}
+			}
+		}
+
+		// Text reuses the GUI shaders but blends, since glyph coverage lives in the alpha channel
+		if self.pipeline_cache.get(TEXT_PIPELINE).is_none() {
+			let (vertex_shader, fragment_shader) = match (self.shader_cache.get("shaders/shader.vert"), self.shader_cache.get("shaders/shader.frag")) {
+				(Some(vertex_shader), Some(fragment_shader)) => (vertex_shader, fragment_shader),
+				_ => {
+					eprintln!("Text drawing requires the GUI shaders to be compiled first");
+					return;
+				}
+			};
+			let pipeline = Pipeline::new(
+				&self.device,
+				self.scene_format(),
+				vertex_shader,
+				fragment_shader,
+				Vertex2DTextured::buffer_descriptor(),
+				None,
+				wgpu::IndexFormat::Uint16,
+				BlendMode::AlphaBlend,
+				self.sample_count,
+				wgpu::PrimitiveTopology::TriangleList,
+			);
+			self.pipeline_cache.set(TEXT_PIPELINE, pipeline);
+			self.pipeline_shaders.insert(
+				String::from(TEXT_PIPELINE),
+				PipelineSource {
+					vertex_shader_path: String::from("shaders/shader.vert"),
+					fragment_shader_path: String::from("shaders/shader.frag"),
+					vertex_buffer_descriptor: Vertex2DTextured::buffer_descriptor(),
+					instance_buffer_descriptor: None,
+					index_format: wgpu::IndexFormat::Uint16,
+					blend_mode: BlendMode::AlphaBlend,
+					topology: wgpu::PrimitiveTopology::TriangleList,
+					layout: PipelineLayout::Texture,
+				},
+			);
+		}
+
+		let quads = match self.text_renderer.as_mut().unwrap().layout(&self.device, &mut self.queue, text, x, y, size) {
+			Ok(quads) => quads,
+			Err(error) => {
+				eprintln!("Failed to lay out text: {}", error);
+				return;
+			}
+		};
+		if quads.is_empty() {
+			return;
+		}
+
+		// One quad per glyph, batched into a single draw command sampling the atlas
+		let viewport = self.logical_viewport();
+		let mut vertices = Vec::with_capacity(quads.len() * 4);
+		let mut indices: Vec<u16> = Vec::with_capacity(quads.len() * 6);
+		for quad in &quads {
+			let corners = quad.rect.to_ndc_vertices(viewport);
+			// Corner order is bottom-left first, so v runs from the glyph's bottom (uv_max) upward
+			let uvs = [
+				[quad.uv_min[0], quad.uv_max[1]],
+				[quad.uv_max[0], quad.uv_max[1]],
+				[quad.uv_max[0], quad.uv_min[1]],
+				[quad.uv_min[0], quad.uv_min[1]],
+			];
+			let base = vertices.len() as u16;
+			for (&position, &uv) in corners.iter().zip(uvs.iter()) {
+				vertices.push(Vertex2DTextured { position, uv });
+			}
+			indices.extend([0u16, 1, 2, 2, 3, 0].iter().map(|index| base + index));
+		}
+
+		let uniform_buffer = UniformBuffer::new(&self.device, uniform_buffer::IDENTITY);
+		let pipeline = self.pipeline_cache.get(TEXT_PIPELINE).unwrap();
+		let atlas = &self.text_renderer.as_ref().unwrap().atlas;
+		let bind_group = pipeline.create_texture_bind_group(&self.device, atlas, &uniform_buffer);
+
+		let mut command = DrawCommand::new(&self.device, String::from(TEXT_PIPELINE), &vertices, &indices, bind_group);
+		command.uniform_buffer = Some(uniform_buffer);
+		self.windows[self.active_window].pending_draw_commands.push(command);
+		self.mark_dirty();
+	}
+
+	// Start watching a cached shader's source file for edits, if hot reloading is enabled
+	fn watch_shader(&mut self, path: &str) {
+		if !self.hot_reload_enabled {
+			return;
+		}
+
+		if self.shader_watcher.is_none() {
+			match ShaderWatcher::new() {
+				Ok(watcher) => self.shader_watcher = Some(watcher),
+				Err(error) => {
+					eprintln!("Failed to start the shader hot-reload watcher: {}", error);
+					self.hot_reload_enabled = false;
+					return;
+				}
+			}
+		}
+
+		if let Some(watcher) = &mut self.shader_watcher {
+			if let Err(error) = watcher.watch(path) {
+				eprintln!("Failed to watch shader '{}' for hot reloading: {}", path, error);
+			}
+		}
+	}
+
+	// Recompile any watched shaders that changed on disk and rebuild the pipelines built from them
+	pub fn poll_shader_changes(&mut self) {
+		let changed = match &self.shader_watcher {
+			Some(watcher) if self.hot_reload_enabled => watcher.poll_changed(),
+			_ => return,
+		};
+
+		for path in changed {
+			let shader_type = if path.ends_with(".vert") { glsl_to_spirv::ShaderType::Vertex } else { glsl_to_spirv::ShaderType::Fragment };
+
+			// On a compile error, keep the old module so a typo mid-edit doesn't take down the app
+			match shader_stage::compile_from_glsl(&self.device, &path, shader_type) {
+				Ok(module) => self.shader_cache.set(&path, module),
+				Err(error) => {
+					eprintln!("Hot reload failed to compile '{}': {}", path, error);
+					continue;
+				}
+			}
+
+			// Rebuild every pipeline that was built from the edited shader
+			let dependents: Vec<_> = self
+				.pipeline_shaders
+				.iter()
+				.filter(|(_, source)| source.vertex_shader_path == path || source.fragment_shader_path == path)
+				.map(|(name, _)| name.clone())
+				.collect();
+			for name in dependents {
+				self.rebuild_pipeline(&name);
+			}
+			self.mark_dirty();
+		}
+	}
+
+	// Reads text from the OS clipboard; None when no provider is available or it holds no text
+	pub fn get_clipboard_text(&mut self) -> Option<String> {
+		self.clipboard.as_mut().and_then(|clipboard| clipboard.get_contents().ok())
+	}
+
+	// Writes text to the OS clipboard, reporting rather than panicking when no provider exists
+	pub fn set_clipboard_text(&mut self, text: &str) -> Result<(), Box<dyn std::error::Error>> {
+		match self.clipboard.as_mut() {
+			Some(clipboard) => clipboard.set_contents(String::from(text)),
+			None => Err("No clipboard provider is available".into()),
+		}
+	}
+
+	// Writes the window's size and position to disk so the next launch can restore them
+	// Failures only cost the remembered geometry, so they report instead of propagating
+	pub fn save_window_state(window: &Window, path: &str) {
+		let size = window.inner_size();
+		// Some platforms (e.g. Wayland) never expose window positions; fall back to the origin
+		let position = window.outer_position().unwrap_or_else(|_| winit::dpi::PhysicalPosition::new(0, 0));
+		let state = WindowState {
+			width: size.width,
+			height: size.height,
+			x: position.x,
+			y: position.y,
+		};
+		if let Err(error) = std::fs::write(path, state.to_json()) {
+			eprintln!("Failed to save the window state: {}", error);
+		}
+	}
+
+	// Reads the geometry a previous session saved; None when there is none or it fails to parse
+	pub fn load_window_state(path: &str) -> Option<WindowState> {
+		WindowState::from_json(&std::fs::read_to_string(path).ok()?)
+	}
+
+	// Stages new vertex data through the belt and records a copy of it into `buffer`
+	// The copy happens when the encoder is submitted; render() finishes and recalls the belt around that submit
+	pub fn upload_vertices(&mut self, encoder: &mut wgpu::CommandEncoder, buffer: &wgpu::Buffer, data: &[u8]) {
+		self.staging_belt.write_buffer(&self.device, encoder, buffer, 0, data);
+	}
+
+	// Runs a cached compute pipeline immediately with the given workgroup counts
+	pub fn dispatch_compute(&mut self, name: &str, bind_group: &wgpu::BindGroup, workgroups: [u32; 3]) {
+		let pipeline = self.compute_pipeline_cache.get(name).expect("Compute dispatch references an uncached compute pipeline");
+
+		let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+		{
+			let mut compute_pass = encoder.begin_compute_pass();
+			compute_pass.set_pipeline(&pipeline.compute_pipeline);
+			compute_pass.set_bind_group(0, bind_group, &[]);
+			compute_pass.dispatch(workgroups[0], workgroups[1], workgroups[2]);
+		}
+		self.queue.submit(&[encoder.finish()]);
+	}
+
+	pub fn frame_stats(&self) -> &FrameStats {
+		&self.frame_stats
+	}
+
+	pub fn fps(&self) -> f32 {
+		self.frame_stats.fps()
+	}
+
+	// Restricts how often dirty frames redraw, easing GPU and power use on high-refresh displays
+	// None removes the cap; zero would never draw again, so it is treated as uncapped too
+	pub fn set_max_fps(&mut self, fps: Option<u32>) {
+		self.max_fps = fps.filter(|&fps| fps > 0);
+	}
+
+	// When the frame cap next allows a redraw; None means the next frame may draw immediately
+	pub fn next_frame_deadline(&self) -> Option<std::time::Instant> {
+		frame_deadline(self.last_render_time, std::time::Instant::now(), self.max_fps)
+	}
+
+	// Caps how many frames may be queued ahead of the display, trading throughput for input latency
+	// A cap of one serializes frames entirely and prefers the Mailbox present mode, since Fifo
+	// queues a full vsync interval of latency that no amount of submission pacing can remove
+	// (set_present_mode falls back to Fifo internally where Mailbox is unsupported)
+	pub fn set_frame_latency(&mut self, latency: u32) {
+		// Zero frames in flight could never make progress; one is the lowest meaningful cap
+		self.frame_latency = latency.max(1);
+		if self.frame_latency == 1 && self.context().surface.is_some() {
+			self.set_present_mode(wgpu::PresentMode::Mailbox);
+		}
+	}
+
+	pub fn frame_latency(&self) -> u32 {
+		self.frame_latency
+	}
+
+	// Renders the active window's frame; window_event points this at the window whose redraw fired
+	pub fn render(&mut self) {
+		// A paused window's frames would never be seen; the skipped work waits, still dirty, for the restore
+		if self.windows[self.active_window].is_render_paused() {
+			return;
+		}
+
+		self.frame_stats.begin_frame();
+		self.last_render_time = std::time::Instant::now();
+
+		// Headless contexts have no swap chain; the frame goes into the offscreen target instead
+		if self.windows[self.active_window].swap_chain.is_none() {
+			if let Some(target) = self.windows[self.active_window].take_offscreen_target() {
+				// The target persists across frames, so partial redraws can reuse its previous contents
+				self.render_to_texture(&target, true);
+				self.windows[self.active_window].return_offscreen_target(target);
+			}
+			self.drain_overlay_commands();
+			self.windows[self.active_window].dirty = false;
+			return;
+		}
+
+		// With a frame latency cap of one, wait out the previous submission before acquiring the
+		// next frame buffer, so at most one frame is ever in flight and the input sampled for this
+		// frame reflects the latest state. With nothing in flight the wait returns immediately
+		if self.frame_latency == 1 {
+			self.device.poll(wgpu::Maintain::Wait);
+		}
+
+		// Get the next frame buffer in the swap chain to render onto
+		// Repeated timeouts escalate to a swap chain rebuild, then to device recovery
+		let frame = match self.windows[self.active_window].swap_chain.as_mut().unwrap().get_next_texture() {
+			Ok(frame) => frame,
+			Err(wgpu::TimeOut) => match acquisition_strategy(self.windows[self.active_window].acquisition_failures) {
+				AcquisitionStrategy::RecreateAndRetry => {
+					self.recreate_swap_chain();
+					match self.windows[self.active_window].swap_chain.as_mut().unwrap().get_next_texture() {
+						Ok(frame) => frame,
+						Err(wgpu::TimeOut) => {
+							eprintln!("Skipping frame: swap chain still timing out after recreation");
+							// A fresh swap chain that still cannot produce frames points at the device, not the surface
+							self.note_acquisition_failure();
+							return;
+						}
+					}
+				}
+				AcquisitionStrategy::SkipFrame => {
+					eprintln!("Skipping frame: timed out acquiring the next frame buffer");
+					self.note_acquisition_failure();
+					return;
+				}
+			},
+		};
+		self.windows[self.active_window].acquisition_failures = 0;
+
+		// Swap chain images rotate between frames, so previous contents cannot be selectively kept;
+		// drain the dirty region for consistent accounting and redraw the window frame in full
+		self.windows[self.active_window].gui_tree.take_dirty_region();
+
+		// Record the frame's passes in the order the render graph resolves
+		// The frame counter in the label tells consecutive frames apart in GPU captures
+		self.flush_push_constant_fallbacks();
+		self.ensure_wireframe_indices();
+
+		// The HDR target tracks the swap chain's dimensions, rebuilding lazily after resizes
+		let (width, height) = (self.windows[self.active_window].swap_chain_descriptor.width, self.windows[self.active_window].swap_chain_descriptor.height);
+		let device = &self.device;
+		if let Some(post) = self.post_process.as_mut() {
+			post.resize(device, width, height);
+		}
+
+		let label = format!("Frame {} Encoder", self.frame_stats.frame_count);
+		let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some(&label) });
+
+		let render_graph = self.render_graph.take().expect("The render graph is only taken for the duration of a frame");
+		match &self.post_process {
+			// The frame's passes render into the HDR target, then the tonemap pass writes the frame
+			Some(post) => {
+				render_graph.execute(self, &mut encoder, &post.target.view);
+				post.record(&mut encoder, &frame.view);
+			}
+			None => render_graph.execute(self, &mut encoder, &frame.view),
+		}
+		self.render_graph = Some(render_graph);
+
+		// Submit the render pass commands to the GPU, cycling any staged uploads through the belt
+		self.staging_belt.finish();
+		self.queue.submit(&[encoder.finish()]);
+		self.staging_belt.recall();
+
+		// With timing on, wait out the frame right away and record the drain in the frame counters
+		if let Some(timer) = &self.gpu_timer {
+			if let Some(duration) = timer.read_duration(&self.device) {
+				self.frame_stats.record_gpu_pass(duration);
+			}
+		}
+
+		// Everything visible in this window is now up to date
+		self.drain_overlay_commands();
+		self.windows[self.active_window].dirty = false;
+	}
+
+	// Immediate-mode shapes last exactly one frame; once it has been submitted, recycle their buffers
+	fn drain_overlay_commands(&mut self) {
+		let overlay = std::mem::take(&mut self.windows[self.active_window].overlay_draw_commands);
+		for command in overlay {
+			self.buffer_pool.reclaim(command);
+		}
+	}
+
+	// Counts a failed frame acquisition; enough of them in a row means the device is lost, not the surface
+	fn note_acquisition_failure(&mut self) {
+		self.windows[self.active_window].acquisition_failures += 1;
+		let failures = self.windows[self.active_window].acquisition_failures;
+		if failures < MAX_ACQUISITION_FAILURES {
+			return;
+		}
+
+		eprintln!("The GPU device appears lost after {} failed frames; rebuilding it", failures);
+		if let Err(error) = self.recover_device() {
+			eprintln!("Device recovery failed: {}", error);
+		}
+		self.windows[self.active_window].acquisition_failures = 0;
+	}
+
+	// Rebuilds the logical device and every resource living on it after a GPU reset (driver update, TDR)
+	// Shaders and textures reload from the source paths their cache keys record, pipelines rebuild from
+	// their PipelineSource records, and everything transient is recreated empty and refilled on redraw
+	pub fn recover_device(&mut self) -> Result<(), ApplicationInitError> {
+		let (device, queue) = request_device_and_queue(&self.adapter, self.anisotropy > 1)?;
+		self.device = device;
+		self.queue = queue;
+
+		// The tone-mapping stage's target, pipeline, and settings all belonged to the old device
+		if self.post_process.is_some() {
+			let descriptor = &self.windows[self.active_window].swap_chain_descriptor;
+			let (width, height, format) = (descriptor.width, descriptor.height, descriptor.format);
+			match PostProcess::new(&self.device, width, height, format) {
+				Ok(post) => {
+					post.write_settings(&self.device, &mut self.queue, self.exposure, self.gamma);
+					self.post_process = Some(post);
+				}
+				Err(error) => {
+					eprintln!("Could not rebuild the post-process stage after device recovery: {}", error);
+					self.post_process = None;
+				}
+			}
+		}
+
+		// The timer's query set and buffers likewise; rebuild it on the fresh device when timing is on
+		if self.gpu_timer.is_some() {
+			self.gpu_timer = Some(GpuTimer::new());
+		}
+
+		// Frame buffers and per-frame scratch allocations all belonged to the old device
+		let scene_format = self.scene_format();
+		for context in &mut self.windows {
+			context.recreate_swap_chain(&self.device);
+			context.recreate_offscreen_target(&self.device);
+			context.recreate_render_targets(&self.device, self.sample_count, scene_format);
+			context.draw_command_queue.clear();
+			context.pending_draw_commands.clear();
+			context.overlay_draw_commands.clear();
+		}
+		self.staging_belt = StagingBelt::new();
+		self.buffer_pool = BufferPool::new();
+
+		// Recompile every cached shader from the source path its cache key records
+		for path in self.shader_cache.keys() {
+			let shader_type = if path.ends_with(".vert") { glsl_to_spirv::ShaderType::Vertex } else { glsl_to_spirv::ShaderType::Fragment };
+			match shader_stage::compile_from_glsl(&self.device, &path, shader_type) {
+				Ok(module) => self.shader_cache.set(&path, module),
+				Err(error) => {
+					eprintln!("Could not recompile shader '{}' after device recovery: {}", path, error);
+					self.shader_cache.remove(&path);
+				}
+			}
+		}
+
+		// Re-upload every texture from disk; entries without a file behind their key are dropped
+		// and their owners must rebuild them, the same as after a cold start
+		for path in self.texture_cache.keys() {
+			match Texture::from_filepath(&self.device, &mut self.queue, &path) {
+				Ok(texture) => self.texture_cache.set(&path, texture),
+				Err(error) => {
+					eprintln!("Could not reload texture '{}' after device recovery: {}", path, error);
+					self.texture_cache.remove(&path);
+				}
+			}
+		}
+
+		// Pipelines rebuild from their recorded sources; deduplicated and text pipelines refill lazily
+		// Compute pipelines keep no source record, so their owners must rebuild them on next use
+		self.pipeline_cache.clear();
+		self.pipeline_manager = PipelineManager::new();
+		self.compute_pipeline_cache.clear();
+		let names: Vec<_> = self.pipeline_shaders.keys().cloned().collect();
+		for name in names {
+			self.rebuild_pipeline(&name);
+		}
+		self.text_renderer = None;
+
+		// Every window's GUI commands referenced resources on the old device; rebuild them all
+		let restore = self.active_window;
+		for index in 0..self.windows.len() {
+			self.active_window = index;
+			self.redraw_gui();
+		}
+		self.active_window = restore;
+		Ok(())
+	}
+
+	// The color attachment and resolve target for a pass: with multisampling, draw into the MSAA
+	// buffer and resolve into the frame; otherwise attach the frame directly
+	fn msaa_attachment<'a>(&'a self, frame_view: &'a wgpu::TextureView) -> (&'a wgpu::TextureView, Option<&'a wgpu::TextureView>) {
+		let context = self.context();
+		match &context.msaa_texture {
+			Some(msaa_texture) => {
+				// resize and set_msaa_sample_count both rebuild this texture, so a mismatch here is a bug
+				debug_assert_eq!(
+					msaa_texture.size(),
+					(context.swap_chain_descriptor.width, context.swap_chain_descriptor.height),
+					"The MSAA texture fell out of sync with the frame dimensions"
+				);
+				(&msaa_texture.view, Some(frame_view))
+			}
+			None => (frame_view, None),
+		}
+	}
+
+	// Records the scene pass: clears the frame and depth buffer, then draws everything except the UI overlay
+	fn record_scene_pass(&self, encoder: &mut wgpu::CommandEncoder, frame_view: &wgpu::TextureView) {
+		let (attachment, resolve_target) = self.msaa_attachment(frame_view);
+
+		let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+			color_attachments: &[wgpu::RenderPassColorAttachmentDescriptor {
+				attachment,
+				resolve_target,
+				load_op: wgpu::LoadOp::Clear,
+				store_op: wgpu::StoreOp::Store,
+				clear_color: self.clear_color,
+			}],
+			depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachmentDescriptor {
+				attachment: &self.context().depth_texture.view,
+				depth_load_op: wgpu::LoadOp::Clear,
+				depth_store_op: wgpu::StoreOp::Store,
+				clear_depth: 1.,
+				stencil_load_op: wgpu::LoadOp::Clear,
+				stencil_store_op: wgpu::StoreOp::Store,
+				clear_stencil: 0,
+			}),
+		});
+
+		self.replay_matching(&mut render_pass, |name| name != TEXT_PIPELINE, None);
+
+		// The embedder's hook draws after the GUI commands, while the pass is still open
+		self.run_render_hook(&mut render_pass);
+	}
+
+	// Records the UI overlay pass: draws text over the scene, preserving what the scene pass rendered
+	fn record_ui_pass(&self, encoder: &mut wgpu::CommandEncoder, frame_view: &wgpu::TextureView) {
+		// Skip the pass entirely when no UI commands are queued
+		if !self.context().draw_command_queue.iter().any(|command| command.pipeline_name == TEXT_PIPELINE) {
+			return;
+		}
+
+		let (attachment, resolve_target) = self.msaa_attachment(frame_view);
+
+		let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+			color_attachments: &[wgpu::RenderPassColorAttachmentDescriptor {
+				attachment,
+				resolve_target,
+				load_op: wgpu::LoadOp::Load,
+				store_op: wgpu::StoreOp::Store,
+				clear_color: self.clear_color,
+			}],
+			depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachmentDescriptor {
+				attachment: &self.context().depth_texture.view,
+				depth_load_op: wgpu::LoadOp::Load,
+				depth_store_op: wgpu::StoreOp::Store,
+				clear_depth: 1.,
+				stencil_load_op: wgpu::LoadOp::Load,
+				stencil_store_op: wgpu::StoreOp::Store,
+				clear_stencil: 0,
+			}),
+		});
+
+		self.replay_matching(&mut render_pass, |name| name == TEXT_PIPELINE, None);
+	}
+
+	// Records the immediate-mode pass: the per-frame shapes queued through draw_line and friends,
+	// drawn over the finished frame. The depth buffer clears so no scene geometry can occlude them
+	fn record_immediate_pass(&self, encoder: &mut wgpu::CommandEncoder, frame_view: &wgpu::TextureView) {
+		if self.context().overlay_draw_commands.is_empty() {
+			return;
+		}
+
+		let (attachment, resolve_target) = self.msaa_attachment(frame_view);
+
+		let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+			color_attachments: &[wgpu::RenderPassColorAttachmentDescriptor {
+				attachment,
+				resolve_target,
+				load_op: wgpu::LoadOp::Load,
+				store_op: wgpu::StoreOp::Store,
+				clear_color: self.clear_color,
+			}],
+			depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachmentDescriptor {
+				attachment: &self.context().depth_texture.view,
+				depth_load_op: wgpu::LoadOp::Clear,
+				depth_store_op: wgpu::StoreOp::Store,
+				clear_depth: 1.,
+				stencil_load_op: wgpu::LoadOp::Clear,
+				stencil_store_op: wgpu::StoreOp::Store,
+				clear_stencil: 0,
+			}),
+		});
+
+		self.replay_commands(&mut render_pass, &self.context().overlay_draw_commands, |_| true, None);
+	}
+
+	// Replays the queued draw commands, grouped by pipeline so each pipeline binds only once
+	fn replay_draw_commands<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>, pass_clip: Option<Rect>) {
+		self.replay_matching(render_pass, |_| true, pass_clip);
+	}
+
+	// Installs a hook that records extra draw calls at the end of the scene pass, after the queued
+	// GUI commands, e.g. a viewport preview interleaved with the GUI
+	//
+	// The hook runs mid-render-pass, which constrains what it can borrow: everything it binds must
+	// outlive the pass, so pipelines, textures, and buffers must come through the `&Application` it
+	// receives (whose borrow is tied to the pass) or from owned state the closure captured; a buffer
+	// created inside the hook drops too early to bind. The application stays immutably borrowed for
+	// the whole pass, so the hook cannot mutate it either; apply per-frame state changes from the
+	// event loop before render runs
+	pub fn set_on_render(&mut self, hook: impl for<'a> FnMut(&mut wgpu::RenderPass<'a>, &'a Application) + 'static) {
+		self.on_render = std::cell::RefCell::new(Some(Box::new(hook)));
+	}
+
+	// Removes the hook installed by set_on_render
+	pub fn clear_on_render(&mut self) {
+		self.on_render = std::cell::RefCell::new(None);
+	}
+
+	// Runs the embedder's render hook, if any, with the pass still open
+	// The hook is moved out for the call, so the RefCell is never borrowed while it runs
+	fn run_render_hook<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+		let hook = self.on_render.borrow_mut().take();
+		if let Some(mut hook) = hook {
+			hook(render_pass, self);
+			*self.on_render.borrow_mut() = Some(hook);
+		}
+	}
+
+	// Per-draw parameter bytes are written into each command's uniform buffer before the frame
+	// encodes, so shaders reading the matrix uniform pick them up; this wgpu version exposes no
+	// push constants, and the write only applies to matrix-sized payloads
+	fn flush_push_constant_fallbacks(&mut self) {
+		for index in 0..self.windows[self.active_window].draw_command_queue.len() {
+			let command = &self.windows[self.active_window].draw_command_queue[index];
+			let matrix = match (&command.push_constants, &command.uniform_buffer) {
+				(Some(bytes), Some(_)) if bytes.len() as wgpu::BufferAddress == uniform_buffer::MATRIX_SIZE => *bytemuck::from_bytes::<uniform_buffer::Matrix4>(bytes),
+				_ => continue,
+			};
+			let uniform = self.windows[self.active_window].draw_command_queue[index].uniform_buffer.as_ref().unwrap();
+			uniform.update_uniform(&self.device, &mut self.queue, matrix);
+		}
+	}
+
+	// Builds the line-list index buffers wireframe replay draws with, for every queued command whose
+	// pipeline rasterizes triangles. Buffers bound mid-pass must already exist, so this runs before
+	// the frame encodes; commands queued while wireframe is off pick their edge lists up here too
+	fn ensure_wireframe_indices(&mut self) {
+		if !self.wireframe {
+			return;
+		}
+		let wireframe_pipelines: std::collections::HashSet<&str> = self.pipeline_shaders.iter().filter(|(_, source)| wireframe_applies(source)).map(|(name, _)| name.as_str()).collect();
+		for command in &mut self.windows[self.active_window].draw_command_queue {
+			if wireframe_pipelines.contains(command.pipeline_name.as_str()) {
+				command.ensure_wireframe_indices(&self.device);
+			}
+		}
+	}
+
+	// Converts a logical clip rectangle to physical pixels, clamped to the viewport as set_scissor_rect requires
+	fn scissor_physical(&self, rect: Rect) -> (u32, u32, u32, u32) {
+		let context = self.context();
+		let scale = context.scale_factor as f32;
+		let (viewport_width, viewport_height) = (context.swap_chain_descriptor.width as f32, context.swap_chain_descriptor.height as f32);
+		let x = (rect.x * scale).max(0.).min(viewport_width);
+		let y = (rect.y * scale).max(0.).min(viewport_height);
+		let width = (rect.width * scale).max(0.).min(viewport_width - x);
+		let height = (rect.height * scale).max(0.).min(viewport_height - y);
+		(x as u32, y as u32, width as u32, height as u32)
+	}
+
+	// Replays the queued draw commands whose pipeline name the filter accepts, e.g. one render graph
+	// pass's share
+	fn replay_matching<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>, include: impl Fn(&str) -> bool, pass_clip: Option<Rect>) {
+		self.replay_commands(render_pass, &self.context().draw_command_queue, include, pass_clip);
+	}
+
+	// Replays a slice of draw commands, grouped by pipeline so each pipeline binds only once
+	// A pass clip further restricts every command to that rectangle, e.g. a partial redraw's dirty region
+	fn replay_commands<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>, commands: &'a [DrawCommand], include: impl Fn(&str) -> bool, pass_clip: Option<Rect>) {
+		let pipeline_names: Vec<&str> = commands.iter().map(|command| command.pipeline_name.as_str()).collect();
+
+		let mut bound_pipeline: Option<&str> = None;
+		// None means the scissor is at its default, the full viewport
+		let mut current_scissor: Option<(u32, u32, u32, u32)> = None;
+		for index in batched_order(&pipeline_names) {
+			let command = &commands[index];
+			if !include(&command.pipeline_name) {
+				continue;
+			}
+
+			// A clipped command rasterizes only inside its scissor; one entirely outside the pass
+			// clip draws nothing at all
+			let clip = match (pass_clip, command.scissor) {
+				(Some(pass_rect), Some(rect)) => match pass_rect.intersection(rect) {
+					Some(overlap) => Some(overlap),
+					None => continue,
+				},
+				(Some(pass_rect), None) => Some(pass_rect),
+				(None, scissor) => scissor,
+			};
+			let scissor = clip.map(|rect| self.scissor_physical(rect));
+			if let Some((_, _, width, height)) = scissor {
+				if width == 0 || height == 0 {
+					continue;
+				}
+			}
+			if scissor != current_scissor {
+				let (x, y, width, height) = scissor.unwrap_or((0, 0, self.context().swap_chain_descriptor.width, self.context().swap_chain_descriptor.height));
+				render_pass.set_scissor_rect(x, y, width, height);
+				current_scissor = scissor;
+			}
+			let pipeline = self.pipeline_cache.get(&command.pipeline_name).expect("Draw command references an uncached pipeline");
+			// The index format is baked into the pipeline state, so the command's buffer layout must agree with it
+			debug_assert_eq!(pipeline.index_format, command.index_format, "Draw command index format does not match its pipeline");
+
+			// Re-bind only when the pipeline actually changes between consecutive commands
+			if bound_pipeline != Some(command.pipeline_name.as_str()) {
+				// Group each pipeline's draws under its name, so GPU captures mirror the batching
+				if self.debug_markers {
+					if bound_pipeline.is_some() {
+						render_pass.pop_debug_group();
+					}
+					render_pass.push_debug_group(&command.pipeline_name);
+				}
+				render_pass.set_pipeline(&pipeline.render_pipeline);
+				bound_pipeline = Some(command.pipeline_name.as_str());
+			}
+			if self.debug_markers {
+				render_pass.insert_debug_marker(&format!("draw {}", index));
+			}
+
+			for (slot, bind_group) in command.bind_groups.iter().enumerate() {
+				render_pass.set_bind_group(slot as u32, bind_group, &[]);
+			}
+			render_pass.set_vertex_buffer(0, &command.vertex_buffer, 0, 0);
+			if let Some(instance_buffer) = &command.instance_buffer {
+				render_pass.set_vertex_buffer(1, instance_buffer, 0, 0);
+			}
+			match (command.draw_kind, &command.index_buffer) {
+				(crate::draw_command::DrawKind::Indexed, Some(index_buffer)) => {
+					// Wireframe replays the derived edge list through the pipeline's line-list topology
+					match (self.wireframe, &command.wireframe_index_buffer) {
+						(true, Some(wireframe_buffer)) => {
+							render_pass.set_index_buffer(wireframe_buffer, 0, 0);
+							render_pass.draw_indexed(0..command.wireframe_index_count, 0, 0..command.instance_count);
+						}
+						_ => {
+							render_pass.set_index_buffer(index_buffer, 0, 0);
+							render_pass.draw_indexed(0..command.index_count, 0, 0..command.instance_count);
+						}
+					}
+				}
+				_ => render_pass.draw(0..command.vertex_count, 0..command.instance_count),
+			}
+		}
+
+		// Close the last pipeline's group; one is open whenever anything was drawn
+		if self.debug_markers && bound_pipeline.is_some() {
+			render_pass.pop_debug_group();
+		}
+	}
+
+	// Replays the draw command queue into an offscreen target instead of the window, e.g. for thumbnails or tests
+	// The target keeps its contents after the pass, so it can be sampled or read back afterwards
+	// With reuse_previous, a partial dirty region repaints only itself and the rest of the target is
+	// kept from the previous frame; pass false for fresh targets whose contents are still undefined
+	pub fn render_to_texture(&mut self, target: &Texture, reuse_previous: bool) {
+		// The offscreen passes need their own depth buffer matching the target's dimensions (not the
+		// window's) and the sample count baked into the pipelines
+		let depth_texture = Texture::create_depth(&self.device, target.size.width, target.size.height, self.sample_count);
+
+		// With multisampling on, drawing happens in a transient multisample buffer that each pass
+		// resolves into the target, so the target only ever holds single-sample pixels that a
+		// copy_texture_to_buffer readback (as in capture_frame) can consume directly
+		let scene_format = self.scene_format();
+		let msaa_texture = match self.sample_count {
+			1 => None,
+			sample_count => Some(Texture::create_msaa(&self.device, target.size.width, target.size.height, scene_format, sample_count)),
+		};
+
+		// Only a region strictly inside the viewport is worth a partial repaint; a full-viewport
+		// region (or no tracking at all) falls back to the ordinary clear-and-redraw
+		// The transient multisample buffer holds no previous frame to load, so MSAA always redraws in full
+		let viewport = self.logical_viewport();
+		let dirty_region = self.windows[self.active_window].gui_tree.take_dirty_region();
+		let partial = match dirty_region {
+			Some(region) if reuse_previous && msaa_texture.is_none() && (region.x > 0. || region.y > 0. || region.x + region.width < viewport.width || region.y + region.height < viewport.height) => Some(region),
+			_ => None,
+		};
+		let load_op = if partial.is_some() { wgpu::LoadOp::Load } else { wgpu::LoadOp::Clear };
+
+		self.flush_push_constant_fallbacks();
+		self.ensure_wireframe_indices();
+
+		// With post-processing on, the scene renders into the HDR target and tonemaps into `target`,
+		// so offscreen captures match what a window would present
+		let (width, height) = target.size();
+		let device = &self.device;
+		if let Some(post) = self.post_process.as_mut() {
+			post.resize(device, width, height);
+		}
+
+		let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("render_to_texture_encoder") });
+
+		{
+			let scene_view = match &self.post_process {
+				Some(post) => &post.target.view,
+				None => &target.view,
+			};
+			// Multisampled passes draw into the transient buffer and resolve into the scene view,
+			// mirroring msaa_attachment for the windowed path
+			let (attachment, resolve_target) = match &msaa_texture {
+				Some(msaa_texture) => (&msaa_texture.view, Some(scene_view)),
+				None => (scene_view, None),
+			};
+			{
+				let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+					color_attachments: &[wgpu::RenderPassColorAttachmentDescriptor {
+						attachment,
+						resolve_target,
+						load_op,
+						store_op: wgpu::StoreOp::Store,
+						clear_color: self.clear_color,
+					}],
+					depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachmentDescriptor {
+						attachment: &depth_texture.view,
+						depth_load_op: wgpu::LoadOp::Clear,
+						depth_store_op: wgpu::StoreOp::Store,
+						clear_depth: 1.,
+						stencil_load_op: wgpu::LoadOp::Clear,
+						stencil_store_op: wgpu::StoreOp::Store,
+						clear_stencil: 0,
+					}),
+				});
+
+				// The scissor keeps every draw inside the dirty region, so the loaded contents survive elsewhere
+				self.replay_draw_commands(&mut render_pass, partial);
+
+				// The hook draws offscreen too, so headless captures match what a window would show
+				self.run_render_hook(&mut render_pass);
+			}
+
+			// The immediate-mode overlay records its own depth-clearing pass over the finished scene,
+			// mirroring record_immediate_pass, so offscreen captures include the debug shapes too
+			if !self.context().overlay_draw_commands.is_empty() {
+				let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+					color_attachments: &[wgpu::RenderPassColorAttachmentDescriptor {
+						attachment,
+						resolve_target,
+						load_op: wgpu::LoadOp::Load,
+						store_op: wgpu::StoreOp::Store,
+						clear_color: self.clear_color,
+					}],
+					depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachmentDescriptor {
+						attachment: &depth_texture.view,
+						depth_load_op: wgpu::LoadOp::Clear,
+						depth_store_op: wgpu::StoreOp::Store,
+						clear_depth: 1.,
+						stencil_load_op: wgpu::LoadOp::Clear,
+						stencil_store_op: wgpu::StoreOp::Store,
+						clear_stencil: 0,
+					}),
+				});
+				self.replay_commands(&mut render_pass, &self.context().overlay_draw_commands, |_| true, None);
+			}
+		}
+
+		if let Some(post) = &self.post_process {
+			post.record(&mut encoder, &target.view);
+		}
+
+		self.queue.submit(&[encoder.finish()]);
+	}
+
+	// Renders the current draw command queue offscreen and writes the result to disk as an image
+	// Intended for visual regression tests, so the encoded pixels match what the window would show
+	pub fn capture_frame(&mut self, path: &str) -> Result<(), CaptureError> {
+		let (width, height) = (self.context().swap_chain_descriptor.width, self.context().swap_chain_descriptor.height);
+
+		// Render into a readable target with the same format the window's pipelines were built for
+		let target = Texture::render_target(&self.device, width, height, self.context().swap_chain_descriptor.format);
+		self.render_to_texture(&target, false);
+
+		// Copy the target into a mappable buffer, padding rows out to wgpu's 256-byte copy alignment
+		let unpadded_bytes_per_row = 4 * width;
+		let padded_bytes_per_row = align_bytes_per_row(unpadded_bytes_per_row);
+		let buffer_size = (padded_bytes_per_row * height) as wgpu::BufferAddress;
+		let readback = self.device.create_buffer(&wgpu::BufferDescriptor {
+			label: Some("capture_readback"),
+			size: buffer_size,
+			usage: wgpu::BufferUsage::COPY_DST | wgpu::BufferUsage::MAP_READ,
+		});
+
+		let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("capture_encoder") });
+		encoder.copy_texture_to_buffer(
+			wgpu::TextureCopyView {
+				texture: &target.texture,
+				mip_level: 0,
+				array_layer: 0,
+				origin: wgpu::Origin3d::ZERO,
+			},
+			wgpu::BufferCopyView {
+				buffer: &readback,
+				offset: 0,
+				bytes_per_row: padded_bytes_per_row,
+				rows_per_image: height,
+			},
+			wgpu::Extent3d { width, height, depth: 1 },
+		);
+		self.queue.submit(&[encoder.finish()]);
+
+		let mapping = readback.map_read(0, buffer_size);
+		self.device.poll(wgpu::Maintain::Wait);
+		let mapping = block_on(mapping).map_err(|_| CaptureError::BufferMap)?;
+		let padded_pixels = mapping.as_slice();
+
+		// Strip the row padding and swizzle the swap chain's BGRA byte order into the RGBA the encoder expects
+		// An sRGB swap chain already stores encoded bytes, which image files expect, so they pass through
+		// untouched; a linear format's bytes get the sRGB encode here or the file would come out too dark
+		let already_encoded = format_is_srgb(self.context().swap_chain_descriptor.format);
+		let encode = |channel: u8| if already_encoded { channel } else { encode_srgb_channel(channel) };
+		let mut pixels = Vec::with_capacity((unpadded_bytes_per_row * height) as usize);
+		for row in padded_pixels.chunks(padded_bytes_per_row as usize) {
+			for pixel in row[..unpadded_bytes_per_row as usize].chunks(4) {
+				pixels.extend_from_slice(&[encode(pixel[2]), encode(pixel[1]), encode(pixel[0]), pixel[3]]);
+			}
+		}
+
+		let image = image::RgbaImage::from_raw(width, height, pixels).expect("Capture buffer size does not match the frame dimensions");
+		image.save(path).map_err(CaptureError::Image)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	// Builds a headless Application, or None on machines without any GPU adapter, so device-backed
+	// tests can return early and pass as skips instead of failing on hardware they cannot control
+	fn headless_app(width: u32, height: u32) -> Option<Application> {
+		match Application::new_headless(width, height) {
+			Ok(app) => Some(app),
+			Err(ApplicationInitError::NoAdapter) => None,
+			Err(error) => panic!("Headless initialization should succeed without a display: {}", error),
+		}
+	}
+
+	#[test]
+	fn batching_groups_commands_without_reordering_within_a_pipeline() {
+		let names = ["quads", "text", "quads", "text", "quads"];
+		assert_eq!(batched_order(&names), vec![0, 2, 4, 1, 3]);
+	}
+
+	#[test]
+	fn batching_collapses_an_alternating_scene_to_one_group_per_pipeline() {
+		// 1000 quads alternating between two pipelines: two groups, each in submission order
+		let names: Vec<&str> = (0..1000).map(|index| if index % 2 == 0 { "solid" } else { "textured" }).collect();
+		let order = batched_order(&names);
+
+		assert!(order[..500].iter().all(|&index| index % 2 == 0));
+		assert!(order[500..].iter().all(|&index| index % 2 == 1));
+		// The single pipeline switch happens exactly at the group boundary
+		let switches = order.windows(2).filter(|pair| names[pair[0]] != names[pair[1]]).count();
+		assert_eq!(switches, 1);
+	}
+
+	#[test]
+	fn headless_application_renders_without_a_surface() {
+		let mut app = match headless_app(64, 64) {
+			Some(app) => app,
+			None => return,
+		};
+
+		app.set_clear_color(ColorPalette::Accent);
+		assert!(app.is_dirty());
+		app.render();
+		assert!(!app.is_dirty());
+	}
+
+	#[test]
+	fn the_render_hook_runs_once_per_frame_and_survives_it() {
+		let mut app = match headless_app(16, 16) {
+			Some(app) => app,
+			None => return,
+		};
+
+		let calls = std::rc::Rc::new(std::cell::Cell::new(0));
+		let seen = calls.clone();
+		app.set_on_render(move |_render_pass, _app| seen.set(seen.get() + 1));
+
+		app.render();
+		assert_eq!(calls.get(), 1);
+
+		// The hook is put back after each call rather than consumed by the first frame
+		app.render();
+		assert_eq!(calls.get(), 2);
+
+		app.clear_on_render();
+		app.render();
+		assert_eq!(calls.get(), 2);
+	}
+
+	#[test]
+	fn the_post_process_stage_toggles_and_frames_still_render() {
+		let mut app = match headless_app(16, 16) {
+			Some(app) => app,
+			None => return,
+		};
+		assert!(!app.post_process_enabled());
+
+		app.set_post_process(true);
+		assert!(app.post_process_enabled());
+		app.set_exposure(1.5);
+		app.set_gamma(2.4);
+		app.render();
+
+		// Turning the stage back off routes the passes straight at the frame again
+		app.set_post_process(false);
+		assert!(!app.post_process_enabled());
+		app.render();
+	}
+
+	#[test]
+	fn a_missing_texture_substitutes_the_fallback_checkerboard() {
+		let mut app = match headless_app(16, 16) {
+			Some(app) => app,
+			None => return,
+		};
+
+		let texture = app.get_or_fallback("textures/does_not_exist.png");
+		assert_eq!(texture.size(), (64, 64));
+
+		// The failure left the cache unpopulated, so a file fixed on disk can still load later
+		assert!(app.texture_cache.get("textures/does_not_exist.png").is_none());
+
+		// A path that does exist loads normally and lands in the cache
+		let texture = app.get_or_fallback("textures/grid.png");
+		assert_eq!(texture.size(), (16, 16));
+		assert!(app.texture_cache.get("textures/grid.png").is_some());
+	}
+
+	#[test]
+	fn gpu_timing_toggles_and_survives_a_frame() {
+		let mut app = match headless_app(16, 16) {
+			Some(app) => app,
+			None => return,
+		};
+		assert!(!app.gpu_timing_enabled());
+
+		// The drain-based timer works on every adapter, so arming always takes effect
+		app.set_gpu_timing(true);
+		assert!(app.gpu_timing_enabled());
+		app.render();
+
+		app.set_gpu_timing(false);
+		assert!(!app.gpu_timing_enabled());
+	}
+
+	#[test]
+	fn immediate_mode_shapes_last_exactly_one_frame() {
+		let mut app = match headless_app(64, 64) {
+			Some(app) => app,
+			None => return,
+		};
+
+		app.draw_rect(Rect::new(4., 4., 16., 16.), ColorPalette::Accent);
+		app.draw_line((0., 0.), (64., 64.), ColorPalette::White, 2.);
+		app.draw_circle((32., 32.), 10., ColorPalette::Color(0xff000080));
+		assert_eq!(app.context().overlay_draw_commands.len(), 3);
+		assert!(app.is_dirty());
+
+		// The frame draws the shapes and then drains them, so the next frame starts empty
+		app.render();
+		assert!(app.context().overlay_draw_commands.is_empty());
+	}
+
+	#[test]
+	fn line_quads_run_perpendicular_offsets_of_half_the_width() {
+		// A horizontal line of width 4 offsets its corners 2 logical pixels to either side
+		let corners = line_quad_corners((10., 20.), (50., 20.), 4.);
+		assert_eq!(corners, [[10., 22.], [50., 22.], [50., 18.], [10., 18.]]);
+
+		// A vertical line offsets along x instead
+		let corners = line_quad_corners((10., 0.), (10., 30.), 2.);
+		assert_eq!(corners, [[9., 0.], [9., 30.], [11., 30.], [11., 0.]]);
+	}
+
+	#[test]
+	fn circle_fans_anchor_at_the_center_and_close_the_rim() {
+		let segments = circle_segments(10.);
+		assert_eq!(segments, 12);
+
+		let positions = circle_positions((100., 50.), 10., segments);
+		assert_eq!(positions.len(), 13);
+		assert_eq!(positions[0], [100., 50.]);
+		assert_eq!(positions[1], [110., 50.]);
+		// Every rim point sits on the circle
+		for rim in &positions[1..] {
+			let (dx, dy) = (rim[0] - 100., rim[1] - 50.);
+			assert!(((dx * dx + dy * dy).sqrt() - 10.).abs() < 1e-4);
+		}
+
+		// One triangle per segment, each anchored at the center, and the last closes back to the start
+		let indices = circle_indices(segments);
+		assert_eq!(indices.len(), segments as usize * 3);
+		assert!(indices.chunks(3).all(|triangle| triangle[0] == 0));
+		assert_eq!(&indices[indices.len() - 3..], &[0, 12, 1]);
+	}
+
+	#[test]
+	fn overlay_vertex_colors_carry_the_palette_channels_and_alpha() {
+		assert_eq!(vertex_color(ColorPalette::White, Theme::Dark), [1., 1., 1., 1.]);
+		// A custom color keeps its alpha; the named entries are all opaque
+		assert_eq!(vertex_color(ColorPalette::Color(0xff000080), Theme::Dark), [1., 0., 0., 128. / 255.]);
+		// Role entries resolve through the theme
+		assert_eq!(vertex_color(ColorPalette::Accent, Theme::Dark)[2], 0xd6 as f32 / 255.);
+	}
+
+	#[test]
+	fn occluded_windows_skip_rendering_until_restored() {
+		let mut app = match headless_app(16, 16) {
+			Some(app) => app,
+			None => return,
+		};
+		assert!(!app.render_paused());
+
+		app.set_occluded(true);
+		assert!(app.render_paused());
+
+		// The skipped frame leaves the window dirty, so the restore still has something to draw
+		app.render();
+		assert!(app.is_dirty());
+
+		app.set_occluded(false);
+		assert!(!app.render_paused());
+		app.render();
+		assert!(!app.is_dirty());
+	}
+
+	#[test]
+	fn zero_size_resizes_pause_rendering_like_a_minimize() {
+		let mut app = match headless_app(16, 16) {
+			Some(app) => app,
+			None => return,
+		};
+		app.render();
+		assert!(!app.is_dirty());
+
+		// Minimizing reports a zero-size resize, which pauses rendering instead of rebuilding buffers
+		app.resize(winit::dpi::PhysicalSize::new(0, 0));
+		assert!(app.render_paused());
+
+		// The restore's real size resumes rendering and queues a redraw of whatever changed while hidden
+		app.resize(winit::dpi::PhysicalSize::new(16, 16));
+		assert!(!app.render_paused());
+		assert!(app.is_dirty());
+	}
+
+	#[test]
+	fn sdf_shapes_read_back_with_transparent_corners() {
+		let mut app = match headless_app(64, 64) {
+			Some(app) => app,
+			None => return,
+		};
+		app.set_clear_color(ColorPalette::Black);
+
+		// A white rounded rectangle via the overlay helper and, through the DrawCommand
+		// constructor directly, a white circle beside it
+		app.draw_rounded_rect(Rect::new(8., 8., 28., 28.), 10., ColorPalette::White);
+		let viewport = app.logical_viewport();
+		let pipeline = app.pipeline_cache.get(SDF_PIPELINE).expect("draw_rounded_rect should have compiled the SDF pipeline");
+		let circle = DrawCommand::circle(&app.device, String::from(SDF_PIPELINE), pipeline, (48., 48.), 10., [1., 1., 1., 1.], viewport);
+		app.windows[0].overlay_draw_commands.push(circle);
+
+		let path = std::env::temp_dir().join("graphite_sdf_capture.png");
+		let path = path.to_str().expect("The temp path should be valid UTF-8");
+		app.capture_frame(path).expect("The capture should render and encode");
+		let image = image::open(path).expect("The captured file should decode").to_rgba8();
+		let _ = std::fs::remove_file(path);
+
+		// Both quads cover their corner pixels, but the distance field rounds the shapes away
+		// there, so those fragments blend to nothing and the clear color survives
+		assert_eq!(image.get_pixel(9, 9)[0], 0);
+		assert_eq!(image.get_pixel(39, 39)[0], 0);
+		// While the shape interiors fill solidly
+		assert_eq!(image.get_pixel(22, 22)[0], 255);
+		assert_eq!(image.get_pixel(48, 48)[0], 255);
+	}
+
+	#[test]
+	fn multisampled_headless_captures_resolve_to_a_readable_image() {
+		let mut app = match headless_app(32, 32) {
+			Some(app) => app,
+			None => return,
+		};
+		app.set_msaa_sample_count(4);
+		app.set_clear_color(ColorPalette::Accent);
+		app.draw_rect(Rect::new(8., 8., 16., 16.), ColorPalette::White);
+
+		// The multisample buffer cannot be copied out directly; the capture must read the resolved target
+		let path = std::env::temp_dir().join("graphite_msaa_capture.png");
+		let path = path.to_str().expect("The temp path should be valid UTF-8");
+		app.capture_frame(path).expect("A multisampled capture should resolve and encode");
+		let image = image::open(path).expect("The captured file should decode").to_rgba8();
+		let _ = std::fs::remove_file(path);
+
+		assert_eq!(image.dimensions(), (32, 32));
+		// The rectangle's interior resolves to its solid fill, and the background keeps the clear color
+		assert_eq!(image.get_pixel(16, 16)[0], 255);
+		assert!(image.get_pixel(2, 2)[0] < 255);
+	}
+
+	#[test]
+	fn the_logical_viewport_divides_out_the_scale_factor() {
+		let viewport = logical_size(1600, 1200, 2.);
+		assert_eq!((viewport.width, viewport.height), (800., 600.));
+
+		// A 1.0 scale factor leaves physical and logical pixels identical
+		let viewport = logical_size(1280, 720, 1.);
+		assert_eq!((viewport.width, viewport.height), (1280., 720.));
+	}
+
+	#[test]
+	fn the_chosen_surface_format_is_always_a_supported_one() {
+		let supported = [wgpu::TextureFormat::Bgra8UnormSrgb, wgpu::TextureFormat::Bgra8Unorm];
+
+		// No override picks the surface's preferred format
+		assert_eq!(choose_surface_format(&supported, None), wgpu::TextureFormat::Bgra8UnormSrgb);
+		// A supported override is honored
+		assert_eq!(choose_surface_format(&supported, Some(wgpu::TextureFormat::Bgra8Unorm)), wgpu::TextureFormat::Bgra8Unorm);
+		// An unsupported override falls back to the preferred format instead of failing at presentation
+		assert_eq!(choose_surface_format(&supported, Some(wgpu::TextureFormat::Rgba16Float)), wgpu::TextureFormat::Bgra8UnormSrgb);
+	}
+
+	#[test]
+	fn push_constant_bytes_reinterpret_as_a_fallback_matrix() {
+		let mut matrix = uniform_buffer::IDENTITY;
+		matrix[3][0] = 0.25;
+
+		// The uniform fallback only applies to matrix-sized payloads, reading them back losslessly
+		let bytes: Vec<u8> = bytemuck::cast_slice(&matrix).to_vec();
+		assert_eq!(bytes.len() as wgpu::BufferAddress, uniform_buffer::MATRIX_SIZE);
+		let restored: uniform_buffer::Matrix4 = *bytemuck::from_bytes(&bytes);
+		assert_eq!(restored, matrix);
+	}
+
+	#[test]
+	fn linear_readback_bytes_encode_to_the_expected_srgb_values() {
+		// The endpoints are fixed points of the transfer function
+		assert_eq!(encode_srgb_channel(0), 0);
+		assert_eq!(encode_srgb_channel(255), 255);
+
+		// A mid-gray clear color of 0.5 linear saves as the familiar sRGB 188
+		assert_eq!(encode_srgb_channel(128), 188);
+
+		// sRGB swap chains already hold encoded bytes, so captures from them skip the encode
+		assert!(format_is_srgb(wgpu::TextureFormat::Bgra8UnormSrgb));
+		assert!(!format_is_srgb(wgpu::TextureFormat::Bgra8Unorm));
+	}
+
+	#[test]
+	fn anisotropy_clamps_into_the_supported_range() {
+		// 0 would be an invalid sampler value, so it rounds up to 1 (disabled)
+		assert_eq!(clamp_anisotropy(0, 16), 1);
+		assert_eq!(clamp_anisotropy(8, 16), 8);
+		// Requests beyond the adapter's limit settle at the limit rather than failing device creation
+		assert_eq!(clamp_anisotropy(32, 16), 16);
+		// Software rasterizers report a limit of 1, which disables the extension entirely
+		assert_eq!(clamp_anisotropy(8, 1), 1);
+	}
+
+	#[test]
+	fn the_frame_cap_schedules_a_deadline_one_interval_after_the_last_render() {
+		let start = std::time::Instant::now();
+
+		// Uncapped applications never wait
+		assert_eq!(frame_deadline(start, start, None), None);
+
+		// A 50 fps cap waits out the 20 ms interval, measured from the last render
+		let deadline = frame_deadline(start, start, Some(50)).expect("A fresh render should schedule a wait");
+		assert_eq!(deadline - start, std::time::Duration::from_millis(20));
+
+		// Once the interval has passed, the next frame may draw immediately
+		let later = start + std::time::Duration::from_millis(25);
+		assert_eq!(frame_deadline(start, later, Some(50)), None);
+	}
+
+	#[test]
+	fn a_zero_frame_cap_is_treated_as_uncapped() {
+		let mut app = match headless_app(4, 4) {
+			Some(app) => app,
+			None => return,
+		};
+		app.set_max_fps(Some(0));
+		assert_eq!(app.next_frame_deadline(), None);
+
+		app.set_max_fps(Some(30));
+		assert!(app.next_frame_deadline().is_some());
+		app.set_max_fps(None);
+		assert_eq!(app.next_frame_deadline(), None);
+	}
+
+	#[test]
+	fn frame_latency_caps_at_one_frame_in_flight_at_minimum() {
+		let mut app = match headless_app(4, 4) {
+			Some(app) => app,
+			None => return,
+		};
+		assert_eq!(app.frame_latency(), 2);
+
+		// Zero frames in flight could never draw, so it rounds up to the lowest meaningful cap
+		app.set_frame_latency(0);
+		assert_eq!(app.frame_latency(), 1);
+		app.set_frame_latency(3);
+		assert_eq!(app.frame_latency(), 3);
+
+		// A latency-one application still renders; the pre-acquire wait simply has nothing in flight
+		app.set_frame_latency(1);
+		app.render();
+		assert!(!app.is_dirty());
+	}
+
+	#[test]
+	fn rows_are_padded_up_to_the_copy_alignment() {
+		assert_eq!(align_bytes_per_row(4 * 16), 256);
+		assert_eq!(align_bytes_per_row(4 * 64), 256);
+		assert_eq!(align_bytes_per_row(4 * 65), 512);
+		assert_eq!(align_bytes_per_row(4 * 1920), 4 * 1920);
+	}
+
+	#[test]
+	fn a_lone_timeout_skips_the_frame() {
+		assert_eq!(acquisition_strategy(0), AcquisitionStrategy::SkipFrame);
+	}
+
+	#[test]
+	fn consecutive_timeouts_escalate_to_a_swap_chain_rebuild() {
+		assert_eq!(acquisition_strategy(1), AcquisitionStrategy::RecreateAndRetry);
+		assert_eq!(acquisition_strategy(MAX_ACQUISITION_FAILURES - 1), AcquisitionStrategy::RecreateAndRetry);
+	}
+}
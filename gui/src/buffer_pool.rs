@@ -0,0 +1,95 @@
+use crate::draw_command::DrawCommand;
+use std::collections::HashMap;
+
+// Recycles GPU buffers across frames so rebuilding the GUI's geometry does not allocate every time
+// wgpu buffers have a fixed size, so only an exact size and usage match can be reused; recycled
+// buffers get their new contents through a staging copy
+pub struct BufferPool {
+	free: HashMap<(wgpu::BufferAddress, wgpu::BufferUsage), Vec<wgpu::Buffer>>,
+}
+
+impl BufferPool {
+	pub fn new() -> Self {
+		Self { free: HashMap::new() }
+	}
+
+	// A buffer holding `data`, recycled from the pool when a matching one is free
+	pub fn acquire(&mut self, device: &wgpu::Device, queue: &mut wgpu::Queue, data: &[u8], usage: wgpu::BufferUsage) -> wgpu::Buffer {
+		let size = data.len() as wgpu::BufferAddress;
+
+		if let Some(buffer) = self.free.get_mut(&(size, usage)).and_then(|buffers| buffers.pop()) {
+			// Refill the recycled buffer; the copy lands before any draw that reads it
+			let staging = device.create_buffer_with_data(data, wgpu::BufferUsage::COPY_SRC);
+			let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("buffer_pool_refill_encoder") });
+			encoder.copy_buffer_to_buffer(&staging, 0, &buffer, 0, size);
+			queue.submit(&[encoder.finish()]);
+			return buffer;
+		}
+
+		// COPY_DST so the buffer can be refilled when it comes back around
+		device.create_buffer_with_data(data, usage | wgpu::BufferUsage::COPY_DST)
+	}
+
+	// Returns a buffer to the pool once nothing references it anymore
+	pub fn release(&mut self, buffer: wgpu::Buffer, size: wgpu::BufferAddress, usage: wgpu::BufferUsage) {
+		self.free.entry((size, usage)).or_default().push(buffer);
+	}
+
+	// Reclaims a retired draw command's geometry buffers
+	// Instance buffers are not pooled; instanced commands are rare and vary in size
+	pub fn reclaim(&mut self, command: DrawCommand) {
+		self.release(command.vertex_buffer, command.vertex_buffer_size, wgpu::BufferUsage::VERTEX);
+		if let Some(index_buffer) = command.index_buffer {
+			self.release(index_buffer, command.index_buffer_size, wgpu::BufferUsage::INDEX);
+		}
+	}
+
+	// How many buffers are sitting in the pool awaiting reuse
+	pub fn free_buffers(&self) -> usize {
+		self.free.values().map(Vec::len).sum()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::test_utils::create_test_device;
+
+	#[test]
+	fn released_buffers_are_reused_for_matching_requests() {
+		let (device, mut queue) = match create_test_device() {
+			Some(pair) => pair,
+			None => return,
+		};
+		let mut pool = BufferPool::new();
+
+		let data = [0u8; 64];
+		let buffer = pool.acquire(&device, &mut queue, &data, wgpu::BufferUsage::VERTEX);
+		pool.release(buffer, 64, wgpu::BufferUsage::VERTEX);
+		assert_eq!(pool.free_buffers(), 1);
+
+		// Same size and usage: the pooled buffer is handed back out
+		pool.acquire(&device, &mut queue, &data, wgpu::BufferUsage::VERTEX);
+		assert_eq!(pool.free_buffers(), 0);
+	}
+
+	#[test]
+	fn mismatched_sizes_do_not_reuse_pooled_buffers() {
+		let (device, mut queue) = match create_test_device() {
+			Some(pair) => pair,
+			None => return,
+		};
+		let mut pool = BufferPool::new();
+
+		let buffer = pool.acquire(&device, &mut queue, &[0u8; 64], wgpu::BufferUsage::VERTEX);
+		pool.release(buffer, 64, wgpu::BufferUsage::VERTEX);
+
+		// A different size allocates fresh and leaves the pooled buffer alone
+		pool.acquire(&device, &mut queue, &[0u8; 128], wgpu::BufferUsage::VERTEX);
+		assert_eq!(pool.free_buffers(), 1);
+
+		// As does the same size under a different usage
+		pool.acquire(&device, &mut queue, &[0u8; 64], wgpu::BufferUsage::INDEX);
+		assert_eq!(pool.free_buffers(), 1);
+	}
+}
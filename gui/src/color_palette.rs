@@ -0,0 +1,305 @@
+use std::fmt;
+
+// Why a hex color string could not be parsed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorParseError {
+	MissingHashPrefix,
+	InvalidLength(usize),
+	InvalidDigit,
+}
+
+impl fmt::Display for ColorParseError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			ColorParseError::MissingHashPrefix => write!(f, "Hex colors must start with '#'"),
+			ColorParseError::InvalidLength(length) => write!(f, "Hex colors need 3, 6, or 8 digits, not {}", length),
+			ColorParseError::InvalidDigit => write!(f, "Hex colors may only contain the digits 0-9 and a-f"),
+		}
+	}
+}
+
+impl std::error::Error for ColorParseError {}
+
+// The overall appearance the role-based palette entries resolve against
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Theme {
+	#[default]
+	Dark,
+	Light,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum ColorPalette {
+	// Roles that resolve to different colors depending on the active theme
+	Background,
+	Panel,
+	Text,
+	Accent,
+	// Fixed entries that look the same in every theme
+	Black,
+	NearBlack,
+	MildBlack,
+	DarkGray,
+	MildGray,
+	Gray,
+	LightGray,
+	NearWhite,
+	White,
+	// An arbitrary color as 0xRRGGBBAA, taking the same code paths as the named entries
+	Color(u32),
+}
+
+impl ColorPalette {
+	// Parses "#RGB", "#RRGGBB", or "#RRGGBBAA" into a palette color carrying the raw RGBA value
+	pub fn from_hex(hex: &str) -> Result<ColorPalette, ColorParseError> {
+		let digits = match hex.strip_prefix('#') {
+			Some(digits) => digits,
+			None => return Err(ColorParseError::MissingHashPrefix),
+		};
+
+		let rgba = match digits.len() {
+			// Shorthand doubles each digit: #abc means #aabbcc
+			3 => {
+				let mut value = 0u32;
+				for character in digits.chars() {
+					let digit = character.to_digit(16).ok_or(ColorParseError::InvalidDigit)?;
+					value = (value << 8) | (digit * 16 + digit);
+				}
+				(value << 8) | 0xff
+			}
+			6 => (u32::from_str_radix(digits, 16).map_err(|_| ColorParseError::InvalidDigit)? << 8) | 0xff,
+			8 => u32::from_str_radix(digits, 16).map_err(|_| ColorParseError::InvalidDigit)?,
+			length => return Err(ColorParseError::InvalidLength(length)),
+		};
+		Ok(ColorPalette::Color(rgba))
+	}
+
+	// The 24-bit sRGB value of this palette entry under the given theme
+	pub fn get_color(&self, theme: Theme) -> u32 {
+		match self {
+			ColorPalette::Background => match theme {
+				Theme::Dark => 0x16161b,
+				Theme::Light => 0xf4f4f7,
+			},
+			ColorPalette::Panel => match theme {
+				Theme::Dark => 0x24242e,
+				Theme::Light => 0xe2e2e9,
+			},
+			ColorPalette::Text => match theme {
+				Theme::Dark => 0xd2d2de,
+				Theme::Light => 0x24242e,
+			},
+			ColorPalette::Accent => match theme {
+				Theme::Dark => 0x3194d6,
+				Theme::Light => 0x1c6ca8,
+			},
+			ColorPalette::Black => 0x000000,
+			ColorPalette::NearBlack => 0x16161b,
+			ColorPalette::MildBlack => 0x24242e,
+			ColorPalette::DarkGray => 0x333340,
+			ColorPalette::MildGray => 0x4b4b5a,
+			ColorPalette::Gray => 0x67677c,
+			ColorPalette::LightGray => 0x9797a8,
+			ColorPalette::NearWhite => 0xd2d2de,
+			ColorPalette::White => 0xffffff,
+			ColorPalette::Color(rgba) => rgba >> 8,
+		}
+	}
+
+	// The alpha channel as 0..1; the named entries are all fully opaque
+	fn alpha(&self) -> f64 {
+		match self {
+			ColorPalette::Color(rgba) => (rgba & 0xff) as f64 / 255.,
+			_ => 1.,
+		}
+	}
+
+	// The palette entry as linear floating point channels for use as a GPU clear color
+	// The stored values are sRGB, so each channel goes through the transfer function; alpha is already linear
+	pub fn get_color_linear(&self, theme: Theme) -> wgpu::Color {
+		let color = self.get_color(theme);
+		wgpu::Color {
+			r: srgb_to_linear(((color >> 16) & 0xff) as f64 / 255.),
+			g: srgb_to_linear(((color >> 8) & 0xff) as f64 / 255.),
+			b: srgb_to_linear((color & 0xff) as f64 / 255.),
+			a: self.alpha(),
+		}
+	}
+
+	// The palette entry as a GPU clear color appropriate for the given surface format
+	// sRGB formats encode on write and want linear values; non-sRGB formats store whatever they are
+	// given, so the raw sRGB channels pass through untouched and colors stay correct either way
+	pub fn get_color_for_format(&self, theme: Theme, format: wgpu::TextureFormat) -> wgpu::Color {
+		if is_srgb_format(format) {
+			return self.get_color_linear(theme);
+		}
+
+		let color = self.get_color(theme);
+		wgpu::Color {
+			r: ((color >> 16) & 0xff) as f64 / 255.,
+			g: ((color >> 8) & 0xff) as f64 / 255.,
+			b: (color & 0xff) as f64 / 255.,
+			a: self.alpha(),
+		}
+	}
+}
+
+// Whether writes to this format pass through the sRGB transfer function in hardware
+pub fn is_srgb_format(format: wgpu::TextureFormat) -> bool {
+	matches!(format, wgpu::TextureFormat::Bgra8UnormSrgb | wgpu::TextureFormat::Rgba8UnormSrgb)
+}
+
+// The standard piecewise sRGB transfer function: linear near black, a 2.4 gamma curve above
+pub fn srgb_to_linear(srgb: f64) -> f64 {
+	if srgb <= 0.04045 {
+		srgb / 12.92
+	} else {
+		((srgb + 0.055) / 1.055).powf(2.4)
+	}
+}
+
+// Composes a hue in degrees with saturation and value in 0..1 into 8-bit RGB channels
+// Hues outside 0..360 wrap, so 420 degrees lands on the same yellow as 60
+pub fn hsv_to_rgb(hue: f32, saturation: f32, value: f32) -> (u8, u8, u8) {
+	let hue = hue.rem_euclid(360.);
+	let chroma = value * saturation;
+	// The second-largest channel's height within the current 60-degree sextant
+	let x = chroma * (1. - ((hue / 60.) % 2. - 1.).abs());
+	let (red, green, blue) = match hue {
+		h if h < 60. => (chroma, x, 0.),
+		h if h < 120. => (x, chroma, 0.),
+		h if h < 180. => (0., chroma, x),
+		h if h < 240. => (0., x, chroma),
+		h if h < 300. => (x, 0., chroma),
+		_ => (chroma, 0., x),
+	};
+	let offset = value - chroma;
+	let channel = |c: f32| ((c + offset) * 255.).round() as u8;
+	(channel(red), channel(green), channel(blue))
+}
+
+// Decomposes 8-bit RGB channels into hue in degrees and saturation and value in 0..1
+// Achromatic colors report hue 0 and saturation 0, since no hue distinguishes them
+pub fn rgb_to_hsv(red: u8, green: u8, blue: u8) -> (f32, f32, f32) {
+	let (red, green, blue) = (red as f32 / 255., green as f32 / 255., blue as f32 / 255.);
+	let max = red.max(green).max(blue);
+	let min = red.min(green).min(blue);
+	let delta = max - min;
+
+	let hue = if delta == 0. {
+		0.
+	} else if max == red {
+		60. * ((green - blue) / delta).rem_euclid(6.)
+	} else if max == green {
+		60. * ((blue - red) / delta + 2.)
+	} else {
+		60. * ((red - green) / delta + 4.)
+	};
+	let saturation = if max == 0. { 0. } else { delta / max };
+	(hue, saturation, max)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn six_digit_hex_matches_the_equivalent_named_entry() {
+		let custom = ColorPalette::from_hex("#3194d6").unwrap();
+		assert_eq!(custom.get_color(Theme::Dark), ColorPalette::Accent.get_color(Theme::Dark));
+		assert_eq!(custom.get_color_linear(Theme::Dark), ColorPalette::Accent.get_color_linear(Theme::Dark));
+	}
+
+	#[test]
+	fn roles_resolve_through_the_active_theme() {
+		// The background flips from near-black to near-white between themes
+		assert_eq!(ColorPalette::Background.get_color(Theme::Dark), 0x16161b);
+		assert_eq!(ColorPalette::Background.get_color(Theme::Light), 0xf4f4f7);
+		// Fixed entries ignore the theme entirely
+		assert_eq!(ColorPalette::White.get_color(Theme::Dark), ColorPalette::White.get_color(Theme::Light));
+	}
+
+	#[test]
+	fn shorthand_hex_doubles_each_digit() {
+		assert_eq!(ColorPalette::from_hex("#fff").unwrap(), ColorPalette::Color(0xffffffff));
+		assert_eq!(ColorPalette::from_hex("#1a0").unwrap(), ColorPalette::Color(0x11aa00ff));
+	}
+
+	#[test]
+	fn eight_digit_hex_carries_alpha() {
+		let translucent = ColorPalette::from_hex("#11223344").unwrap();
+		assert_eq!(translucent, ColorPalette::Color(0x11223344));
+		assert!((translucent.get_color_linear(Theme::Dark).a - 0x44 as f64 / 255.).abs() < 1e-9);
+	}
+
+	#[test]
+	fn the_transfer_function_matches_known_values() {
+		// Reference values from the sRGB specification
+		assert_eq!(srgb_to_linear(0.), 0.);
+		assert_eq!(srgb_to_linear(1.), 1.);
+		assert!((srgb_to_linear(0.5) - 0.2140).abs() < 1e-4);
+		assert!((srgb_to_linear(0.2) - 0.0331).abs() < 1e-4);
+		// Below the 0.04045 threshold the curve is a straight 1/12.92 slope
+		assert!((srgb_to_linear(0.04) - 0.04 / 12.92).abs() < 1e-9);
+	}
+
+	#[test]
+	fn mid_gray_is_darker_in_linear_space() {
+		let gray = ColorPalette::from_hex("#808080").unwrap().get_color_linear(Theme::Dark);
+		assert!((gray.r - 0.2159).abs() < 1e-4);
+		assert_eq!(gray.r, gray.g);
+		assert_eq!(gray.g, gray.b);
+		// Alpha is coverage, not color, and must stay untouched
+		assert_eq!(gray.a, 1.);
+	}
+
+	#[test]
+	fn non_srgb_formats_skip_the_transfer_function() {
+		// An sRGB swap chain encodes on write, so it takes the linear conversion
+		let accent_linear = ColorPalette::Accent.get_color_for_format(Theme::Dark, wgpu::TextureFormat::Bgra8UnormSrgb);
+		assert_eq!(accent_linear, ColorPalette::Accent.get_color_linear(Theme::Dark));
+
+		// A non-sRGB swap chain applies no encoding, so the raw sRGB channels pass through
+		let accent_raw = ColorPalette::Accent.get_color_for_format(Theme::Dark, wgpu::TextureFormat::Bgra8Unorm);
+		assert!((accent_raw.r - 0x31 as f64 / 255.).abs() < 1e-9);
+		assert!(accent_raw.r > accent_linear.r);
+	}
+
+	#[test]
+	fn hsv_hits_the_primaries_and_secondaries() {
+		assert_eq!(hsv_to_rgb(0., 1., 1.), (255, 0, 0));
+		assert_eq!(hsv_to_rgb(60., 1., 1.), (255, 255, 0));
+		assert_eq!(hsv_to_rgb(120., 1., 1.), (0, 255, 0));
+		assert_eq!(hsv_to_rgb(240., 1., 1.), (0, 0, 255));
+		// Hues wrap, saturation zero is gray, and value zero is black regardless of the rest
+		assert_eq!(hsv_to_rgb(420., 1., 1.), (255, 255, 0));
+		assert_eq!(hsv_to_rgb(200., 0., 0.5), (128, 128, 128));
+		assert_eq!(hsv_to_rgb(200., 1., 0.), (0, 0, 0));
+	}
+
+	#[test]
+	fn rgb_survives_a_round_trip_through_hsv() {
+		// A lattice across the cube, including the corners, reproduces every channel exactly
+		for red in (0..=255).step_by(51) {
+			for green in (0..=255).step_by(51) {
+				for blue in (0..=255).step_by(51) {
+					let (hue, saturation, value) = rgb_to_hsv(red as u8, green as u8, blue as u8);
+					assert_eq!(hsv_to_rgb(hue, saturation, value), (red as u8, green as u8, blue as u8));
+				}
+			}
+		}
+
+		// The accent color specifically, since the picker reflects selections like it
+		let (hue, saturation, value) = rgb_to_hsv(0x31, 0x94, 0xd6);
+		assert_eq!(hsv_to_rgb(hue, saturation, value), (0x31, 0x94, 0xd6));
+		assert!(hue > 180. && hue < 240.);
+	}
+
+	#[test]
+	fn malformed_strings_are_rejected() {
+		assert_eq!(ColorPalette::from_hex("3194d6"), Err(ColorParseError::MissingHashPrefix));
+		assert_eq!(ColorPalette::from_hex("#12345"), Err(ColorParseError::InvalidLength(5)));
+		assert_eq!(ColorPalette::from_hex("#gggggg"), Err(ColorParseError::InvalidDigit));
+		assert_eq!(ColorPalette::from_hex("#"), Err(ColorParseError::InvalidLength(0)));
+	}
+}
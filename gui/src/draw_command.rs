@@ -0,0 +1,490 @@
+use crate::buffer_pool::BufferPool;
+use crate::color_palette::ColorPalette;
+use crate::geometry::{Rect, Size};
+use crate::pipeline::Pipeline;
+use crate::uniform_buffer::{self, UniformBuffer};
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct Vertex {
+	pub position: [f32; 2],
+}
+
+impl Vertex {
+	const ATTRIBUTES: [wgpu::VertexAttributeDescriptor; 1] = [wgpu::VertexAttributeDescriptor {
+		offset: 0,
+		shader_location: 0,
+		format: wgpu::VertexFormat::Float2,
+	}];
+
+	pub fn buffer_descriptor() -> wgpu::VertexBufferDescriptor<'static> {
+		wgpu::VertexBufferDescriptor {
+			stride: std::mem::size_of::<Vertex>() as wgpu::BufferAddress,
+			step_mode: wgpu::InputStepMode::Vertex,
+			attributes: &Vertex::ATTRIBUTES,
+		}
+	}
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct Vertex2DTextured {
+	pub position: [f32; 2],
+	pub uv: [f32; 2],
+}
+
+impl Vertex2DTextured {
+	const ATTRIBUTES: [wgpu::VertexAttributeDescriptor; 2] = [
+		wgpu::VertexAttributeDescriptor {
+			offset: 0,
+			shader_location: 0,
+			format: wgpu::VertexFormat::Float2,
+		},
+		wgpu::VertexAttributeDescriptor {
+			offset: 8,
+			shader_location: 1,
+			format: wgpu::VertexFormat::Float2,
+		},
+	];
+
+	pub fn buffer_descriptor() -> wgpu::VertexBufferDescriptor<'static> {
+		wgpu::VertexBufferDescriptor {
+			stride: std::mem::size_of::<Vertex2DTextured>() as wgpu::BufferAddress,
+			step_mode: wgpu::InputStepMode::Vertex,
+			attributes: &Vertex2DTextured::ATTRIBUTES,
+		}
+	}
+}
+
+// A vertex carrying its own color, for gradients and solid fills that need no texture
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct Vertex2DColored {
+	pub position: [f32; 2],
+	pub color: [f32; 4],
+}
+
+impl Vertex2DColored {
+	const ATTRIBUTES: [wgpu::VertexAttributeDescriptor; 2] = [
+		wgpu::VertexAttributeDescriptor {
+			offset: 0,
+			shader_location: 0,
+			format: wgpu::VertexFormat::Float2,
+		},
+		wgpu::VertexAttributeDescriptor {
+			offset: 8,
+			shader_location: 1,
+			format: wgpu::VertexFormat::Float4,
+		},
+	];
+
+	pub fn buffer_descriptor() -> wgpu::VertexBufferDescriptor<'static> {
+		wgpu::VertexBufferDescriptor {
+			stride: std::mem::size_of::<Vertex2DColored>() as wgpu::BufferAddress,
+			step_mode: wgpu::InputStepMode::Vertex,
+			attributes: &Vertex2DColored::ATTRIBUTES,
+		}
+	}
+}
+
+// A filled rectangle in normalized device coordinates, the building block of GUI panels
+pub struct Quad {
+	pub rect: Rect,
+	// Carried so the quad can be tinted once the GUI shader takes a color input
+	pub color: ColorPalette,
+}
+
+impl Quad {
+	// Two triangles covering the rectangle, wound counter-clockwise
+	pub const INDICES: [u16; 6] = [0, 1, 2, 2, 3, 0];
+
+	pub fn new(rect: Rect, color: ColorPalette) -> Self {
+		Self { rect, color }
+	}
+
+	// Corner positions counter-clockwise from the rectangle's origin corner
+	pub fn vertices(&self) -> [Vertex; 4] {
+		let Rect { x, y, width, height } = self.rect;
+		[
+			Vertex { position: [x, y] },
+			Vertex { position: [x + width, y] },
+			Vertex { position: [x + width, y + height] },
+			Vertex { position: [x, y + height] },
+		]
+	}
+}
+
+// Per-draw parameters of the SDF shape shaders, laid out to match their std140 uniform block: the
+// transform the vertex stage applies, then the fill color and shape the fragment stage evaluates
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct SdfUniforms {
+	pub transform: uniform_buffer::Matrix4,
+	pub color: [f32; 4],
+	// Half the shape's extents in logical pixels; the distance field evaluates in this space
+	pub half_size: [f32; 2],
+	pub corner_radius: f32,
+	// Rounds the block size up to std140's 16-byte alignment
+	pub padding: f32,
+}
+
+// The uniform block's byte size, which the SDF bind group binds in full
+pub const SDF_UNIFORM_SIZE: wgpu::BufferAddress = std::mem::size_of::<SdfUniforms>() as wgpu::BufferAddress;
+
+// Whether replay issues an indexed draw or walks the vertex buffer directly
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DrawKind {
+	Indexed,
+	NonIndexed,
+}
+
+// A self-contained unit of geometry, ready to be replayed into a render pass each frame
+pub struct DrawCommand {
+	pub pipeline_name: String,
+	pub vertex_buffer: wgpu::Buffer,
+	// None for non-indexed commands, which draw vertex_count vertices in buffer order
+	pub index_buffer: Option<wgpu::Buffer>,
+	// Byte sizes recorded so retired buffers can be keyed back into the BufferPool
+	pub vertex_buffer_size: wgpu::BufferAddress,
+	pub index_buffer_size: wgpu::BufferAddress,
+	pub vertex_count: u32,
+	pub index_count: u32,
+	pub index_format: wgpu::IndexFormat,
+	pub draw_kind: DrawKind,
+	// Bound at their indices during replay: slot 0 for the common texture group, higher slots
+	// for pipelines that split per-frame and per-object resources
+	pub bind_groups: Vec<wgpu::BindGroup>,
+	pub instance_buffer: Option<wgpu::Buffer>,
+	pub instance_count: u32,
+	// Kept alive here so the bind group referencing it stays valid for the command's lifetime
+	pub uniform_buffer: Option<UniformBuffer>,
+	// Restricts rasterization to this rectangle in logical pixels; None draws to the whole viewport
+	pub scissor: Option<Rect>,
+	// Small per-draw parameter bytes, written into uniform_buffer before the frame encodes so
+	// shaders reading the matrix uniform pick them up; only matrix-sized payloads apply
+	pub push_constants: Option<Vec<u8>>,
+	// The CPU copy of the index data, retained so wireframe mode can derive an edge list from it;
+	// empty for non-indexed commands
+	pub index_data: Vec<u8>,
+	// The derived line-list index buffer wireframe replay draws with; built on demand by
+	// ensure_wireframe_indices, since most commands never render in wireframe
+	pub wireframe_index_buffer: Option<wgpu::Buffer>,
+	pub wireframe_index_count: u32,
+}
+
+// Converts triangle-list indices into the line list drawing each triangle's edges exactly once;
+// an edge shared by adjacent triangles appears a single time, whichever way each triangle winds it
+pub fn wireframe_indices<I: Copy + Ord + std::hash::Hash>(indices: &[I]) -> Vec<I> {
+	let mut seen = std::collections::HashSet::new();
+	let mut lines = Vec::new();
+	for triangle in indices.chunks_exact(3) {
+		for &(a, b) in &[(triangle[0], triangle[1]), (triangle[1], triangle[2]), (triangle[2], triangle[0])] {
+			if seen.insert((a.min(b), a.max(b))) {
+				lines.push(a);
+				lines.push(b);
+			}
+		}
+	}
+	lines
+}
+
+// The byte-level counterpart of wireframe_indices, dispatching on the command's index format
+fn wireframe_index_bytes(index_bytes: &[u8], index_format: wgpu::IndexFormat) -> Vec<u8> {
+	match index_format {
+		wgpu::IndexFormat::Uint16 => bytemuck::cast_slice(&wireframe_indices::<u16>(bytemuck::cast_slice(index_bytes))).to_vec(),
+		wgpu::IndexFormat::Uint32 => bytemuck::cast_slice(&wireframe_indices::<u32>(bytemuck::cast_slice(index_bytes))).to_vec(),
+	}
+}
+
+impl DrawCommand {
+	pub fn new<V: bytemuck::Pod>(device: &wgpu::Device, pipeline_name: String, vertices: &[V], indices: &[u16], bind_group: wgpu::BindGroup) -> Self {
+		DrawCommand::with_index_format(device, pipeline_name, vertices, bytemuck::cast_slice(indices), indices.len() as u32, wgpu::IndexFormat::Uint16, vec![bind_group])
+	}
+
+	// Like new, but binding one group per slot for pipelines built with several bind group layouts
+	pub fn new_multi<V: bytemuck::Pod>(device: &wgpu::Device, pipeline_name: String, vertices: &[V], indices: &[u16], bind_groups: Vec<wgpu::BindGroup>) -> Self {
+		DrawCommand::with_index_format(device, pipeline_name, vertices, bytemuck::cast_slice(indices), indices.len() as u32, wgpu::IndexFormat::Uint16, bind_groups)
+	}
+
+	// A filled rectangle, saving callers from hand-authoring quad vertices and indices
+	pub fn quad(device: &wgpu::Device, pipeline_name: String, rect: Rect, color: ColorPalette, bind_group: wgpu::BindGroup) -> Self {
+		let quad = Quad::new(rect, color);
+		DrawCommand::new(device, pipeline_name, &quad.vertices(), &Quad::INDICES, bind_group)
+	}
+
+	// A single quad whose fragment shader evaluates a rounded-box signed distance field, giving an
+	// anti-aliased rectangle with `corner_radius`-rounded corners at any scale without tessellation
+	// or multisampling. `rect` is in logical pixels; `pipeline` must be the cached SDF pipeline,
+	// whose bind group layout the shape parameters bind against
+	pub fn rounded_rect(device: &wgpu::Device, pipeline_name: String, pipeline: &Pipeline, rect: Rect, corner_radius: f32, color: [f32; 4], viewport: Size) -> Self {
+		let corners = rect.to_ndc_vertices(viewport);
+		const UVS: [[f32; 2]; 4] = [[0., 1.], [1., 1.], [1., 0.], [0., 0.]];
+		let vertices: Vec<Vertex2DTextured> = corners.iter().zip(UVS.iter()).map(|(&position, &uv)| Vertex2DTextured { position, uv }).collect();
+
+		let half_size = [rect.width / 2., rect.height / 2.];
+		let uniforms = SdfUniforms {
+			transform: uniform_buffer::IDENTITY,
+			color,
+			half_size,
+			// Beyond the shorter half extent the corner discs would overlap and invert the field
+			corner_radius: corner_radius.max(0.).min(half_size[0]).min(half_size[1]),
+			padding: 0.,
+		};
+		let buffer = device.create_buffer_with_data(bytemuck::bytes_of(&uniforms), wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST);
+		let uniform_buffer = UniformBuffer { buffer };
+		let bind_group = pipeline.create_sdf_bind_group(device, &uniform_buffer);
+
+		let mut command = DrawCommand::new(device, pipeline_name, &vertices, &Quad::INDICES, bind_group);
+		command.uniform_buffer = Some(uniform_buffer);
+		command
+	}
+
+	// An anti-aliased circle: a rounded rectangle over the circle's bounding square whose corner
+	// radius is the full radius, which rounds the square down to its inscribed circle
+	pub fn circle(device: &wgpu::Device, pipeline_name: String, pipeline: &Pipeline, center: (f32, f32), radius: f32, color: [f32; 4], viewport: Size) -> Self {
+		let rect = Rect::new(center.0 - radius, center.1 - radius, radius * 2., radius * 2.);
+		DrawCommand::rounded_rect(device, pipeline_name, pipeline, rect, radius, color, viewport)
+	}
+
+	// Draws the vertices in buffer order with no index buffer, e.g. a short triangle list or strip
+	// whose vertices are not worth deduplicating
+	pub fn new_unindexed<V: bytemuck::Pod>(device: &wgpu::Device, pipeline_name: String, vertices: &[V], bind_group: wgpu::BindGroup) -> Self {
+		let vertex_bytes: &[u8] = bytemuck::cast_slice(vertices);
+		let vertex_buffer = device.create_buffer_with_data(vertex_bytes, wgpu::BufferUsage::VERTEX);
+
+		Self {
+			pipeline_name,
+			vertex_buffer,
+			index_buffer: None,
+			vertex_buffer_size: vertex_bytes.len() as wgpu::BufferAddress,
+			index_buffer_size: 0,
+			vertex_count: vertices.len() as u32,
+			index_count: 0,
+			index_format: wgpu::IndexFormat::Uint16,
+			draw_kind: DrawKind::NonIndexed,
+			bind_groups: vec![bind_group],
+			instance_buffer: None,
+			instance_count: 1,
+			uniform_buffer: None,
+			scissor: None,
+			push_constants: None,
+			index_data: Vec::new(),
+			wireframe_index_buffer: None,
+			wireframe_index_count: 0,
+		}
+	}
+
+	// Indexes with 32 bits per entry, for meshes too large for the u16 65536-vertex ceiling
+	pub fn new_u32<V: bytemuck::Pod>(device: &wgpu::Device, pipeline_name: String, vertices: &[V], indices: &[u32], bind_group: wgpu::BindGroup) -> Self {
+		DrawCommand::with_index_format(device, pipeline_name, vertices, bytemuck::cast_slice(indices), indices.len() as u32, wgpu::IndexFormat::Uint32, vec![bind_group])
+	}
+
+	// Draws the same indexed geometry once per instance, with per-instance attributes streamed from vertex buffer slot 1
+	pub fn new_instanced<V: bytemuck::Pod, I: bytemuck::Pod>(device: &wgpu::Device, pipeline_name: String, vertices: &[V], indices: &[u16], bind_group: wgpu::BindGroup, instances: &[I]) -> Self {
+		let mut command = DrawCommand::with_index_format(device, pipeline_name, vertices, bytemuck::cast_slice(indices), indices.len() as u32, wgpu::IndexFormat::Uint16, vec![bind_group]);
+		command.instance_buffer = Some(device.create_buffer_with_data(bytemuck::cast_slice(instances), wgpu::BufferUsage::VERTEX));
+		command.instance_count = instances.len() as u32;
+		command
+	}
+
+	// Like new, but draws its geometry buffers from the pool instead of always allocating
+	pub fn new_pooled<V: bytemuck::Pod>(device: &wgpu::Device, queue: &mut wgpu::Queue, pool: &mut BufferPool, pipeline_name: String, vertices: &[V], indices: &[u16], bind_group: wgpu::BindGroup) -> Self {
+		let vertex_bytes: &[u8] = bytemuck::cast_slice(vertices);
+		let index_bytes: &[u8] = bytemuck::cast_slice(indices);
+		let vertex_buffer = pool.acquire(device, queue, vertex_bytes, wgpu::BufferUsage::VERTEX);
+		let index_buffer = pool.acquire(device, queue, index_bytes, wgpu::BufferUsage::INDEX);
+
+		Self {
+			pipeline_name,
+			vertex_buffer,
+			index_buffer: Some(index_buffer),
+			vertex_buffer_size: vertex_bytes.len() as wgpu::BufferAddress,
+			index_buffer_size: index_bytes.len() as wgpu::BufferAddress,
+			vertex_count: vertices.len() as u32,
+			index_count: indices.len() as u32,
+			index_format: wgpu::IndexFormat::Uint16,
+			draw_kind: DrawKind::Indexed,
+			bind_groups: vec![bind_group],
+			instance_buffer: None,
+			instance_count: 1,
+			uniform_buffer: None,
+			scissor: None,
+			push_constants: None,
+			index_data: index_bytes.to_vec(),
+			wireframe_index_buffer: None,
+			wireframe_index_count: 0,
+		}
+	}
+
+	fn with_index_format<V: bytemuck::Pod>(device: &wgpu::Device, pipeline_name: String, vertices: &[V], index_bytes: &[u8], index_count: u32, index_format: wgpu::IndexFormat, bind_groups: Vec<wgpu::BindGroup>) -> Self {
+		// Upload the vertex and index data to GPU memory
+		let vertex_bytes: &[u8] = bytemuck::cast_slice(vertices);
+		let vertex_buffer = device.create_buffer_with_data(vertex_bytes, wgpu::BufferUsage::VERTEX);
+		let index_buffer = device.create_buffer_with_data(index_bytes, wgpu::BufferUsage::INDEX);
+
+		Self {
+			pipeline_name,
+			vertex_buffer,
+			index_buffer: Some(index_buffer),
+			vertex_buffer_size: vertex_bytes.len() as wgpu::BufferAddress,
+			index_buffer_size: index_bytes.len() as wgpu::BufferAddress,
+			vertex_count: vertices.len() as u32,
+			index_count,
+			index_format,
+			draw_kind: DrawKind::Indexed,
+			bind_groups,
+			instance_buffer: None,
+			instance_count: 1,
+			uniform_buffer: None,
+			scissor: None,
+			push_constants: None,
+			index_data: index_bytes.to_vec(),
+			wireframe_index_buffer: None,
+			wireframe_index_count: 0,
+		}
+	}
+
+	// Builds the line-list index buffer wireframe replay draws with, derived from the retained
+	// index data; a repeat call, or a non-indexed command, is a no-op
+	pub fn ensure_wireframe_indices(&mut self, device: &wgpu::Device) {
+		if self.wireframe_index_buffer.is_some() || self.draw_kind != DrawKind::Indexed {
+			return;
+		}
+		let line_bytes = wireframe_index_bytes(&self.index_data, self.index_format);
+		let index_size = match self.index_format {
+			wgpu::IndexFormat::Uint16 => 2,
+			wgpu::IndexFormat::Uint32 => 4,
+		};
+		self.wireframe_index_count = (line_bytes.len() / index_size) as u32;
+		self.wireframe_index_buffer = Some(device.create_buffer_with_data(&line_bytes, wgpu::BufferUsage::INDEX));
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::test_utils::create_test_device;
+
+	fn empty_bind_group(device: &wgpu::Device) -> wgpu::BindGroup {
+		let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor { bindings: &[], label: None });
+		device.create_bind_group(&wgpu::BindGroupDescriptor {
+			layout: &layout,
+			bindings: &[],
+			label: None,
+		})
+	}
+
+	#[test]
+	fn quad_vertices_cover_the_rect_corners() {
+		let quad = Quad::new(Rect::new(-0.5, -0.5, 1., 1.), ColorPalette::Accent);
+
+		let positions: Vec<[f32; 2]> = quad.vertices().iter().map(|vertex| vertex.position).collect();
+		assert_eq!(positions, vec![[-0.5, -0.5], [0.5, -0.5], [0.5, 0.5], [-0.5, 0.5]]);
+
+		// The index pattern references every corner and only those corners
+		assert!(Quad::INDICES.iter().all(|&index| index < 4));
+		for corner in 0..4 {
+			assert!(Quad::INDICES.contains(&corner));
+		}
+	}
+
+	#[test]
+	fn colored_vertices_interleave_position_then_color() {
+		let descriptor = Vertex2DColored::buffer_descriptor();
+		assert_eq!(descriptor.stride, 24);
+		// The color attribute starts right after the two position floats
+		assert_eq!(descriptor.attributes[1].offset, 8);
+		assert_eq!(descriptor.attributes[1].format, wgpu::VertexFormat::Float4);
+	}
+
+	#[test]
+	fn sdf_uniforms_fill_their_std140_block_exactly() {
+		// mat4 at offset 0, vec4 at 64, vec2 at 80, float at 88, padded out to the 16-byte boundary
+		assert_eq!(SDF_UNIFORM_SIZE, 96);
+	}
+
+	#[test]
+	fn u32_indices_are_not_truncated() {
+		let (device, _queue) = match create_test_device() {
+			Some(pair) => pair,
+			None => return,
+		};
+
+		// Index past the u16 ceiling so truncation to 16 bits would be caught
+		let vertices: Vec<Vertex> = (0..70_000).map(|i| Vertex { position: [i as f32, 0.] }).collect();
+		let indices: Vec<u32> = (0..70_000).collect();
+
+		let command = DrawCommand::new_u32(&device, String::from("test"), &vertices, &indices, empty_bind_group(&device));
+		assert_eq!(command.index_count, 70_000);
+		assert_eq!(command.index_format, wgpu::IndexFormat::Uint32);
+	}
+
+	#[test]
+	fn unindexed_commands_draw_their_vertex_count() {
+		let (device, _queue) = match create_test_device() {
+			Some(pair) => pair,
+			None => return,
+		};
+
+		let vertices = [Vertex { position: [0., 0.] }, Vertex { position: [1., 0.] }, Vertex { position: [0., 1.] }];
+		let command = DrawCommand::new_unindexed(&device, String::from("test"), &vertices, empty_bind_group(&device));
+
+		// Replay sees no index buffer and issues draw(0..3) instead of draw_indexed
+		assert_eq!(command.draw_kind, DrawKind::NonIndexed);
+		assert!(command.index_buffer.is_none());
+		assert_eq!(command.vertex_count, 3);
+		assert_eq!(command.index_count, 0);
+	}
+
+	#[test]
+	fn multiple_bind_groups_keep_their_slot_order() {
+		let (device, _queue) = match create_test_device() {
+			Some(pair) => pair,
+			None => return,
+		};
+
+		let vertices = [Vertex { position: [0., 0.] }, Vertex { position: [1., 0.] }, Vertex { position: [0., 1.] }];
+		let groups = vec![empty_bind_group(&device), empty_bind_group(&device)];
+		let command = DrawCommand::new_multi(&device, String::from("test"), &vertices, &[0u16, 1, 2], groups);
+		// Replay binds index 0 as the per-frame group and index 1 as the per-object group
+		assert_eq!(command.bind_groups.len(), 2);
+	}
+
+	#[test]
+	fn wireframe_edges_shared_between_triangles_draw_once() {
+		// A quad's two triangles share the diagonal, so its four sides plus the diagonal make five
+		// edges; without deduplication the edge list would draw six
+		let lines = wireframe_indices(&Quad::INDICES);
+		assert_eq!(lines.len(), 10);
+	}
+
+	#[test]
+	fn wireframe_index_buffers_build_once_on_demand() {
+		let (device, _queue) = match create_test_device() {
+			Some(pair) => pair,
+			None => return,
+		};
+
+		let vertices = [Vertex { position: [0., 0.] }, Vertex { position: [1., 0.] }, Vertex { position: [0., 1.] }];
+		let mut command = DrawCommand::new(&device, String::from("test"), &vertices, &[0u16, 1, 2], empty_bind_group(&device));
+		assert!(command.wireframe_index_buffer.is_none());
+
+		// A lone triangle has three edges, drawn as three lines
+		command.ensure_wireframe_indices(&device);
+		assert_eq!(command.wireframe_index_count, 6);
+		assert!(command.wireframe_index_buffer.is_some());
+	}
+
+	#[test]
+	fn u16_constructor_keeps_the_small_format() {
+		let (device, _queue) = match create_test_device() {
+			Some(pair) => pair,
+			None => return,
+		};
+
+		let vertices = [Vertex { position: [0., 0.] }, Vertex { position: [1., 0.] }, Vertex { position: [0., 1.] }];
+		let command = DrawCommand::new(&device, String::from("test"), &vertices, &[0u16, 1, 2], empty_bind_group(&device));
+		assert_eq!(command.index_count, 3);
+		assert_eq!(command.index_format, wgpu::IndexFormat::Uint16);
+	}
+}
@@ -0,0 +1,111 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+// How many recent frames contribute to the rolling average
+const ROLLING_WINDOW: usize = 60;
+
+// Rendering performance counters, updated once per frame at the top of render()
+pub struct FrameStats {
+	last_frame_start: Option<Instant>,
+	pub last_frame_duration: Duration,
+	pub frame_count: u64,
+	recent_durations: VecDeque<Duration>,
+	// How long the last frame's passes took on the GPU itself; None until a timed frame completes,
+	// and forever on adapters without timestamp queries
+	pub last_gpu_pass_duration: Option<Duration>,
+}
+
+impl FrameStats {
+	pub fn new() -> Self {
+		Self {
+			last_frame_start: None,
+			last_frame_duration: Duration::from_secs(0),
+			frame_count: 0,
+			recent_durations: VecDeque::with_capacity(ROLLING_WINDOW),
+			last_gpu_pass_duration: None,
+		}
+	}
+
+	// Records a GPU pass duration read back from the timestamp queries; arrives out of band from
+	// begin_frame since the measurement only exists after the frame's commands complete
+	pub fn record_gpu_pass(&mut self, duration: Duration) {
+		self.last_gpu_pass_duration = Some(duration);
+	}
+
+	// Marks the start of a new frame, measuring the previous frame's duration
+	pub fn begin_frame(&mut self) {
+		let now = Instant::now();
+
+		if let Some(last_frame_start) = self.last_frame_start {
+			self.last_frame_duration = now - last_frame_start;
+			if self.recent_durations.len() == ROLLING_WINDOW {
+				self.recent_durations.pop_front();
+			}
+			self.recent_durations.push_back(self.last_frame_duration);
+		}
+
+		self.last_frame_start = Some(now);
+		self.frame_count += 1;
+	}
+
+	// The mean duration over the last 60 frames
+	pub fn average_frame_duration(&self) -> Duration {
+		if self.recent_durations.is_empty() {
+			return Duration::from_secs(0);
+		}
+		self.recent_durations.iter().sum::<Duration>() / self.recent_durations.len() as u32
+	}
+
+	pub fn fps(&self) -> f32 {
+		let average = self.average_frame_duration();
+		if average.as_secs_f32() == 0. {
+			return 0.;
+		}
+		1. / average.as_secs_f32()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn counts_frames_and_measures_durations() {
+		let mut stats = FrameStats::new();
+		assert_eq!(stats.frame_count, 0);
+		assert_eq!(stats.fps(), 0.);
+
+		stats.begin_frame();
+		std::thread::sleep(Duration::from_millis(5));
+		stats.begin_frame();
+
+		assert_eq!(stats.frame_count, 2);
+		assert!(stats.last_frame_duration >= Duration::from_millis(5));
+		assert!(stats.fps() > 0.);
+	}
+
+	#[test]
+	fn gpu_pass_durations_arrive_out_of_band() {
+		let mut stats = FrameStats::new();
+		assert!(stats.last_gpu_pass_duration.is_none());
+
+		stats.begin_frame();
+		stats.record_gpu_pass(Duration::from_micros(250));
+		assert_eq!(stats.last_gpu_pass_duration, Some(Duration::from_micros(250)));
+
+		// The next frame keeps the previous measurement until a new one is read back
+		stats.begin_frame();
+		assert_eq!(stats.last_gpu_pass_duration, Some(Duration::from_micros(250)));
+	}
+
+	#[test]
+	fn rolling_average_is_bounded_to_sixty_frames() {
+		let mut stats = FrameStats::new();
+		for _ in 0..200 {
+			stats.begin_frame();
+		}
+
+		assert_eq!(stats.frame_count, 200);
+		assert_eq!(stats.recent_durations.len(), ROLLING_WINDOW);
+	}
+}
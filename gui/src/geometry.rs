@@ -0,0 +1,170 @@
+// Shared rectangle math for layout, hit-testing, clipping, and dirty regions, so each subsystem
+// doesn't grow its own slightly different copy
+
+// An axis-aligned rectangle in logical pixels, used for node bounds and hit-testing
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Rect {
+	pub x: f32,
+	pub y: f32,
+	pub width: f32,
+	pub height: f32,
+}
+
+impl Rect {
+	pub fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+		Self { x, y, width, height }
+	}
+
+	// The left and top edges are inside, the right and bottom edges are not, so adjacent
+	// rectangles tile the plane without any point landing in two of them
+	pub fn contains(&self, x: f32, y: f32) -> bool {
+		x >= self.x && x < self.x + self.width && y >= self.y && y < self.y + self.height
+	}
+
+	// Whether the two rectangles overlap with positive area; merely touching edges do not count
+	pub fn intersects(&self, other: Rect) -> bool {
+		self.x < other.x + other.width && other.x < self.x + self.width && self.y < other.y + other.height && other.y < self.y + self.height
+	}
+
+	// The overlapping region, or None when the rectangles are disjoint or only touch at an edge
+	pub fn intersection(&self, other: Rect) -> Option<Rect> {
+		let x = self.x.max(other.x);
+		let y = self.y.max(other.y);
+		let width = (self.x + self.width).min(other.x + other.width) - x;
+		let height = (self.y + self.height).min(other.y + other.height) - y;
+		if width > 0. && height > 0. {
+			Some(Rect::new(x, y, width, height))
+		} else {
+			None
+		}
+	}
+
+	// The smallest rectangle containing both; the dirty region grows by unioning changed node bounds
+	pub fn union(&self, other: Rect) -> Rect {
+		let x = self.x.min(other.x);
+		let y = self.y.min(other.y);
+		Rect::new(x, y, (self.x + self.width).max(other.x + other.width) - x, (self.y + self.height).max(other.y + other.height) - y)
+	}
+
+	// The four corner vertices in normalized device coordinates, ordered to match a quad's UV
+	// corners: bottom-left, bottom-right, top-right, top-left in screen terms
+	pub fn to_ndc_vertices(self, viewport: Size) -> [[f32; 2]; 4] {
+		let bottom_left = viewport_to_ndc(self.x, self.y + self.height, viewport);
+		let bottom_right = viewport_to_ndc(self.x + self.width, self.y + self.height, viewport);
+		let top_right = viewport_to_ndc(self.x + self.width, self.y, viewport);
+		let top_left = viewport_to_ndc(self.x, self.y, viewport);
+		[bottom_left, bottom_right, top_right, top_left]
+	}
+}
+
+// Converts a point in logical pixels (y down from the top left) to normalized device coordinates
+// (y up, -1..1 with the origin at the viewport's center)
+pub fn viewport_to_ndc(px: f32, py: f32, viewport: Size) -> [f32; 2] {
+	[px / viewport.width * 2. - 1., 1. - py / viewport.height * 2.]
+}
+
+// The inverse of viewport_to_ndc, mapping clip-space coordinates back to logical pixels
+pub fn ndc_to_viewport(nx: f32, ny: f32, viewport: Size) -> [f32; 2] {
+	[(nx + 1.) / 2. * viewport.width, (1. - ny) / 2. * viewport.height]
+}
+
+// A width and height in logical pixels
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Size {
+	pub width: f32,
+	pub height: f32,
+}
+
+impl Size {
+	pub fn new(width: f32, height: f32) -> Self {
+		Self { width, height }
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn contains_includes_the_near_edges_and_excludes_the_far_ones() {
+		let rect = Rect::new(10., 20., 30., 40.);
+
+		assert!(rect.contains(10., 20.));
+		assert!(rect.contains(39., 59.));
+		// The right and bottom edges belong to the neighboring rectangle
+		assert!(!rect.contains(40., 20.));
+		assert!(!rect.contains(10., 60.));
+		assert!(!rect.contains(9., 20.));
+	}
+
+	#[test]
+	fn overlapping_rectangles_intersect() {
+		let a = Rect::new(0., 0., 50., 50.);
+		let b = Rect::new(30., 10., 40., 20.);
+
+		assert!(a.intersects(b));
+		assert!(b.intersects(a));
+		assert_eq!(a.intersection(b), Some(Rect::new(30., 10., 20., 20.)));
+	}
+
+	#[test]
+	fn edge_touching_rectangles_do_not_intersect() {
+		let a = Rect::new(0., 0., 50., 50.);
+
+		// Sharing the x = 50 edge gives zero overlap area
+		let beside = Rect::new(50., 0., 20., 50.);
+		assert!(!a.intersects(beside));
+		assert_eq!(a.intersection(beside), None);
+
+		// Sharing only the bottom-right corner point
+		let cornered = Rect::new(50., 50., 10., 10.);
+		assert!(!a.intersects(cornered));
+		assert_eq!(a.intersection(cornered), None);
+
+		// Fully disjoint rectangles
+		let far = Rect::new(100., 100., 10., 10.);
+		assert!(!a.intersects(far));
+		assert_eq!(a.intersection(far), None);
+	}
+
+	#[test]
+	fn union_spans_both_rectangles() {
+		let a = Rect::new(0., 0., 10., 10.);
+		let b = Rect::new(20., 5., 10., 30.);
+
+		assert_eq!(a.union(b), Rect::new(0., 0., 30., 35.));
+		// A rectangle unioned with one it contains is unchanged
+		assert_eq!(a.union(Rect::new(2., 2., 5., 5.)), a);
+	}
+
+	#[test]
+	fn ndc_conversion_flips_y_and_spans_minus_one_to_one() {
+		let viewport = Size::new(200., 100.);
+
+		// The viewport's corners map to clip space's corners, with y flipped
+		assert_eq!(viewport_to_ndc(0., 0., viewport), [-1., 1.]);
+		assert_eq!(viewport_to_ndc(200., 0., viewport), [1., 1.]);
+		assert_eq!(viewport_to_ndc(0., 100., viewport), [-1., -1.]);
+		assert_eq!(viewport_to_ndc(200., 100., viewport), [1., -1.]);
+		// The viewport's center is clip space's origin
+		assert_eq!(viewport_to_ndc(100., 50., viewport), [0., 0.]);
+
+		// The full viewport maps to the full clip space square
+		assert_eq!(Rect::new(0., 0., 200., 100.).to_ndc_vertices(viewport), [[-1., -1.], [1., -1.], [1., 1.], [-1., 1.]]);
+		// The top-left quarter covers clip space's upper-left quadrant
+		assert_eq!(Rect::new(0., 0., 100., 50.).to_ndc_vertices(viewport), [[-1., 0.], [0., 0.], [0., 1.], [-1., 1.]]);
+	}
+
+	#[test]
+	fn ndc_and_viewport_conversions_are_inverses() {
+		let viewport = Size::new(1280., 720.);
+
+		for &(px, py) in &[(0., 0.), (640., 360.), (1280., 720.), (17., 693.)] {
+			let [nx, ny] = viewport_to_ndc(px, py, viewport);
+			let [rx, ry] = ndc_to_viewport(nx, ny, viewport);
+			// The round trip loses a little precision to the f32 divisions
+			assert!((rx - px).abs() < 1e-3 && (ry - py).abs() < 1e-3);
+		}
+		assert_eq!(ndc_to_viewport(0., 0., viewport), [640., 360.]);
+	}
+}
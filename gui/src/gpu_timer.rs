@@ -0,0 +1,30 @@
+use std::time::{Duration, Instant};
+
+// Approximates how long the frame's work takes on the GPU, as opposed to the wall-clock numbers
+// FrameStats keeps. This wgpu version exposes no timestamp queries, so the timer instead blocks
+// right after the submit until the device has drained the frame and measures that wait: close to
+// the pass cost on the GPU, though it includes driver scheduling a hardware timestamp would exclude
+// Timing is opt-in through Application::set_gpu_timing, so the stall only exists while someone is
+// actually measuring
+#[derive(Default)]
+pub struct GpuTimer;
+
+impl GpuTimer {
+	pub fn new() -> Self {
+		Self
+	}
+
+	// Blocks until the device finishes the submitted frame and reports how long the drain took;
+	// call immediately after the submit, while the frame is the only work in flight
+	// None when the wait finished within the clock's resolution, e.g. on an already idle device
+	pub fn read_duration(&self, device: &wgpu::Device) -> Option<Duration> {
+		let submitted = Instant::now();
+		device.poll(wgpu::Maintain::Wait);
+		let duration = submitted.elapsed();
+		if duration.as_nanos() == 0 {
+			None
+		} else {
+			Some(duration)
+		}
+	}
+}
@@ -0,0 +1,756 @@
+use crate::color_palette::ColorPalette;
+use crate::geometry::Rect;
+use crate::gui_tree::{ClipboardEvent, EventPropagation, FileDropEvent, KeyEvent, PointerEvent, ScrollEvent};
+
+// Editable single-line text state for text field nodes
+// caret and the selection anchor are byte offsets into `text`, always kept on char boundaries
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct TextField {
+	pub text: String,
+	pub caret: usize,
+	// Where the selection started; the selected range runs between it and the caret
+	pub selection_anchor: Option<usize>,
+}
+
+impl TextField {
+	pub fn new(text: impl Into<String>) -> Self {
+		let text = text.into();
+		let caret = text.len();
+		Self { text, caret, selection_anchor: None }
+	}
+
+	// The selected byte range in ascending order, or None when nothing is selected
+	pub fn selection(&self) -> Option<(usize, usize)> {
+		match self.selection_anchor {
+			Some(anchor) if anchor != self.caret => Some((anchor.min(self.caret), anchor.max(self.caret))),
+			_ => None,
+		}
+	}
+
+	pub fn selected_text(&self) -> Option<&str> {
+		self.selection().map(|(start, end)| &self.text[start..end])
+	}
+
+	pub fn select_all(&mut self) {
+		self.selection_anchor = Some(0);
+		self.caret = self.text.len();
+	}
+
+	pub fn insert(&mut self, character: char) {
+		self.delete_selection();
+		self.text.insert(self.caret, character);
+		self.caret += character.len_utf8();
+	}
+
+	// Inserts a whole string at the caret, e.g. pasted clipboard text
+	pub fn insert_str(&mut self, text: &str) {
+		self.delete_selection();
+		self.text.insert_str(self.caret, text);
+		self.caret += text.len();
+	}
+
+	pub fn backspace(&mut self) {
+		if self.delete_selection() {
+			return;
+		}
+		if self.caret > 0 {
+			let previous = prev_char_boundary(&self.text, self.caret);
+			self.text.replace_range(previous..self.caret, "");
+			self.caret = previous;
+		}
+	}
+
+	pub fn delete(&mut self) {
+		if self.delete_selection() {
+			return;
+		}
+		if self.caret < self.text.len() {
+			let next = next_char_boundary(&self.text, self.caret);
+			self.text.replace_range(self.caret..next, "");
+		}
+	}
+
+	pub fn move_left(&mut self) {
+		// With a selection, a plain arrow collapses the caret to the selection edge
+		match self.selection() {
+			Some((start, _)) => self.caret = start,
+			None => self.caret = prev_char_boundary(&self.text, self.caret),
+		}
+		self.selection_anchor = None;
+	}
+
+	pub fn move_right(&mut self) {
+		match self.selection() {
+			Some((_, end)) => self.caret = end,
+			None => self.caret = next_char_boundary(&self.text, self.caret),
+		}
+		self.selection_anchor = None;
+	}
+
+	// Applies a key press as an edit; releases and unmapped keys leave the field untouched
+	pub fn handle_key(&mut self, event: KeyEvent) {
+		if event.state != winit::event::ElementState::Pressed {
+			return;
+		}
+		match event.key {
+			winit::event::VirtualKeyCode::Back => self.backspace(),
+			winit::event::VirtualKeyCode::Delete => self.delete(),
+			winit::event::VirtualKeyCode::Left => self.move_left(),
+			winit::event::VirtualKeyCode::Right => self.move_right(),
+			winit::event::VirtualKeyCode::Home => {
+				self.caret = 0;
+				self.selection_anchor = None;
+			}
+			winit::event::VirtualKeyCode::End => {
+				self.caret = self.text.len();
+				self.selection_anchor = None;
+			}
+			key => {
+				if let Some(character) = character_for(key) {
+					self.insert(character);
+				}
+			}
+		}
+	}
+
+	// Removes the selected range, leaving the caret at its start; false when nothing was selected
+	fn delete_selection(&mut self) -> bool {
+		match self.selection() {
+			Some((start, end)) => {
+				self.text.replace_range(start..end, "");
+				self.caret = start;
+				self.selection_anchor = None;
+				true
+			}
+			None => {
+				self.selection_anchor = None;
+				false
+			}
+		}
+	}
+}
+
+// The largest char boundary strictly before index, stopping at the start of the string
+fn prev_char_boundary(text: &str, index: usize) -> usize {
+	text[..index].char_indices().next_back().map(|(boundary, _)| boundary).unwrap_or(0)
+}
+
+// The smallest char boundary strictly after index, stopping at the end of the string
+fn next_char_boundary(text: &str, index: usize) -> usize {
+	text[index..].chars().next().map(|character| index + character.len_utf8()).unwrap_or(index)
+}
+
+// The character a key press types, for the keys virtual keycodes can represent without layout data
+// TODO: Forward winit's ReceivedCharacter events instead, which carry real layout-aware characters
+fn character_for(key: winit::event::VirtualKeyCode) -> Option<char> {
+	use winit::event::VirtualKeyCode::*;
+	let character = match key {
+		A => 'a',
+		B => 'b',
+		C => 'c',
+		D => 'd',
+		E => 'e',
+		F => 'f',
+		G => 'g',
+		H => 'h',
+		I => 'i',
+		J => 'j',
+		K => 'k',
+		L => 'l',
+		M => 'm',
+		N => 'n',
+		O => 'o',
+		P => 'p',
+		Q => 'q',
+		R => 'r',
+		S => 's',
+		T => 't',
+		U => 'u',
+		V => 'v',
+		W => 'w',
+		X => 'x',
+		Y => 'y',
+		Z => 'z',
+		Key0 | Numpad0 => '0',
+		Key1 | Numpad1 => '1',
+		Key2 | Numpad2 => '2',
+		Key3 | Numpad3 => '3',
+		Key4 | Numpad4 => '4',
+		Key5 | Numpad5 => '5',
+		Key6 | Numpad6 => '6',
+		Key7 | Numpad7 => '7',
+		Key8 | Numpad8 => '8',
+		Key9 | Numpad9 => '9',
+		Space => ' ',
+		Minus => '-',
+		Period => '.',
+		_ => return None,
+	};
+	Some(character)
+}
+
+// A draggable value control: the node's own quad is the track and the thumb draws at the value's
+// position along it
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Slider {
+	pub min: f32,
+	pub max: f32,
+	pub value: f32,
+	// Values the drag passed through since the widget last drained them, oldest first
+	#[serde(skip)]
+	pub pending_changes: Vec<f32>,
+}
+
+impl Slider {
+	pub fn new(min: f32, max: f32, value: f32) -> Self {
+		Self {
+			min,
+			max,
+			value: value.max(min).min(max),
+			pending_changes: Vec::new(),
+		}
+	}
+
+	// The value the cursor at `x` maps to within the track's bounds, clamped to the range
+	pub fn value_at(&self, bounds: Rect, x: f32) -> f32 {
+		if bounds.width <= 0. {
+			return self.min;
+		}
+		let fraction = ((x - bounds.x) / bounds.width).clamp(0., 1.);
+		self.min + fraction * (self.max - self.min)
+	}
+
+	// Sets the value, clamped to the range, queueing a change event; false when nothing changed
+	pub fn set_value(&mut self, value: f32) -> bool {
+		let clamped = value.max(self.min).min(self.max);
+		if clamped == self.value {
+			return false;
+		}
+		self.value = clamped;
+		self.pending_changes.push(clamped);
+		true
+	}
+
+	// Hands the queued change events to the widget and starts collecting afresh
+	pub fn take_changes(&mut self) -> Vec<f32> {
+		std::mem::take(&mut self.pending_changes)
+	}
+
+	// The current value's normalized 0..1 position along the track
+	pub fn fraction(&self) -> f32 {
+		if self.max <= self.min {
+			return 0.;
+		}
+		(self.value - self.min) / (self.max - self.min)
+	}
+}
+
+// A button's visual state, driven by the pointer: Pressed shows while the held pointer is inside
+// A deserialized button comes back in its resting state; the pointer rebuilds the rest
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ButtonState {
+	#[default]
+	Normal,
+	Hover,
+	Pressed,
+}
+
+// A clickable control that repaints per interaction state and queues a click on a full
+// press-then-release within its bounds
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Button {
+	// Drawn over the button through the text renderer when present
+	pub label: Option<String>,
+	// The palette role the node draws with in each state
+	pub normal: ColorPalette,
+	pub hover: ColorPalette,
+	pub pressed: ColorPalette,
+	#[serde(skip)]
+	pub state: ButtonState,
+	// True from the press on this button until its release, even while the cursor is away,
+	// so leaving and returning mid-press restores the Pressed visuals
+	#[serde(skip)]
+	pub held: bool,
+	// Clicks fired since the widget last drained them
+	#[serde(skip)]
+	pub pending_clicks: usize,
+}
+
+impl Button {
+	pub fn new(normal: ColorPalette, hover: ColorPalette, pressed: ColorPalette) -> Self {
+		Self {
+			label: None,
+			normal,
+			hover,
+			pressed,
+			state: ButtonState::Normal,
+			held: false,
+			pending_clicks: 0,
+		}
+	}
+
+	// The palette role the current state draws with
+	pub fn color(&self) -> ColorPalette {
+		match self.state {
+			ButtonState::Normal => self.normal,
+			ButtonState::Hover => self.hover,
+			ButtonState::Pressed => self.pressed,
+		}
+	}
+
+	// A press inside the bounds starts a hold and shows the pressed visuals
+	pub fn press(&mut self) {
+		self.held = true;
+		self.state = ButtonState::Pressed;
+	}
+
+	// Ends the hold; only a release back inside the bounds counts as a click
+	pub fn release(&mut self, inside: bool) {
+		if self.held && inside {
+			self.pending_clicks += 1;
+		}
+		self.held = false;
+		self.state = if inside { ButtonState::Hover } else { ButtonState::Normal };
+	}
+
+	// Updates the state for the cursor entering or leaving the bounds; true when the visuals changed
+	// Re-entering during a hold restores Pressed rather than Hover
+	pub fn hover(&mut self, inside: bool) -> bool {
+		let state = match (inside, self.held) {
+			(true, true) => ButtonState::Pressed,
+			(true, false) => ButtonState::Hover,
+			(false, _) => ButtonState::Normal,
+		};
+		if state == self.state {
+			return false;
+		}
+		self.state = state;
+		true
+	}
+
+	// Hands the queued clicks to the widget and starts counting afresh
+	pub fn take_clicks(&mut self) -> usize {
+		std::mem::take(&mut self.pending_clicks)
+	}
+}
+
+// Which of a color picker's two regions a drag is adjusting
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorPickerRegion {
+	HueBar,
+	SaturationValue,
+}
+
+// A two-region color control: a saturation/value square beside a vertical hue bar; drags on either
+// region recompose the selection and queue it as the same Color values ColorPalette carries
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ColorPicker {
+	// Hue in degrees within 0..360; saturation and value within 0..1
+	pub hue: f32,
+	pub saturation: f32,
+	pub value: f32,
+	// Carried through unchanged into every emitted color
+	pub alpha: u8,
+	// The region the press landed in, acting as capture: a drag that wanders into the other region
+	// keeps adjusting the control it started on until the release
+	#[serde(skip)]
+	pub active_region: Option<ColorPickerRegion>,
+	// Colors the drag passed through since the widget last drained them, oldest first
+	#[serde(skip)]
+	pub pending_changes: Vec<ColorPalette>,
+}
+
+impl ColorPicker {
+	pub fn new(rgba: u32) -> Self {
+		let mut picker = Self {
+			hue: 0.,
+			saturation: 0.,
+			value: 0.,
+			alpha: 0xff,
+			active_region: None,
+			pending_changes: Vec::new(),
+		};
+		picker.set_rgba(rgba);
+		picker
+	}
+
+	// The current selection in the 0xRRGGBBAA form ColorPalette::Color carries
+	pub fn color(&self) -> ColorPalette {
+		let (red, green, blue) = crate::color_palette::hsv_to_rgb(self.hue, self.saturation, self.value);
+		ColorPalette::Color((red as u32) << 24 | (green as u32) << 16 | (blue as u32) << 8 | self.alpha as u32)
+	}
+
+	// Points the picker at an externally-chosen color, e.g. the currently-selected object's, without
+	// queueing a change event
+	// Achromatic colors keep the previous hue so the hue thumb does not jump to red
+	pub fn set_rgba(&mut self, rgba: u32) {
+		let (hue, saturation, value) = crate::color_palette::rgb_to_hsv((rgba >> 24) as u8, (rgba >> 16) as u8, (rgba >> 8) as u8);
+		if saturation > 0. && value > 0. {
+			self.hue = hue;
+		}
+		self.saturation = saturation;
+		self.value = value;
+		self.alpha = rgba as u8;
+	}
+
+	// Sets the hue in degrees, clamped to 0..360, queueing the recomposed color; false when nothing changed
+	pub fn set_hue(&mut self, hue: f32) -> bool {
+		let clamped = hue.clamp(0., 360.);
+		if clamped == self.hue {
+			return false;
+		}
+		self.hue = clamped;
+		self.pending_changes.push(self.color());
+		true
+	}
+
+	// Sets both of the square's axes at once, clamped to 0..1, queueing the recomposed color likewise
+	pub fn set_saturation_value(&mut self, saturation: f32, value: f32) -> bool {
+		let saturation = saturation.clamp(0., 1.);
+		let value = value.clamp(0., 1.);
+		if saturation == self.saturation && value == self.value {
+			return false;
+		}
+		self.saturation = saturation;
+		self.value = value;
+		self.pending_changes.push(self.color());
+		true
+	}
+
+	// Hands the queued change events to the widget and starts collecting afresh
+	pub fn take_changes(&mut self) -> Vec<ColorPalette> {
+		std::mem::take(&mut self.pending_changes)
+	}
+}
+
+// Which axis a node stacks its children along
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum FlexDirection {
+	Row,
+	Column,
+}
+
+// A single rectangular element in the GUI hierarchy
+// Serialization skips the runtime-only fields — layout results, the hover cursor, and the pending
+// event queues — which the next layout pass and event dispatch rebuild from scratch
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct GuiNode {
+	// Layout inputs: children stack along `direction`, splitting leftover space by `grow`
+	pub direction: FlexDirection,
+	pub grow: f32,
+	pub padding: f32,
+	// Fixed main- or cross-axis sizes; None means sized by the layout pass
+	pub width: Option<f32>,
+	pub height: Option<f32>,
+	// Where the last layout pass placed this node, in logical pixels; hit-testing reads this
+	#[serde(skip)]
+	pub computed_bounds: Rect,
+	pub color: ColorPalette,
+	// Containers that only arrange children set this false and emit no geometry
+	pub visible: bool,
+	// A disabled control stays on screen but Tab traversal skips over it
+	pub disabled: bool,
+	// Stacking order among overlapping nodes; higher values draw on top, ties keep tree order
+	pub z_index: i32,
+	// Clips descendants to this node's bounds, e.g. so scrolled content cannot spill out of its panel
+	pub clip: bool,
+	// The mouse cursor shown while this node is hovered, e.g. a resize arrow on a panel divider
+	#[serde(skip)]
+	pub cursor: winit::window::CursorIcon,
+	// Receives pointer events aimed at descendants on the way down (capture) or back up (bubble)
+	// Both default off so events reach only their target; composite widgets opt in
+	pub capture_pointer: bool,
+	pub bubble_pointer: bool,
+	// What this node's pointer handler returns during dispatch; Handled consumes the event there
+	pub pointer_propagation: EventPropagation,
+	// Present on editable text field nodes; keystrokes edit this instead of queueing as events
+	pub text_field: Option<TextField>,
+	// Present on slider nodes; pointer drags move its value along the node's bounds
+	pub slider: Option<Slider>,
+	// Present on button nodes; the pointer drives its visual state and queues its clicks
+	pub button: Option<Button>,
+	// Present on color picker nodes; pointer drags on its two regions recompose its selection
+	pub color_picker: Option<ColorPicker>,
+	// Keyboard events delivered while this node was focused, queued until the widget consumes them
+	#[serde(skip)]
+	pub pending_key_events: Vec<KeyEvent>,
+	// Pointer events delivered while the cursor was over this node, queued likewise
+	#[serde(skip)]
+	pub pending_pointer_events: Vec<PointerEvent>,
+	// Files dropped onto this node, queued until the widget loads or rejects them
+	#[serde(skip)]
+	pub pending_file_events: Vec<FileDropEvent>,
+	// Copy requests and pasted text delivered while this node was focused
+	#[serde(skip)]
+	pub pending_clipboard_events: Vec<ClipboardEvent>,
+	// How far this node's content is scrolled, in logical pixels
+	pub scroll_offset: (f32, f32),
+}
+
+impl GuiNode {
+	pub fn new(color: ColorPalette) -> Self {
+		Self {
+			direction: FlexDirection::Row,
+			grow: 0.,
+			padding: 0.,
+			width: None,
+			height: None,
+			computed_bounds: Rect::new(0., 0., 0., 0.),
+			color,
+			visible: true,
+			disabled: false,
+			z_index: 0,
+			clip: false,
+			cursor: winit::window::CursorIcon::Default,
+			capture_pointer: false,
+			bubble_pointer: false,
+			pointer_propagation: EventPropagation::Continue,
+			text_field: None,
+			slider: None,
+			button: None,
+			color_picker: None,
+			pending_key_events: Vec::new(),
+			pending_pointer_events: Vec::new(),
+			pending_file_events: Vec::new(),
+			pending_clipboard_events: Vec::new(),
+			scroll_offset: (0., 0.),
+		}
+	}
+
+	// Whether Tab traversal can land here: any visible, enabled node carrying an interactive widget
+	pub fn is_focusable(&self) -> bool {
+		self.visible && !self.disabled && (self.button.is_some() || self.slider.is_some() || self.text_field.is_some() || self.color_picker.is_some())
+	}
+
+	pub fn handle_key(&mut self, event: KeyEvent) {
+		// Text fields consume keys as edits; other widgets drain them from the pending queue
+		match &mut self.text_field {
+			Some(field) => field.handle_key(event),
+			None => self.pending_key_events.push(event),
+		}
+	}
+
+	pub fn handle_pointer(&mut self, event: PointerEvent) -> EventPropagation {
+		self.pending_pointer_events.push(event);
+		self.pointer_propagation
+	}
+
+	pub fn handle_clipboard(&mut self, event: ClipboardEvent) {
+		// Pasted text goes straight into a text field; Copy still queues so the application can
+		// read the selection back and place it on the clipboard
+		if let Some(field) = &mut self.text_field {
+			if let ClipboardEvent::Paste(text) = &event {
+				field.insert_str(text);
+				return;
+			}
+		}
+		self.pending_clipboard_events.push(event);
+	}
+
+	pub fn handle_file_drop(&mut self, event: FileDropEvent) {
+		self.pending_file_events.push(event);
+	}
+
+	pub fn handle_scroll(&mut self, event: ScrollEvent) {
+		self.scroll_offset.0 += event.dx;
+		self.scroll_offset.1 += event.dy;
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use winit::event::{ElementState, VirtualKeyCode};
+
+	fn pressed(key: VirtualKeyCode) -> KeyEvent {
+		KeyEvent { key, state: ElementState::Pressed }
+	}
+
+	#[test]
+	fn typing_edits_the_buffer_at_the_caret() {
+		let mut field = TextField::new("ac");
+		field.caret = 1;
+
+		field.handle_key(pressed(VirtualKeyCode::B));
+		assert_eq!(field.text, "abc");
+		assert_eq!(field.caret, 2);
+
+		field.handle_key(pressed(VirtualKeyCode::Back));
+		assert_eq!(field.text, "ac");
+		assert_eq!(field.caret, 1);
+
+		field.handle_key(pressed(VirtualKeyCode::Delete));
+		assert_eq!(field.text, "a");
+		assert_eq!(field.caret, 1);
+
+		// Key releases are not edits
+		field.handle_key(KeyEvent { key: VirtualKeyCode::B, state: ElementState::Released });
+		assert_eq!(field.text, "a");
+	}
+
+	#[test]
+	fn the_caret_moves_over_multibyte_characters() {
+		// "é" is two bytes and "日" is three; arrow keys must land on char boundaries, never inside them
+		let mut field = TextField::new("aé日");
+		assert_eq!(field.caret, 6);
+
+		field.move_left();
+		assert_eq!(field.caret, 3);
+		field.move_left();
+		assert_eq!(field.caret, 1);
+		field.move_left();
+		assert_eq!(field.caret, 0);
+		// Already at the start; another left stays put
+		field.move_left();
+		assert_eq!(field.caret, 0);
+
+		field.move_right();
+		assert_eq!(field.caret, 1);
+		field.backspace();
+		assert_eq!(field.text, "é日");
+		assert_eq!(field.caret, 0);
+		field.delete();
+		assert_eq!(field.text, "日");
+	}
+
+	#[test]
+	fn typing_replaces_the_selection() {
+		let mut field = TextField::new("hello world");
+		field.selection_anchor = Some(5);
+		field.caret = 11;
+		assert_eq!(field.selected_text(), Some(" world"));
+
+		field.insert('!');
+		assert_eq!(field.text, "hello!");
+		assert_eq!(field.caret, 6);
+		assert_eq!(field.selection(), None);
+	}
+
+	#[test]
+	fn select_all_then_backspace_clears_the_field() {
+		let mut field = TextField::new("scratch");
+		field.select_all();
+		field.backspace();
+		assert_eq!(field.text, "");
+		assert_eq!(field.caret, 0);
+	}
+
+	#[test]
+	fn text_field_nodes_consume_keys_instead_of_queueing_them() {
+		let mut node = GuiNode::new(ColorPalette::Panel);
+		node.text_field = Some(TextField::new(""));
+
+		node.handle_key(pressed(VirtualKeyCode::H));
+		node.handle_key(pressed(VirtualKeyCode::I));
+
+		assert_eq!(node.text_field.as_ref().unwrap().text, "hi");
+		assert!(node.pending_key_events.is_empty());
+	}
+
+	#[test]
+	fn a_button_walks_normal_hover_pressed_and_back() {
+		let mut button = Button::new(ColorPalette::Panel, ColorPalette::Gray, ColorPalette::Accent);
+		assert_eq!(button.state, ButtonState::Normal);
+		assert_eq!(button.color(), ColorPalette::Panel);
+
+		assert!(button.hover(true));
+		assert_eq!(button.state, ButtonState::Hover);
+		assert_eq!(button.color(), ColorPalette::Gray);
+
+		button.press();
+		assert_eq!(button.state, ButtonState::Pressed);
+		assert_eq!(button.color(), ColorPalette::Accent);
+
+		// Releasing inside fires a click and drops back to Hover, since the cursor is still over it
+		button.release(true);
+		assert_eq!(button.state, ButtonState::Hover);
+		assert_eq!(button.take_clicks(), 1);
+		assert_eq!(button.take_clicks(), 0);
+	}
+
+	#[test]
+	fn leaving_and_returning_mid_press_restores_the_pressed_visuals() {
+		let mut button = Button::new(ColorPalette::Panel, ColorPalette::Gray, ColorPalette::Accent);
+		button.press();
+
+		// Dragging off the button relaxes the visuals but keeps the hold alive
+		assert!(button.hover(false));
+		assert_eq!(button.state, ButtonState::Normal);
+		assert!(button.held);
+
+		// Returning before the release shows Pressed again, and releasing inside still clicks
+		assert!(button.hover(true));
+		assert_eq!(button.state, ButtonState::Pressed);
+		button.release(true);
+		assert_eq!(button.take_clicks(), 1);
+	}
+
+	#[test]
+	fn releasing_outside_the_bounds_does_not_click() {
+		let mut button = Button::new(ColorPalette::Panel, ColorPalette::Gray, ColorPalette::Accent);
+		button.press();
+		button.hover(false);
+
+		button.release(false);
+		assert_eq!(button.state, ButtonState::Normal);
+		assert!(!button.held);
+		assert_eq!(button.take_clicks(), 0);
+
+		// Hovering without a hold never shows Pressed
+		button.hover(true);
+		assert_eq!(button.state, ButtonState::Hover);
+	}
+
+	#[test]
+	fn the_picker_round_trips_the_palette_color_it_was_seeded_with() {
+		// The accent color, as an object selection would hand it over
+		let picker = ColorPicker::new(0x3194d6ff);
+		assert_eq!(picker.color(), ColorPalette::Color(0x3194d6ff));
+
+		// Alpha rides along untouched through the HSV decomposition
+		let translucent = ColorPicker::new(0x3194d680);
+		assert_eq!(translucent.color(), ColorPalette::Color(0x3194d680));
+	}
+
+	#[test]
+	fn reflecting_an_achromatic_color_keeps_the_hue_thumb_in_place() {
+		let mut picker = ColorPicker::new(0x00ff00ff);
+		assert_eq!(picker.hue, 120.);
+
+		// Pointing the picker at a gray zeroes saturation but leaves the hue where it was
+		picker.set_rgba(0x808080ff);
+		assert_eq!(picker.hue, 120.);
+		assert_eq!(picker.saturation, 0.);
+		assert_eq!(picker.color(), ColorPalette::Color(0x808080ff));
+		// Reflecting a selection is not a user edit, so nothing was queued
+		assert!(picker.take_changes().is_empty());
+	}
+
+	#[test]
+	fn drag_setters_clamp_and_queue_the_recomposed_colors() {
+		let mut picker = ColorPicker::new(0xff0000ff);
+
+		// Each committed change queues the full color it composed at that moment
+		assert!(picker.set_hue(480.));
+		assert_eq!(picker.hue, 360.);
+		assert!(picker.set_saturation_value(2., 0.5));
+		assert_eq!((picker.saturation, picker.value), (1., 0.5));
+		assert_eq!(picker.take_changes(), vec![ColorPalette::Color(0xff0000ff), ColorPalette::Color(0x800000ff)]);
+		assert_eq!(picker.take_changes(), vec![]);
+
+		// Setting the same values again is not a change
+		assert!(!picker.set_saturation_value(1., 0.5));
+	}
+
+	#[test]
+	fn pasting_inserts_into_the_text_field() {
+		let mut node = GuiNode::new(ColorPalette::Panel);
+		node.text_field = Some(TextField::new("color: "));
+
+		node.handle_clipboard(ClipboardEvent::Paste(String::from("#3194d6")));
+		assert_eq!(node.text_field.as_ref().unwrap().text, "color: #3194d6");
+		// Copy still queues so the application can read the selection back out
+		node.handle_clipboard(ClipboardEvent::Copy);
+		assert_eq!(node.pending_clipboard_events, vec![ClipboardEvent::Copy]);
+	}
+}
@@ -0,0 +1,1571 @@
+use crate::buffer_pool::BufferPool;
+use crate::color_palette::ColorPalette;
+use crate::draw_command::{DrawCommand, Vertex2DTextured};
+use crate::geometry::{Rect, Size};
+use crate::gui_node::{ColorPickerRegion, FlexDirection, GuiNode};
+use crate::pipeline::Pipeline;
+use crate::resource_cache::ResourceCache;
+use crate::texture::Texture;
+use crate::uniform_buffer::{self, UniformBuffer};
+use winit::event::{ElementState, VirtualKeyCode};
+
+// The cached pipeline and texture GUI quads are drawn with
+// TODO: Give the GUI its own shader set instead of reusing the example's
+pub const GUI_PIPELINE: &str = "example";
+pub const GUI_TEXTURE: &str = "textures/grid.png";
+
+// Identifies a node in the GUI tree
+// The generation guards against stale ids: a slot reused after removal gets a new generation,
+// so ids held across a removal can never silently address the wrong node
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NodeId {
+	index: usize,
+	generation: u32,
+}
+
+// A keyboard event as delivered to the focused GUI node
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KeyEvent {
+	pub key: VirtualKeyCode,
+	pub state: ElementState,
+}
+
+// A pointer event as delivered to the node under the cursor
+// Click is synthesized when a press and its release both land on the same node
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PointerEvent {
+	Down,
+	Up,
+	Click,
+}
+
+// Whether a node's handler consumed a pointer event or lets dispatch continue to the next node
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum EventPropagation {
+	Handled,
+	Continue,
+}
+
+// A wheel or trackpad scroll, normalized to logical pixels regardless of how the OS reported it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScrollEvent {
+	pub dx: f32,
+	pub dy: f32,
+}
+
+// How many logical pixels one wheel notch scrolls by when the OS reports line deltas
+pub const SCROLL_PIXELS_PER_LINE: f32 = 20.;
+
+// Text fields approximate glyph advances with a fixed column width until the GUI shares the
+// TextRenderer's metrics
+// TODO: Measure real advances through TextRenderer once text and GUI rendering share state
+const TEXT_FIELD_CHAR_WIDTH: f32 = 8.;
+const CARET_WIDTH: f32 = 1.;
+
+// Whether the caret sits in the visible half of its one-second blink cycle
+pub(crate) fn caret_blink_visible(elapsed: std::time::Duration) -> bool {
+	elapsed.as_millis() % 1000 < 500
+}
+
+// The caret's quad inside a text field's bounds: a thin bar after the caret's column
+pub(crate) fn caret_rect(field: &crate::gui_node::TextField, bounds: Rect) -> Rect {
+	let column = field.text[..field.caret].chars().count() as f32;
+	Rect::new(bounds.x + column * TEXT_FIELD_CHAR_WIDTH, bounds.y, CARET_WIDTH, bounds.height)
+}
+
+// How wide a slider's thumb quad draws, in logical pixels
+const SLIDER_THUMB_WIDTH: f32 = 8.;
+
+// The thumb's quad along its track: centered on the value's fraction, kept inside the bounds
+pub(crate) fn slider_thumb_rect(slider: &crate::gui_node::Slider, bounds: Rect) -> Rect {
+	let travel = (bounds.width - SLIDER_THUMB_WIDTH).max(0.);
+	Rect::new(bounds.x + slider.fraction() * travel, bounds.y, SLIDER_THUMB_WIDTH.min(bounds.width), bounds.height)
+}
+
+// How a color picker's bounds split between its two regions, in logical pixels
+const COLOR_PICKER_HUE_BAR_WIDTH: f32 = 16.;
+const COLOR_PICKER_GAP: f32 = 4.;
+
+// The saturation/value square's quad: the picker's bounds minus the hue bar and the gap before it
+pub(crate) fn color_picker_sv_rect(bounds: Rect) -> Rect {
+	Rect::new(bounds.x, bounds.y, (bounds.width - COLOR_PICKER_HUE_BAR_WIDTH - COLOR_PICKER_GAP).max(0.), bounds.height)
+}
+
+// The vertical hue bar's quad along the picker's right edge
+pub(crate) fn color_picker_hue_rect(bounds: Rect) -> Rect {
+	let width = COLOR_PICKER_HUE_BAR_WIDTH.min(bounds.width);
+	Rect::new(bounds.x + bounds.width - width, bounds.y, width, bounds.height)
+}
+
+// How far the focus ring's edge quads sit outside the focused node's bounds
+const FOCUS_RING_THICKNESS: f32 = 2.;
+
+// The four edge quads forming the focus ring just outside the bounds: top, bottom, left, right
+// Sitting outside means the ring never covers the control it highlights
+pub(crate) fn focus_ring_rects(bounds: Rect) -> [Rect; 4] {
+	let Rect { x, y, width, height } = bounds;
+	let thickness = FOCUS_RING_THICKNESS;
+	[
+		Rect::new(x - thickness, y - thickness, width + 2. * thickness, thickness),
+		Rect::new(x - thickness, y + height, width + 2. * thickness, thickness),
+		Rect::new(x - thickness, y, thickness, height),
+		Rect::new(x + width, y, thickness, height),
+	]
+}
+
+// How far apart consecutive z-indices sit in the 0..1 depth range
+const Z_DEPTH_STEP: f32 = 1. / 1024.;
+
+// Converts a stacking index into a depth value: higher z-indices land nearer the camera (smaller
+// depth), spaced so 512 layers fit on either side of the default z = 0 plane at depth 0.5
+pub(crate) fn z_to_depth(z_index: i32) -> f32 {
+	(0.5 - z_index as f32 * Z_DEPTH_STEP).clamp(0., 1.)
+}
+
+// Clipboard traffic delivered to the focused node: Copy asks the widget to put its selection
+// on the clipboard, Paste hands it the clipboard's current text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClipboardEvent {
+	Copy,
+	Paste(String),
+}
+
+// A file dragged in from the OS and dropped on the window, delivered to the node under the cursor
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileDropEvent {
+	pub path: std::path::PathBuf,
+}
+
+// A node plus its place in the hierarchy
+struct NodeEntry {
+	node: GuiNode,
+	parent: Option<NodeId>,
+	children: Vec<NodeId>,
+}
+
+// One arena slot; empty slots keep their generation so it can be bumped on reuse
+struct Slot {
+	generation: u32,
+	entry: Option<NodeEntry>,
+}
+
+// A node and its subtree in the nested shape JSON reads naturally; saving flattens the arena into
+// this and loading rebuilds the arena from it, so slot indices and generations never hit the disk
+#[derive(serde::Serialize, serde::Deserialize)]
+struct SavedNode {
+	node: GuiNode,
+	children: Vec<SavedNode>,
+}
+
+// The hierarchy of GUI elements making up the editor interface
+pub struct GuiTree {
+	slots: Vec<Slot>,
+	free: Vec<usize>,
+	root: NodeId,
+	// The node receiving keyboard input, if any
+	focused_node: Option<NodeId>,
+	// The node the last mouse press landed on, pending its release
+	press_target: Option<NodeId>,
+	// The node the cursor was last over, so crossing an edge updates both sides' hover state
+	hovered_node: Option<NodeId>,
+	// The union of every changed node's bounds since the last frame, in logical pixels
+	// None means nothing changed; rendering can reuse the previous frame outside this region
+	dirty_region: Option<Rect>,
+	// When the focused text field's caret last went solid; focus changes and edits reset it so the
+	// caret never blinks away mid-keystroke
+	caret_blink_started: std::time::Instant,
+}
+
+impl GuiTree {
+	pub fn new() -> Self {
+		// Start with a root node covering the whole window
+		let root = NodeId { index: 0, generation: 0 };
+
+		// The root is a pure container; child panels provide the visible surfaces
+		let mut root_node = GuiNode::new(ColorPalette::Background);
+		root_node.visible = false;
+
+		Self {
+			slots: vec![Slot {
+				generation: 0,
+				entry: Some(NodeEntry {
+					node: root_node,
+					parent: None,
+					children: Vec::new(),
+				}),
+			}],
+			free: Vec::new(),
+			root,
+			focused_node: None,
+			press_target: None,
+			hovered_node: None,
+			dirty_region: None,
+			caret_blink_started: std::time::Instant::now(),
+		}
+	}
+
+	pub fn root(&self) -> NodeId {
+		self.root
+	}
+
+	// Inserts a node under the given parent (the root when None), returning its id
+	pub fn add_node(&mut self, parent: Option<NodeId>, node: GuiNode) -> NodeId {
+		let parent = parent.unwrap_or(self.root);
+		debug_assert!(self.entry(parent).is_some(), "Adding a GUI node under a removed parent");
+
+		let entry = NodeEntry {
+			node,
+			parent: Some(parent),
+			children: Vec::new(),
+		};
+
+		// Reuse a freed slot when one exists, bumping its generation to invalidate old ids
+		let id = match self.free.pop() {
+			Some(index) => {
+				let slot = &mut self.slots[index];
+				slot.generation += 1;
+				slot.entry = Some(entry);
+				NodeId { index, generation: slot.generation }
+			}
+			None => {
+				self.slots.push(Slot { generation: 0, entry: Some(entry) });
+				NodeId {
+					index: self.slots.len() - 1,
+					generation: 0,
+				}
+			}
+		};
+
+		if let Some(parent_entry) = self.entry_mut(parent) {
+			parent_entry.children.push(id);
+		}
+		id
+	}
+
+	// Removes a node and its whole subtree; the root cannot be removed
+	pub fn remove_node(&mut self, id: NodeId) {
+		if id == self.root || self.entry(id).is_none() {
+			return;
+		}
+
+		// Detach from the parent before freeing the subtree
+		if let Some(parent) = self.entry(id).and_then(|entry| entry.parent) {
+			if let Some(parent_entry) = self.entry_mut(parent) {
+				parent_entry.children.retain(|&child| child != id);
+			}
+		}
+
+		// Free depth-first without recursion
+		let mut pending = vec![id];
+		while let Some(current) = pending.pop() {
+			if let Some(entry) = self.slots[current.index].entry.take() {
+				pending.extend(entry.children);
+				self.free.push(current.index);
+			}
+
+			// Input state must not keep pointing into the removed subtree
+			if self.focused_node == Some(current) {
+				self.focused_node = None;
+			}
+			if self.press_target == Some(current) {
+				self.press_target = None;
+			}
+			if self.hovered_node == Some(current) {
+				self.hovered_node = None;
+			}
+		}
+	}
+
+	pub fn children(&self, id: NodeId) -> &[NodeId] {
+		self.entry(id).map(|entry| entry.children.as_slice()).unwrap_or(&[])
+	}
+
+	pub fn get(&self, id: NodeId) -> Option<&GuiNode> {
+		self.entry(id).map(|entry| &entry.node)
+	}
+
+	pub fn get_mut(&mut self, id: NodeId) -> Option<&mut GuiNode> {
+		self.entry_mut(id).map(|entry| &mut entry.node)
+	}
+
+	// How many nodes are currently alive, including the root
+	pub fn len(&self) -> usize {
+		self.slots.len() - self.free.len()
+	}
+
+	// The whole hierarchy as a JSON document, each node nested inside its parent in tree order
+	// Only the persistent node properties are written; input state like focus, hover, and the
+	// pending event queues belongs to the running session, not the document
+	pub fn to_json(&self) -> String {
+		serde_json::to_string(&self.save_node(self.root)).expect("GUI nodes always serialize")
+	}
+
+	fn save_node(&self, id: NodeId) -> SavedNode {
+		SavedNode {
+			node: self.get(id).expect("Saving a removed GUI node").clone(),
+			children: self.children(id).iter().map(|&child| self.save_node(child)).collect(),
+		}
+	}
+
+	// Rebuilds a tree from to_json's output; node ids are not part of the document, so callers
+	// re-find their nodes by walking the structure after loading
+	pub fn from_json(json: &str) -> Result<GuiTree, serde_json::Error> {
+		let saved: SavedNode = serde_json::from_str(json)?;
+		let mut tree = GuiTree::new();
+		let root = tree.root();
+		if let Some(root_node) = tree.get_mut(root) {
+			*root_node = saved.node;
+		}
+		for child in saved.children {
+			tree.restore_node(root, child);
+		}
+		Ok(tree)
+	}
+
+	fn restore_node(&mut self, parent: NodeId, saved: SavedNode) {
+		let id = self.add_node(Some(parent), saved.node);
+		for child in saved.children {
+			self.restore_node(id, child);
+		}
+	}
+
+	fn entry(&self, id: NodeId) -> Option<&NodeEntry> {
+		let slot = self.slots.get(id.index)?;
+		if slot.generation != id.generation {
+			return None;
+		}
+		slot.entry.as_ref()
+	}
+
+	fn entry_mut(&mut self, id: NodeId) -> Option<&mut NodeEntry> {
+		let slot = self.slots.get_mut(id.index)?;
+		if slot.generation != id.generation {
+			return None;
+		}
+		slot.entry.as_mut()
+	}
+
+	// Directs keyboard input to the given node; None clears focus entirely
+	pub fn set_focus(&mut self, node: Option<NodeId>) {
+		if let Some(id) = node {
+			if self.entry(id).is_none() {
+				return;
+			}
+		}
+		self.focused_node = node;
+		self.caret_blink_started = std::time::Instant::now();
+	}
+
+	pub fn focused_node(&self) -> Option<NodeId> {
+		self.focused_node
+	}
+
+	// Every focusable node in tree order: parents before children, earlier siblings first
+	// This is the order Tab walks through, so it follows the layout's reading order
+	pub fn focus_order(&self) -> Vec<NodeId> {
+		self.draw_order().into_iter().filter(|&id| self.get(id).map(|node| node.is_focusable()).unwrap_or(false)).collect()
+	}
+
+	// Moves keyboard focus to the next focusable node in tree order (the previous with `backwards`),
+	// wrapping at the ends; with nothing focused, Tab starts at the first node and Shift-Tab at the last
+	pub fn advance_focus(&mut self, backwards: bool) {
+		let order = self.focus_order();
+		if order.is_empty() {
+			self.set_focus(None);
+			return;
+		}
+
+		// A focused node that became disabled or invisible no longer appears in the order; treat
+		// that like no focus, so Tab recovers by starting over from an end
+		let target = match self.focused_node.and_then(|id| order.iter().position(|&candidate| candidate == id)) {
+			Some(index) if backwards => order[(index + order.len() - 1) % order.len()],
+			Some(index) => order[(index + 1) % order.len()],
+			None if backwards => order[order.len() - 1],
+			None => order[0],
+		};
+
+		// Both the node losing its focus ring and the one gaining it must repaint
+		if let Some(previous) = self.focused_node {
+			self.mark_focus_ring_dirty(previous);
+		}
+		self.set_focus(Some(target));
+		self.mark_focus_ring_dirty(target);
+	}
+
+	// The ring extends a little outside the node's bounds, so the dirty region must too
+	fn mark_focus_ring_dirty(&mut self, id: NodeId) {
+		if let Some(node) = self.get(id) {
+			let bounds = node.computed_bounds;
+			self.mark_region_dirty(Rect::new(
+				bounds.x - FOCUS_RING_THICKNESS,
+				bounds.y - FOCUS_RING_THICKNESS,
+				bounds.width + 2. * FOCUS_RING_THICKNESS,
+				bounds.height + 2. * FOCUS_RING_THICKNESS,
+			));
+		}
+	}
+
+	// Delivers a key event to the focused node; with no focus the event is dropped
+	pub fn handle_key(&mut self, event: KeyEvent) {
+		if let Some(id) = self.focused_node {
+			let edits_text = match self.get_mut(id) {
+				Some(node) => {
+					node.handle_key(event);
+					node.text_field.is_some()
+				}
+				None => false,
+			};
+			// An edited field must repaint, and typing holds the caret solid
+			if edits_text {
+				self.mark_node_dirty(id);
+				self.caret_blink_started = std::time::Instant::now();
+			}
+		}
+	}
+
+	// Delivers a clipboard event to the focused node; with no focus the event is dropped
+	pub fn handle_clipboard(&mut self, event: ClipboardEvent) {
+		if let Some(id) = self.focused_node {
+			if let Some(node) = self.get_mut(id) {
+				node.handle_clipboard(event);
+			}
+		}
+	}
+
+	// Delivers a pointer event to a node, synthesizing Click when a press and release land on the same node
+	// Each concrete event runs through dispatch_pointer's capture and bubble phases
+	pub fn handle_pointer(&mut self, node: NodeId, event: PointerEvent) {
+		match event {
+			PointerEvent::Down => {
+				self.press_target = Some(node);
+				self.dispatch_pointer(node, PointerEvent::Down);
+				self.press_button(node);
+			}
+			PointerEvent::Up => {
+				let pressed = self.press_target.take();
+				let clicked = pressed == Some(node);
+				self.dispatch_pointer(node, PointerEvent::Up);
+				if clicked {
+					self.dispatch_pointer(node, PointerEvent::Click);
+				}
+				// The pressed button releases wherever the cursor ended up; only clicked means inside
+				if let Some(id) = pressed {
+					self.release_button(id, clicked);
+					self.release_picker(id);
+				}
+			}
+			PointerEvent::Click => self.dispatch_pointer(node, PointerEvent::Click),
+		}
+	}
+
+	// Puts a pressed node's button into its Pressed state, repainting it
+	fn press_button(&mut self, id: NodeId) {
+		match self.get_mut(id).and_then(|node| node.button.as_mut()) {
+			Some(button) => button.press(),
+			None => return,
+		}
+		self.refresh_button(id);
+	}
+
+	// Ends a button's hold, queueing a click when the release landed back inside its bounds
+	fn release_button(&mut self, id: NodeId, inside: bool) {
+		match self.get_mut(id).and_then(|node| node.button.as_mut()) {
+			Some(button) => button.release(inside),
+			None => return,
+		}
+		self.refresh_button(id);
+	}
+
+	// Reflects a button's state into its node's color so the next rebuild draws the right role
+	fn refresh_button(&mut self, id: NodeId) {
+		let color = match self.get(id).and_then(|node| node.button.as_ref()) {
+			Some(button) => button.color(),
+			None => return,
+		};
+		if let Some(node) = self.get_mut(id) {
+			node.color = color;
+		}
+		self.mark_node_dirty(id);
+	}
+
+	// Re-hit-tests the cursor's position, updating button hover state on the nodes it crossed
+	// between; true when some button's visuals changed
+	pub fn handle_cursor_move(&mut self, x: f32, y: f32) -> bool {
+		let hovered = self.hit_test(x, y);
+		if hovered == self.hovered_node {
+			return false;
+		}
+		let previous = std::mem::replace(&mut self.hovered_node, hovered);
+
+		let mut changed = false;
+		if let Some(id) = previous {
+			changed |= self.update_button_hover(id, false);
+		}
+		if let Some(id) = hovered {
+			changed |= self.update_button_hover(id, true);
+		}
+		changed
+	}
+
+	fn update_button_hover(&mut self, id: NodeId, inside: bool) -> bool {
+		let changed = match self.get_mut(id).and_then(|node| node.button.as_mut()) {
+			Some(button) => button.hover(inside),
+			None => false,
+		};
+		if changed {
+			self.refresh_button(id);
+		}
+		changed
+	}
+
+	// Two-phase dispatch mirroring DOM semantics: opted-in ancestors see the event from the root down
+	// to the target (capture), then the target, then opted-in ancestors back up to the root (bubble)
+	// Any handler returning Handled consumes the event and stops the walk there
+	fn dispatch_pointer(&mut self, target: NodeId, event: PointerEvent) {
+		let path = self.path_from_root(target);
+
+		// Capture: enclosing widgets intercept before the event reaches the target
+		for &id in path.iter().take(path.len().saturating_sub(1)) {
+			if self.get(id).map(|node| node.capture_pointer).unwrap_or(false) && self.deliver_pointer(id, event) == EventPropagation::Handled {
+				return;
+			}
+		}
+
+		// The target itself always receives the event
+		if self.deliver_pointer(target, event) == EventPropagation::Handled {
+			return;
+		}
+
+		// Bubble: the target's ancestors react after it, nearest first
+		for &id in path.iter().rev().skip(1) {
+			if self.get(id).map(|node| node.bubble_pointer).unwrap_or(false) && self.deliver_pointer(id, event) == EventPropagation::Handled {
+				return;
+			}
+		}
+	}
+
+	fn deliver_pointer(&mut self, id: NodeId, event: PointerEvent) -> EventPropagation {
+		match self.get_mut(id) {
+			Some(node) => node.handle_pointer(event),
+			None => EventPropagation::Continue,
+		}
+	}
+
+	// The chain of live nodes from the root down to and including the target
+	fn path_from_root(&self, target: NodeId) -> Vec<NodeId> {
+		let mut path = vec![target];
+		let mut current = self.entry(target).and_then(|entry| entry.parent);
+		while let Some(ancestor) = current {
+			path.push(ancestor);
+			current = self.entry(ancestor).and_then(|entry| entry.parent);
+		}
+		path.reverse();
+		path
+	}
+
+	// Delivers a scroll to a node, which adjusts its content offset
+	pub fn handle_scroll(&mut self, node: NodeId, event: ScrollEvent) {
+		if let Some(target) = self.get_mut(node) {
+			target.handle_scroll(event);
+		}
+	}
+
+	// Delivers a dropped file to a node, e.g. so an image panel can load it into a texture
+	pub fn handle_file_drop(&mut self, node: NodeId, event: FileDropEvent) {
+		if let Some(target) = self.get_mut(node) {
+			target.handle_file_drop(event);
+		}
+	}
+
+	// Drags the pressed slider's value or color picker's selection to the cursor; true when it changed
+	// The press target acts as pointer capture, so the drag keeps following the cursor outside the
+	// widget's bounds until the button releases
+	pub fn handle_cursor_drag(&mut self, x: f32, y: f32) -> bool {
+		let id = match self.press_target {
+			Some(id) => id,
+			None => return false,
+		};
+		let bounds = match self.get(id) {
+			Some(node) => node.computed_bounds,
+			None => return false,
+		};
+
+		let changed = if let Some(slider) = self.get_mut(id).and_then(|node| node.slider.as_mut()) {
+			let value = slider.value_at(bounds, x);
+			slider.set_value(value)
+		} else if let Some(picker) = self.get_mut(id).and_then(|node| node.color_picker.as_mut()) {
+			// The first drag after the press latches its region; the press position decides which
+			let region = *picker
+				.active_region
+				.get_or_insert(if color_picker_hue_rect(bounds).contains(x, y) { ColorPickerRegion::HueBar } else { ColorPickerRegion::SaturationValue });
+			match region {
+				ColorPickerRegion::HueBar => {
+					let bar = color_picker_hue_rect(bounds);
+					let fraction = if bar.height <= 0. { 0. } else { ((y - bar.y) / bar.height).clamp(0., 1.) };
+					picker.set_hue(fraction * 360.)
+				}
+				ColorPickerRegion::SaturationValue => {
+					let square = color_picker_sv_rect(bounds);
+					let saturation = if square.width <= 0. { 0. } else { ((x - square.x) / square.width).clamp(0., 1.) };
+					// The square's top row is full value, so the y fraction inverts
+					let value = if square.height <= 0. { 0. } else { 1. - ((y - square.y) / square.height).clamp(0., 1.) };
+					picker.set_saturation_value(saturation, value)
+				}
+			}
+		} else {
+			false
+		};
+		if changed {
+			self.mark_node_dirty(id);
+		}
+		changed
+	}
+
+	// Forgets the pending press, e.g. when the release happened outside every node
+	// A held button releases without clicking, since the release never landed inside it
+	pub fn clear_press(&mut self) {
+		if let Some(id) = self.press_target.take() {
+			self.release_button(id, false);
+			self.release_picker(id);
+		}
+	}
+
+	// Ends a color picker's drag so the next press latches its region afresh
+	fn release_picker(&mut self, id: NodeId) {
+		if let Some(picker) = self.get_mut(id).and_then(|node| node.color_picker.as_mut()) {
+			picker.active_region = None;
+		}
+	}
+
+	// Records that a node changed visibly, growing the dirty region by its laid-out bounds
+	// Widget code calls this after mutating a node so rendering knows how much of the frame is stale
+	pub fn mark_node_dirty(&mut self, id: NodeId) {
+		if let Some(node) = self.get(id) {
+			self.mark_region_dirty(node.computed_bounds);
+		}
+	}
+
+	pub(crate) fn mark_region_dirty(&mut self, rect: Rect) {
+		self.dirty_region = Some(match self.dirty_region {
+			Some(existing) => existing.union(rect),
+			None => rect,
+		});
+	}
+
+	// Hands the accumulated dirty region to the renderer and starts accumulating afresh
+	pub fn take_dirty_region(&mut self) -> Option<Rect> {
+		self.dirty_region.take()
+	}
+
+	// Every live node id in draw order: parents before children, earlier siblings before later ones
+	pub fn draw_order(&self) -> Vec<NodeId> {
+		let mut order = Vec::with_capacity(self.len());
+		let mut pending = vec![self.root];
+		while let Some(current) = pending.pop() {
+			if self.entry(current).is_none() {
+				continue;
+			}
+			order.push(current);
+			// Reverse so the stack pops earlier siblings (and their subtrees) first
+			pending.extend(self.children(current).iter().rev());
+		}
+		order
+	}
+
+	// The rectangle a node's geometry is clipped to: the intersection of every clipping ancestor's
+	// bounds, or None when no ancestor clips
+	fn clip_rect_for(&self, id: NodeId) -> Option<Rect> {
+		let mut clip: Option<Rect> = None;
+		let mut current = self.entry(id).and_then(|entry| entry.parent);
+		while let Some(ancestor) = current {
+			if let Some(entry) = self.entry(ancestor) {
+				if entry.node.clip {
+					let bounds = entry.node.computed_bounds;
+					clip = Some(match clip {
+						// Disjoint clipping ancestors leave nothing visible, which the empty rectangle expresses
+						Some(existing) => existing.intersection(bounds).unwrap_or(Rect::new(bounds.x, bounds.y, 0., 0.)),
+						None => bounds,
+					});
+				}
+				current = entry.parent;
+			} else {
+				break;
+			}
+		}
+		clip
+	}
+
+	// Draw order re-sorted by z-index: higher z-indices come later (on top), and the stable sort
+	// keeps tree order between nodes sharing a z-index
+	pub fn paint_order(&self) -> Vec<NodeId> {
+		let mut order = self.draw_order();
+		order.sort_by_key(|&id| self.get(id).map(|node| node.z_index).unwrap_or(0));
+		order
+	}
+
+	// Emits one textured quad per visible node, in paint order, from the bounds the layout pass computed
+	// Returns nothing if the GUI pipeline or texture has not been cached yet
+	pub fn build_draw_commands(
+		&self,
+		device: &wgpu::Device,
+		queue: &mut wgpu::Queue,
+		pool: &mut BufferPool,
+		pipeline_cache: &ResourceCache<Pipeline>,
+		texture_cache: &ResourceCache<Texture>,
+		viewport: Size,
+	) -> Vec<DrawCommand> {
+		let (pipeline, texture) = match (pipeline_cache.get(GUI_PIPELINE), texture_cache.get(GUI_TEXTURE)) {
+			(Some(pipeline), Some(texture)) => (pipeline, texture),
+			_ => return Vec::new(),
+		};
+
+		let mut commands = Vec::new();
+		for id in self.paint_order() {
+			let node = match self.get(id) {
+				Some(node) if node.visible => node,
+				_ => continue,
+			};
+
+			// TODO: Tint by node.color once the shader takes a color input rather than sampling the texture directly
+			let mut command = gui_quad_command(device, queue, pool, pipeline, texture, node.computed_bounds, viewport, node.z_index);
+			command.scissor = self.clip_rect_for(id);
+			commands.push(command);
+
+			// A slider's node quad is the track; the thumb draws one layer above it
+			if let Some(slider) = &node.slider {
+				let mut thumb = gui_quad_command(device, queue, pool, pipeline, texture, slider_thumb_rect(slider, node.computed_bounds), viewport, node.z_index + 1);
+				thumb.scissor = Some(node.computed_bounds);
+				commands.push(thumb);
+			}
+		}
+
+		// The focused control draws a focus ring of four edge quads just outside its bounds, so
+		// keyboard users can see where Tab has landed
+		if let Some(id) = self.focused_node {
+			if let Some(node) = self.get(id) {
+				if node.is_focusable() {
+					for edge in focus_ring_rects(node.computed_bounds).iter() {
+						let mut command = gui_quad_command(device, queue, pool, pipeline, texture, *edge, viewport, node.z_index + 2);
+						command.scissor = self.clip_rect_for(id);
+						commands.push(command);
+					}
+				}
+			}
+		}
+
+		// The focused text field draws its caret as a thin quad one layer above its node
+		if let Some(id) = self.focused_node {
+			if let Some(node) = self.get(id) {
+				if let Some(field) = &node.text_field {
+					if caret_blink_visible(self.caret_blink_started.elapsed()) {
+						let mut command = gui_quad_command(device, queue, pool, pipeline, texture, caret_rect(field, node.computed_bounds), viewport, node.z_index + 1);
+						command.scissor = Some(node.computed_bounds);
+						commands.push(command);
+					}
+				}
+			}
+		}
+		commands
+	}
+
+	// The topmost node whose bounds contain the point, in logical pixels
+	// Later nodes draw over earlier ones, so the walk runs back-to-front through the paint order
+	pub fn hit_test(&self, x: f32, y: f32) -> Option<NodeId> {
+		self.paint_order().into_iter().rev().find(|&id| self.get(id).map(|node| node.computed_bounds.contains(x, y)).unwrap_or(false))
+	}
+
+	// Computes every node's on-screen bounds from its flex properties, top-down from the viewport
+	pub fn layout(&mut self, viewport: Size) {
+		let root = self.root;
+		self.layout_node(root, Rect::new(0., 0., viewport.width, viewport.height));
+		// Every node may have moved, so the whole viewport is stale
+		self.mark_region_dirty(Rect::new(0., 0., viewport.width, viewport.height));
+	}
+
+	fn layout_node(&mut self, id: NodeId, bounds: Rect) {
+		let (direction, padding) = match self.get_mut(id) {
+			Some(node) => {
+				node.computed_bounds = bounds;
+				(node.direction, node.padding)
+			}
+			None => return,
+		};
+
+		let children: Vec<NodeId> = self.children(id).to_vec();
+		if children.is_empty() {
+			return;
+		}
+
+		// The area children are placed within: the node's bounds inset by its padding
+		let content = Rect::new(bounds.x + padding, bounds.y + padding, (bounds.width - 2. * padding).max(0.), (bounds.height - 2. * padding).max(0.));
+
+		// Fixed-size children take their requested main-axis length; the rest is split by grow weight
+		let main_total = match direction {
+			FlexDirection::Row => content.width,
+			FlexDirection::Column => content.height,
+		};
+		let mut fixed_total = 0.;
+		let mut grow_total = 0.;
+		for &child in &children {
+			if let Some(node) = self.get(child) {
+				let fixed = match direction {
+					FlexDirection::Row => node.width,
+					FlexDirection::Column => node.height,
+				};
+				match fixed {
+					Some(size) => fixed_total += size,
+					None => grow_total += node.grow,
+				}
+			}
+		}
+		let remaining = (main_total - fixed_total).max(0.);
+
+		// Place the children one after another along the main axis
+		let mut cursor = 0.;
+		for &child in &children {
+			let (main_fixed, cross_fixed, grow) = match self.get(child) {
+				Some(node) => match direction {
+					FlexDirection::Row => (node.width, node.height, node.grow),
+					FlexDirection::Column => (node.height, node.width, node.grow),
+				},
+				None => continue,
+			};
+
+			let main_size = main_fixed.unwrap_or(if grow_total > 0. { remaining * grow / grow_total } else { 0. });
+			let cross_total = match direction {
+				FlexDirection::Row => content.height,
+				FlexDirection::Column => content.width,
+			};
+			let cross_size = cross_fixed.unwrap_or(cross_total);
+
+			let child_bounds = match direction {
+				FlexDirection::Row => Rect::new(content.x + cursor, content.y, main_size, cross_size),
+				FlexDirection::Column => Rect::new(content.x, content.y + cursor, cross_size, main_size),
+			};
+			cursor += main_size;
+
+			self.layout_node(child, child_bounds);
+		}
+	}
+}
+
+// Builds one textured GUI quad covering `rect` at the given stacking index, with the depth baked
+// into the transform so the depth test agrees with painter's order
+#[allow(clippy::too_many_arguments)]
+fn gui_quad_command(
+	device: &wgpu::Device,
+	queue: &mut wgpu::Queue,
+	pool: &mut BufferPool,
+	pipeline: &Pipeline,
+	texture: &Texture,
+	rect: Rect,
+	viewport: Size,
+	z_index: i32,
+) -> DrawCommand {
+	let corners = rect.to_ndc_vertices(viewport);
+	const UVS: [[f32; 2]; 4] = [[0., 1.], [1., 1.], [1., 0.], [0., 0.]];
+	let vertices: Vec<Vertex2DTextured> = corners.iter().zip(UVS.iter()).map(|(&position, &uv)| Vertex2DTextured { position, uv }).collect();
+	const INDICES: &[u16] = &[0, 1, 2, 2, 3, 0];
+
+	let mut transform = uniform_buffer::IDENTITY;
+	transform[3][2] = z_to_depth(z_index);
+	let uniform_buffer = UniformBuffer::new(device, transform);
+	let bind_group = pipeline.create_texture_bind_group(device, texture, &uniform_buffer);
+
+	let mut command = DrawCommand::new_pooled(device, queue, pool, String::from(GUI_PIPELINE), &vertices, INDICES, bind_group);
+	command.uniform_buffer = Some(uniform_buffer);
+	command
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn pressed(key: VirtualKeyCode) -> KeyEvent {
+		KeyEvent { key, state: ElementState::Pressed }
+	}
+
+	fn node(x: f32, y: f32, width: f32, height: f32) -> GuiNode {
+		let mut node = GuiNode::new(ColorPalette::Accent);
+		node.computed_bounds = Rect::new(x, y, width, height);
+		node
+	}
+
+	#[test]
+	fn adding_nodes_builds_a_hierarchy() {
+		let mut tree = GuiTree::new();
+		let panel = tree.add_node(None, node(0., 0., 100., 100.));
+		let button = tree.add_node(Some(panel), node(10., 10., 20., 20.));
+
+		assert_eq!(tree.children(tree.root()), &[panel]);
+		assert_eq!(tree.children(panel), &[button]);
+		assert_eq!(tree.len(), 3);
+	}
+
+	#[test]
+	fn removing_a_node_detaches_its_subtree() {
+		let mut tree = GuiTree::new();
+		let panel = tree.add_node(None, node(0., 0., 100., 100.));
+		let button = tree.add_node(Some(panel), node(10., 10., 20., 20.));
+
+		tree.remove_node(panel);
+
+		assert!(tree.children(tree.root()).is_empty());
+		assert!(tree.get(panel).is_none());
+		assert!(tree.get(button).is_none());
+		assert_eq!(tree.len(), 1);
+	}
+
+	#[test]
+	fn stale_ids_do_not_alias_reused_slots() {
+		let mut tree = GuiTree::new();
+		let first = tree.add_node(None, node(0., 0., 10., 10.));
+		tree.remove_node(first);
+
+		// The replacement reuses the freed slot, but the old id must not resolve to it
+		let second = tree.add_node(None, node(0., 0., 20., 20.));
+		assert!(tree.get(first).is_none());
+		assert!(tree.get(second).is_some());
+		assert_ne!(first, second);
+	}
+
+	#[test]
+	fn the_root_cannot_be_removed() {
+		let mut tree = GuiTree::new();
+		tree.remove_node(tree.root());
+		assert!(tree.get(tree.root()).is_some());
+	}
+
+	#[test]
+	fn key_events_reach_only_the_focused_node() {
+		let mut tree = GuiTree::new();
+		let panel = tree.add_node(None, node(0., 0., 50., 50.));
+
+		// Without focus, events are dropped
+		tree.handle_key(pressed(VirtualKeyCode::A));
+		assert!(tree.get(tree.root()).unwrap().pending_key_events.is_empty());
+
+		tree.set_focus(Some(panel));
+		tree.handle_key(pressed(VirtualKeyCode::B));
+		assert!(tree.get(tree.root()).unwrap().pending_key_events.is_empty());
+		assert_eq!(tree.get(panel).unwrap().pending_key_events, vec![pressed(VirtualKeyCode::B)]);
+	}
+
+	#[test]
+	fn only_the_focused_text_field_receives_keystrokes() {
+		let mut tree = GuiTree::new();
+		let mut field_node = GuiNode::new(ColorPalette::Panel);
+		field_node.text_field = Some(crate::gui_node::TextField::new(""));
+		let focused = tree.add_node(None, field_node);
+		let mut other_node = GuiNode::new(ColorPalette::Panel);
+		other_node.text_field = Some(crate::gui_node::TextField::new(""));
+		let other = tree.add_node(None, other_node);
+
+		tree.set_focus(Some(focused));
+		tree.handle_key(pressed(VirtualKeyCode::O));
+		tree.handle_key(pressed(VirtualKeyCode::K));
+
+		assert_eq!(tree.get(focused).unwrap().text_field.as_ref().unwrap().text, "ok");
+		assert_eq!(tree.get(other).unwrap().text_field.as_ref().unwrap().text, "");
+		// The edit dirtied the field so the caret and text repaint
+		assert!(tree.take_dirty_region().is_some());
+	}
+
+	#[test]
+	fn tab_traversal_wraps_in_both_directions() {
+		let mut tree = GuiTree::new();
+		let first = tree.add_node(None, button_node(0., 0., 10., 10.));
+		let second = tree.add_node(None, button_node(10., 0., 10., 10.));
+		let third = tree.add_node(None, button_node(20., 0., 10., 10.));
+
+		// With nothing focused, Tab starts at the first control
+		tree.advance_focus(false);
+		assert_eq!(tree.focused_node(), Some(first));
+		tree.advance_focus(false);
+		assert_eq!(tree.focused_node(), Some(second));
+		tree.advance_focus(false);
+		assert_eq!(tree.focused_node(), Some(third));
+
+		// Advancing past the end wraps back to the first; retreating from it wraps to the last
+		tree.advance_focus(false);
+		assert_eq!(tree.focused_node(), Some(first));
+		tree.advance_focus(true);
+		assert_eq!(tree.focused_node(), Some(third));
+	}
+
+	#[test]
+	fn traversal_skips_disabled_and_invisible_controls() {
+		let mut tree = GuiTree::new();
+		let first = tree.add_node(None, button_node(0., 0., 10., 10.));
+		let mut hidden = button_node(10., 0., 10., 10.);
+		hidden.visible = false;
+		tree.add_node(None, hidden);
+		let mut grayed = button_node(20., 0., 10., 10.);
+		grayed.disabled = true;
+		tree.add_node(None, grayed);
+		let last = tree.add_node(None, button_node(30., 0., 10., 10.));
+
+		// Plain containers never enter the order, and neither do the hidden or disabled buttons
+		assert_eq!(tree.focus_order(), vec![first, last]);
+
+		tree.advance_focus(false);
+		assert_eq!(tree.focused_node(), Some(first));
+		tree.advance_focus(false);
+		assert_eq!(tree.focused_node(), Some(last));
+
+		// Shift-Tab from nowhere starts at the last control
+		tree.set_focus(None);
+		tree.advance_focus(true);
+		assert_eq!(tree.focused_node(), Some(last));
+	}
+
+	#[test]
+	fn the_focus_ring_hugs_the_outside_of_the_bounds() {
+		let [top, bottom, left, right] = focus_ring_rects(Rect::new(10., 10., 20., 10.));
+		assert_eq!(top, Rect::new(8., 8., 24., 2.));
+		assert_eq!(bottom, Rect::new(8., 20., 24., 2.));
+		assert_eq!(left, Rect::new(8., 10., 2., 10.));
+		assert_eq!(right, Rect::new(30., 10., 2., 10.));
+	}
+
+	#[test]
+	fn the_caret_blinks_on_a_steady_phase() {
+		let ms = std::time::Duration::from_millis;
+		assert!(caret_blink_visible(ms(0)));
+		assert!(caret_blink_visible(ms(499)));
+		assert!(!caret_blink_visible(ms(500)));
+		assert!(caret_blink_visible(ms(1000)));
+	}
+
+	#[test]
+	fn the_caret_quad_tracks_the_caret_column() {
+		let mut field = crate::gui_node::TextField::new("abc");
+		let bounds = Rect::new(10., 20., 100., 16.);
+
+		// At the end of three characters the caret sits three columns in
+		assert_eq!(caret_rect(&field, bounds), Rect::new(10. + 3. * TEXT_FIELD_CHAR_WIDTH, 20., CARET_WIDTH, 16.));
+
+		// Multibyte characters still count as one column
+		field.text = String::from("日本");
+		field.caret = 3;
+		assert_eq!(caret_rect(&field, bounds).x, 10. + TEXT_FIELD_CHAR_WIDTH);
+	}
+
+	#[test]
+	fn dragging_a_pressed_slider_follows_and_clamps_the_cursor() {
+		let mut tree = GuiTree::new();
+		let mut slider_node = node(100., 0., 200., 20.);
+		slider_node.slider = Some(crate::gui_node::Slider::new(0., 10., 0.));
+		let slider = tree.add_node(None, slider_node);
+
+		// No press yet, so cursor movement does nothing
+		assert!(!tree.handle_cursor_drag(150., 10.));
+
+		tree.handle_pointer(slider, PointerEvent::Down);
+		assert!(tree.handle_cursor_drag(200., 10.));
+		assert_eq!(tree.get(slider).unwrap().slider.as_ref().unwrap().value, 5.);
+
+		// The press captures the pointer: dragging past the end keeps following, clamped to the range
+		assert!(tree.handle_cursor_drag(500., 10.));
+		assert_eq!(tree.get(slider).unwrap().slider.as_ref().unwrap().value, 10.);
+
+		// Releasing ends the capture
+		tree.handle_pointer(slider, PointerEvent::Up);
+		assert!(!tree.handle_cursor_drag(100., 10.));
+
+		// Every committed value was queued for the widget, oldest first
+		assert_eq!(tree.get_mut(slider).unwrap().slider.as_mut().unwrap().take_changes(), vec![5., 10.]);
+	}
+
+	#[test]
+	fn a_picker_drag_stays_latched_to_the_region_the_press_landed_in() {
+		let mut tree = GuiTree::new();
+		let mut picker_node = node(0., 0., 120., 100.);
+		picker_node.color_picker = Some(crate::gui_node::ColorPicker::new(0xff0000ff));
+		let picker = tree.add_node(None, picker_node);
+
+		// The square spans x 0..100, so its center maps to half saturation and three-quarter value
+		tree.handle_pointer(picker, PointerEvent::Down);
+		assert!(tree.handle_cursor_drag(50., 25.));
+		let state = tree.get(picker).unwrap().color_picker.as_ref().unwrap();
+		assert!((state.saturation - 0.5).abs() < 1e-6);
+		assert!((state.value - 0.75).abs() < 1e-6);
+
+		// Wandering over the hue bar mid-drag keeps adjusting the square, clamped at its edges
+		assert!(tree.handle_cursor_drag(150., -20.));
+		let state = tree.get(picker).unwrap().color_picker.as_ref().unwrap();
+		assert_eq!((state.saturation, state.value), (1., 1.));
+		assert_eq!(state.hue, 0.);
+		tree.handle_pointer(picker, PointerEvent::Up);
+
+		// A fresh press on the bar adjusts the hue instead: halfway down is 180 degrees
+		tree.handle_pointer(picker, PointerEvent::Down);
+		assert!(tree.handle_cursor_drag(110., 50.));
+		assert!((tree.get(picker).unwrap().color_picker.as_ref().unwrap().hue - 180.).abs() < 1e-3);
+		tree.handle_pointer(picker, PointerEvent::Up);
+
+		// Every committed color was queued for the widget, oldest first
+		assert_eq!(tree.get_mut(picker).unwrap().color_picker.as_mut().unwrap().take_changes().len(), 3);
+	}
+
+	fn button_node(x: f32, y: f32, width: f32, height: f32) -> GuiNode {
+		let mut button_node = node(x, y, width, height);
+		button_node.button = Some(crate::gui_node::Button::new(ColorPalette::Panel, ColorPalette::Gray, ColorPalette::Accent));
+		button_node
+	}
+
+	#[test]
+	fn pointer_events_drive_the_button_through_its_states() {
+		let mut tree = GuiTree::new();
+		let button = tree.add_node(None, button_node(0., 0., 40., 20.));
+		let state = |tree: &GuiTree| tree.get(button).unwrap().button.as_ref().unwrap().state;
+
+		// Entering shows Hover and repaints with the hover role
+		assert!(tree.handle_cursor_move(10., 10.));
+		assert_eq!(state(&tree), crate::gui_node::ButtonState::Hover);
+		assert_eq!(tree.get(button).unwrap().color, ColorPalette::Gray);
+
+		tree.handle_pointer(button, PointerEvent::Down);
+		assert_eq!(state(&tree), crate::gui_node::ButtonState::Pressed);
+		assert_eq!(tree.get(button).unwrap().color, ColorPalette::Accent);
+
+		// Releasing on the button clicks and settles back to Hover
+		tree.handle_pointer(button, PointerEvent::Up);
+		assert_eq!(state(&tree), crate::gui_node::ButtonState::Hover);
+		assert_eq!(tree.get_mut(button).unwrap().button.as_mut().unwrap().take_clicks(), 1);
+	}
+
+	#[test]
+	fn a_press_that_leaves_and_returns_still_clicks_on_release() {
+		let mut tree = GuiTree::new();
+		let button = tree.add_node(None, button_node(0., 0., 40., 20.));
+		let state = |tree: &GuiTree| tree.get(button).unwrap().button.as_ref().unwrap().state;
+
+		tree.handle_cursor_move(10., 10.);
+		tree.handle_pointer(button, PointerEvent::Down);
+
+		// Dragging off the button relaxes the visuals without ending the hold
+		assert!(tree.handle_cursor_move(100., 100.));
+		assert_eq!(state(&tree), crate::gui_node::ButtonState::Normal);
+
+		// Coming back restores Pressed, and the release inside still fires the click
+		assert!(tree.handle_cursor_move(20., 10.));
+		assert_eq!(state(&tree), crate::gui_node::ButtonState::Pressed);
+		tree.handle_pointer(button, PointerEvent::Up);
+		assert_eq!(tree.get_mut(button).unwrap().button.as_mut().unwrap().take_clicks(), 1);
+	}
+
+	#[test]
+	fn a_release_off_the_button_ends_the_hold_without_clicking() {
+		let mut tree = GuiTree::new();
+		let other = tree.add_node(None, node(100., 100., 40., 20.));
+		let button = tree.add_node(None, button_node(0., 0., 40., 20.));
+
+		tree.handle_pointer(button, PointerEvent::Down);
+		tree.handle_cursor_move(110., 110.);
+		tree.handle_pointer(other, PointerEvent::Up);
+
+		let released = tree.get(button).unwrap().button.as_ref().unwrap();
+		assert_eq!(released.state, crate::gui_node::ButtonState::Normal);
+		assert!(!released.held);
+		assert_eq!(tree.get_mut(button).unwrap().button.as_mut().unwrap().take_clicks(), 0);
+
+		// A cancelled press (release outside every node) releases the button likewise
+		tree.handle_pointer(button, PointerEvent::Down);
+		tree.clear_press();
+		assert!(!tree.get(button).unwrap().button.as_ref().unwrap().held);
+		assert_eq!(tree.get_mut(button).unwrap().button.as_mut().unwrap().take_clicks(), 0);
+	}
+
+	#[test]
+	fn the_slider_thumb_travels_the_track_minus_its_own_width() {
+		let slider = crate::gui_node::Slider::new(0., 1., 0.5);
+		let bounds = Rect::new(0., 0., 108., 20.);
+
+		// Half way along the 100 pixels of travel left over after the thumb's width
+		assert_eq!(slider_thumb_rect(&slider, bounds), Rect::new(50., 0., SLIDER_THUMB_WIDTH, 20.));
+
+		let at_max = crate::gui_node::Slider::new(0., 1., 1.);
+		assert_eq!(slider_thumb_rect(&at_max, bounds).x, 100.);
+	}
+
+	#[test]
+	fn removal_clears_focus_into_the_subtree() {
+		let mut tree = GuiTree::new();
+		let panel = tree.add_node(None, node(0., 0., 50., 50.));
+		let button = tree.add_node(Some(panel), node(0., 0., 10., 10.));
+
+		tree.set_focus(Some(button));
+		tree.remove_node(panel);
+		assert_eq!(tree.focused_node(), None);
+	}
+
+	#[test]
+	fn press_and_release_on_the_same_node_fires_a_click() {
+		let mut tree = GuiTree::new();
+		let root = tree.root();
+
+		tree.handle_pointer(root, PointerEvent::Down);
+		tree.handle_pointer(root, PointerEvent::Up);
+
+		assert_eq!(tree.get(root).unwrap().pending_pointer_events, vec![PointerEvent::Down, PointerEvent::Up, PointerEvent::Click]);
+	}
+
+	#[test]
+	fn releasing_on_a_different_node_does_not_click() {
+		let mut tree = GuiTree::new();
+		let panel = tree.add_node(None, node(50., 50., 50., 50.));
+		let root = tree.root();
+
+		tree.handle_pointer(root, PointerEvent::Down);
+		tree.handle_pointer(panel, PointerEvent::Up);
+
+		assert_eq!(tree.get(root).unwrap().pending_pointer_events, vec![PointerEvent::Down]);
+		assert_eq!(tree.get(panel).unwrap().pending_pointer_events, vec![PointerEvent::Up]);
+	}
+
+	#[test]
+	fn a_capturing_ancestor_intercepts_before_the_target() {
+		let mut tree = GuiTree::new();
+		let mut panel = GuiNode::new(ColorPalette::Panel);
+		panel.capture_pointer = true;
+		panel.pointer_propagation = EventPropagation::Handled;
+		let panel = tree.add_node(None, panel);
+		let button = tree.add_node(Some(panel), GuiNode::new(ColorPalette::Accent));
+
+		tree.handle_pointer(button, PointerEvent::Down);
+
+		// The capture phase ran the panel's handler first, and Handled kept the event from the target
+		assert_eq!(tree.get(panel).unwrap().pending_pointer_events, vec![PointerEvent::Down]);
+		assert!(tree.get(button).unwrap().pending_pointer_events.is_empty());
+	}
+
+	#[test]
+	fn bubbling_reaches_opted_in_ancestors_after_the_target() {
+		let mut tree = GuiTree::new();
+		let mut panel = GuiNode::new(ColorPalette::Panel);
+		panel.bubble_pointer = true;
+		let panel = tree.add_node(None, panel);
+		let button = tree.add_node(Some(panel), GuiNode::new(ColorPalette::Accent));
+
+		tree.handle_pointer(button, PointerEvent::Down);
+
+		// The target received the event and the bubble carried it on to the panel, but the root,
+		// which never opted in, saw nothing
+		assert_eq!(tree.get(button).unwrap().pending_pointer_events, vec![PointerEvent::Down]);
+		assert_eq!(tree.get(panel).unwrap().pending_pointer_events, vec![PointerEvent::Down]);
+		assert!(tree.get(tree.root()).unwrap().pending_pointer_events.is_empty());
+	}
+
+	#[test]
+	fn a_handled_target_stops_the_bubble() {
+		let mut tree = GuiTree::new();
+		let mut panel = GuiNode::new(ColorPalette::Panel);
+		panel.bubble_pointer = true;
+		let panel = tree.add_node(None, panel);
+		let mut consuming = GuiNode::new(ColorPalette::Accent);
+		consuming.pointer_propagation = EventPropagation::Handled;
+		let button = tree.add_node(Some(panel), consuming);
+
+		tree.handle_pointer(button, PointerEvent::Down);
+
+		assert_eq!(tree.get(button).unwrap().pending_pointer_events, vec![PointerEvent::Down]);
+		assert!(tree.get(panel).unwrap().pending_pointer_events.is_empty());
+	}
+
+	#[test]
+	fn a_cancelled_press_cannot_click_later() {
+		let mut tree = GuiTree::new();
+		let root = tree.root();
+
+		tree.handle_pointer(root, PointerEvent::Down);
+		tree.clear_press();
+		tree.handle_pointer(root, PointerEvent::Up);
+
+		assert_eq!(tree.get(root).unwrap().pending_pointer_events, vec![PointerEvent::Down, PointerEvent::Up]);
+	}
+
+	#[test]
+	fn scrolling_accumulates_into_the_content_offset() {
+		let mut tree = GuiTree::new();
+		let root = tree.root();
+
+		tree.handle_scroll(root, ScrollEvent { dx: 0., dy: -SCROLL_PIXELS_PER_LINE });
+		tree.handle_scroll(root, ScrollEvent { dx: 5., dy: -SCROLL_PIXELS_PER_LINE });
+
+		assert_eq!(tree.get(root).unwrap().scroll_offset, (5., -2. * SCROLL_PIXELS_PER_LINE));
+	}
+
+	#[test]
+	fn the_highest_z_index_paints_on_top_of_overlapping_quads() {
+		let mut tree = GuiTree::new();
+
+		// Three fully overlapping quads added in tree order with mixed z-indices
+		let mut below = GuiNode::new(ColorPalette::DarkGray);
+		below.z_index = -2;
+		let middle = GuiNode::new(ColorPalette::Gray);
+		let mut top = GuiNode::new(ColorPalette::Accent);
+		top.z_index = 5;
+
+		let top = tree.add_node(None, top);
+		tree.add_node(None, middle);
+		tree.add_node(None, below);
+
+		for id in tree.draw_order() {
+			if let Some(node) = tree.get_mut(id) {
+				node.computed_bounds = Rect::new(0., 0., 100., 100.);
+			}
+		}
+
+		// The z = 5 node paints last despite being added first, and hit-testing finds it on top
+		assert_eq!(*tree.paint_order().last().unwrap(), top);
+		assert_eq!(tree.hit_test(50., 50.), Some(top));
+
+		// Its baked depth also sits nearer the camera than the default plane
+		assert!(z_to_depth(5) < z_to_depth(0));
+		assert!(z_to_depth(-2) > z_to_depth(0));
+	}
+
+	#[test]
+	fn children_are_clipped_to_their_ancestor_panels() {
+		let mut tree = GuiTree::new();
+		let mut panel = GuiNode::new(ColorPalette::Panel);
+		panel.clip = true;
+		let panel = tree.add_node(None, panel);
+		let child = tree.add_node(Some(panel), GuiNode::new(ColorPalette::Gray));
+
+		tree.get_mut(panel).unwrap().computed_bounds = Rect::new(0., 0., 50., 50.);
+		tree.get_mut(child).unwrap().computed_bounds = Rect::new(40., 40., 30., 30.);
+
+		// The child clips to the panel's bounds; the panel itself has no clipping ancestor
+		assert_eq!(tree.clip_rect_for(child), Some(Rect::new(0., 0., 50., 50.)));
+		assert_eq!(tree.clip_rect_for(panel), None);
+	}
+
+	#[test]
+	fn nested_clips_intersect_and_disjoint_ones_leave_nothing_visible() {
+		let mut tree = GuiTree::new();
+		let mut outer = node(0., 0., 50., 50.);
+		outer.clip = true;
+		let outer = tree.add_node(None, outer);
+		let mut inner = node(30., 10., 40., 20.);
+		inner.clip = true;
+		let inner = tree.add_node(Some(outer), inner);
+		let leaf = tree.add_node(Some(inner), node(35., 15., 5., 5.));
+
+		// Nested clipping panels narrow the visible region to their overlap
+		assert_eq!(tree.clip_rect_for(leaf), Some(Rect::new(30., 10., 20., 20.)));
+
+		// Moving the inner panel outside the outer one leaves a degenerate region, which replay
+		// skips drawing entirely
+		tree.get_mut(inner).unwrap().computed_bounds = Rect::new(60., 0., 20., 50.);
+		let empty = tree.clip_rect_for(leaf).unwrap();
+		assert!(empty.width <= 0.);
+	}
+
+	#[test]
+	fn equal_z_indices_keep_tree_order() {
+		let mut tree = GuiTree::new();
+		let first = tree.add_node(None, GuiNode::new(ColorPalette::Gray));
+		let second = tree.add_node(None, GuiNode::new(ColorPalette::Gray));
+
+		let order = tree.paint_order();
+		let first_position = order.iter().position(|&id| id == first).unwrap();
+		let second_position = order.iter().position(|&id| id == second).unwrap();
+		assert!(first_position < second_position);
+	}
+
+	#[test]
+	fn clipboard_events_reach_only_the_focused_node() {
+		let mut tree = GuiTree::new();
+		let field = tree.add_node(None, GuiNode::new(ColorPalette::Panel));
+
+		// Without focus the paste has nowhere to go and is dropped
+		tree.handle_clipboard(ClipboardEvent::Paste(String::from("ignored")));
+		assert!(tree.get(field).unwrap().pending_clipboard_events.is_empty());
+
+		tree.set_focus(Some(field));
+		tree.handle_clipboard(ClipboardEvent::Copy);
+		tree.handle_clipboard(ClipboardEvent::Paste(String::from("#3194d6")));
+
+		let pending = &tree.get(field).unwrap().pending_clipboard_events;
+		assert_eq!(pending.as_slice(), &[ClipboardEvent::Copy, ClipboardEvent::Paste(String::from("#3194d6"))]);
+	}
+
+	#[test]
+	fn dropped_files_queue_on_the_target_node() {
+		let mut tree = GuiTree::new();
+		let panel = tree.add_node(None, GuiNode::new(ColorPalette::Gray));
+
+		tree.handle_file_drop(panel, FileDropEvent { path: std::path::PathBuf::from("image.png") });
+
+		let pending = &tree.get(panel).unwrap().pending_file_events;
+		assert_eq!(pending.len(), 1);
+		assert_eq!(pending[0].path, std::path::PathBuf::from("image.png"));
+		// The drop reached only its target
+		assert!(tree.get(tree.root()).unwrap().pending_file_events.is_empty());
+	}
+
+	#[test]
+	fn dirty_bounds_union_and_drain() {
+		let mut tree = GuiTree::new();
+		let left = tree.add_node(None, node(0., 0., 10., 10.));
+		let right = tree.add_node(None, node(40., 40., 10., 10.));
+
+		// Nothing is dirty until a node is marked
+		assert_eq!(tree.take_dirty_region(), None);
+
+		tree.mark_node_dirty(left);
+		tree.mark_node_dirty(right);
+
+		// The region is the union of both bounds, and taking it starts a fresh accumulation
+		assert_eq!(tree.take_dirty_region(), Some(Rect::new(0., 0., 50., 50.)));
+		assert_eq!(tree.take_dirty_region(), None);
+	}
+
+	#[test]
+	fn layout_dirties_the_whole_viewport() {
+		let mut tree = GuiTree::new();
+		tree.add_node(None, node(0., 0., 10., 10.));
+
+		tree.layout(Size::new(200., 100.));
+		assert_eq!(tree.take_dirty_region(), Some(Rect::new(0., 0., 200., 100.)));
+	}
+
+	#[test]
+	fn two_growing_children_split_a_row_evenly() {
+		let mut tree = GuiTree::new();
+		let mut left = GuiNode::new(ColorPalette::DarkGray);
+		left.grow = 1.;
+		let mut right = GuiNode::new(ColorPalette::MildGray);
+		right.grow = 1.;
+		let left = tree.add_node(None, left);
+		let right = tree.add_node(None, right);
+
+		tree.layout(Size::new(200., 100.));
+
+		assert_eq!(tree.get(left).unwrap().computed_bounds, Rect::new(0., 0., 100., 100.));
+		assert_eq!(tree.get(right).unwrap().computed_bounds, Rect::new(100., 0., 100., 100.));
+	}
+
+	#[test]
+	fn fixed_widths_are_taken_before_growth_is_distributed() {
+		let mut tree = GuiTree::new();
+		let mut sidebar = GuiNode::new(ColorPalette::DarkGray);
+		sidebar.width = Some(60.);
+		let mut content = GuiNode::new(ColorPalette::MildGray);
+		content.grow = 1.;
+		let sidebar = tree.add_node(None, sidebar);
+		let content = tree.add_node(None, content);
+
+		tree.layout(Size::new(200., 100.));
+
+		assert_eq!(tree.get(sidebar).unwrap().computed_bounds, Rect::new(0., 0., 60., 100.));
+		assert_eq!(tree.get(content).unwrap().computed_bounds, Rect::new(60., 0., 140., 100.));
+	}
+
+	#[test]
+	fn columns_stack_vertically_inside_the_padding() {
+		let mut tree = GuiTree::new();
+		tree.get_mut(tree.root()).unwrap().direction = FlexDirection::Column;
+		tree.get_mut(tree.root()).unwrap().padding = 10.;
+		let mut header = GuiNode::new(ColorPalette::DarkGray);
+		header.height = Some(30.);
+		let mut body = GuiNode::new(ColorPalette::MildGray);
+		body.grow = 1.;
+		let header = tree.add_node(None, header);
+		let body = tree.add_node(None, body);
+
+		tree.layout(Size::new(100., 200.));
+
+		assert_eq!(tree.get(header).unwrap().computed_bounds, Rect::new(10., 10., 80., 30.));
+		assert_eq!(tree.get(body).unwrap().computed_bounds, Rect::new(10., 40., 80., 150.));
+	}
+
+	#[test]
+	fn hit_test_returns_the_topmost_containing_node() {
+		let mut tree = GuiTree::new();
+		tree.get_mut(tree.root()).unwrap().computed_bounds = Rect::new(0., 0., 100., 100.);
+		let inner = tree.add_node(None, node(25., 25., 50., 50.));
+
+		// The overlapping region resolves to the later (topmost) node
+		assert_eq!(tree.hit_test(50., 50.), Some(inner));
+		// Outside the inner node but inside the root
+		assert_eq!(tree.hit_test(10., 10.), Some(tree.root()));
+		// Outside everything
+		assert_eq!(tree.hit_test(150., 50.), None);
+	}
+
+	#[test]
+	fn hit_test_bounds_are_inclusive_of_the_origin_edge_only() {
+		let mut tree = GuiTree::new();
+		tree.get_mut(tree.root()).unwrap().computed_bounds = Rect::new(0., 0., 100., 100.);
+
+		assert_eq!(tree.hit_test(0., 0.), Some(tree.root()));
+		assert_eq!(tree.hit_test(100., 100.), None);
+	}
+
+	#[test]
+	fn the_tree_round_trips_through_json() {
+		let mut tree = GuiTree::new();
+		let mut panel = GuiNode::new(ColorPalette::Panel);
+		panel.direction = FlexDirection::Column;
+		panel.width = Some(120.);
+		panel.padding = 4.;
+		panel.z_index = 3;
+		panel.clip = true;
+		let panel = tree.add_node(None, panel);
+		let mut field = GuiNode::new(ColorPalette::Color(0x3194d6ff));
+		field.text_field = Some(crate::gui_node::TextField::new("hello"));
+		tree.add_node(Some(panel), field);
+		let mut grayed = button_node(0., 0., 10., 10.);
+		grayed.disabled = true;
+		tree.add_node(None, grayed);
+
+		let restored = GuiTree::from_json(&tree.to_json()).expect("A saved tree loads back");
+		assert_eq!(restored.len(), tree.len());
+
+		// The hierarchy survives: the panel and the button under the root, the field under the panel
+		let top_level = restored.children(restored.root()).to_vec();
+		assert_eq!(top_level.len(), 2);
+		let panel = restored.get(top_level[0]).unwrap();
+		assert_eq!(panel.direction, FlexDirection::Column);
+		assert_eq!(panel.width, Some(120.));
+		assert_eq!(panel.padding, 4.);
+		assert_eq!(panel.z_index, 3);
+		assert!(panel.clip);
+		let field = restored.get(restored.children(top_level[0])[0]).unwrap();
+		assert_eq!(field.color, ColorPalette::Color(0x3194d6ff));
+		assert_eq!(field.text_field.as_ref().unwrap().text, "hello");
+		let grayed = restored.get(top_level[1]).unwrap();
+		assert!(grayed.disabled);
+		assert_eq!(grayed.button.as_ref().unwrap().hover, ColorPalette::Gray);
+	}
+
+	#[test]
+	fn runtime_state_is_rebuilt_rather_than_saved() {
+		let mut tree = GuiTree::new();
+		let button = tree.add_node(None, button_node(10., 10., 40., 20.));
+		tree.set_focus(Some(button));
+		tree.handle_pointer(button, PointerEvent::Down);
+
+		// Mid-press, the button holds transient state the document must not carry
+		assert!(tree.get(button).unwrap().button.as_ref().unwrap().held);
+		assert!(!tree.get(button).unwrap().pending_pointer_events.is_empty());
+
+		let restored = GuiTree::from_json(&tree.to_json()).expect("A saved tree loads back");
+		assert_eq!(restored.focused_node(), None);
+		let loaded = restored.get(restored.children(restored.root())[0]).unwrap();
+		assert!(loaded.pending_pointer_events.is_empty());
+		assert!(!loaded.button.as_ref().unwrap().held);
+		assert_eq!(loaded.button.as_ref().unwrap().state, crate::gui_node::ButtonState::Normal);
+		// Bounds come back from the next layout pass, not the document
+		assert_eq!(loaded.computed_bounds, Rect::new(0., 0., 0., 0.));
+	}
+
+	#[test]
+	fn malformed_documents_are_rejected() {
+		assert!(GuiTree::from_json("not json").is_err());
+		assert!(GuiTree::from_json("{\"children\": []}").is_err());
+	}
+}
@@ -0,0 +1,79 @@
+// The render modules double as an embedding API: much of their surface is exercised only by
+// tests and embedders, none of which the binary target can see
+#![allow(dead_code)]
+
+mod application;
+mod buffer_pool;
+mod color_palette;
+mod draw_command;
+mod frame_stats;
+mod geometry;
+mod gpu_timer;
+mod gui_node;
+mod gui_tree;
+mod path;
+mod pipeline;
+mod post_process;
+mod render_graph;
+mod resource_cache;
+mod shader_stage;
+mod shader_watcher;
+mod staging_belt;
+#[cfg(test)]
+mod test_utils;
+mod text_renderer;
+mod texture;
+mod texture_atlas;
+mod uniform_buffer;
+mod window_context;
+mod window_events;
+mod window_state;
+
+use application::{Application, ApplicationConfig};
+
+fn main() {
+	// Handles all window events, user input, and redraws
+	let event_loop = winit::event_loop::EventLoop::new();
+
+	// Restore the previous session's window geometry, pulling it back onto a monitor that still
+	// exists in case the one it was saved on has been disconnected
+	let saved = Application::load_window_state(window_state::WINDOW_STATE_PATH).map(|mut state| {
+		let monitors: Vec<_> = event_loop
+			.available_monitors()
+			.map(|monitor| (monitor.position().x, monitor.position().y, monitor.size().width, monitor.size().height))
+			.collect();
+		state.clamp_to_monitors(&monitors);
+		state
+	});
+
+	// Initialize the window with a title, at the remembered size when one was saved
+	let mut builder = winit::window::WindowBuilder::new().with_title("Graphite");
+	if let Some(state) = saved {
+		builder = builder.with_inner_size(winit::dpi::PhysicalSize::new(state.width, state.height));
+	}
+	let window = builder.build(&event_loop).unwrap();
+	if let Some(state) = saved {
+		window.set_outer_position(winit::dpi::PhysicalPosition::new(state.x, state.y));
+	}
+
+	// Initialize the render pipeline, surfacing GPU setup failures with a useful message
+	let mut app = match Application::new(&window, ApplicationConfig::default()) {
+		Ok(app) => app,
+		Err(error) => {
+			eprintln!("Failed to initialize the GPU: {}", error);
+			std::process::exit(1);
+		}
+	};
+
+	// Lay out the GUI and build the draw commands for the first frame
+	app.redraw_gui();
+
+	// Every open window keyed by id; events are dispatched to the matching window context
+	let mut windows = std::collections::HashMap::new();
+	windows.insert(window.id(), window);
+
+	// Begin the application lifecycle, handing off ownership of the window event dispatch
+	event_loop.run(move |event, _, control_flow| {
+		window_events::window_event(&mut app, &windows, event, control_flow);
+	});
+}
@@ -0,0 +1,236 @@
+use crate::draw_command::Vertex;
+use lyon::math::point;
+use lyon::path::Path as LyonPath;
+use lyon::tessellation::{BuffersBuilder, FillAttributes, FillOptions, FillTessellator, StrokeAttributes, StrokeOptions, StrokeTessellator, TessellationError, VertexBuffers};
+
+// Re-exported so callers style strokes without importing lyon themselves
+pub use lyon::tessellation::{LineCap, LineJoin};
+
+// The commands making up a path, recorded by the builder methods below
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PathCommand {
+	MoveTo(f32, f32),
+	LineTo(f32, f32),
+	// Two control points followed by the destination
+	CubicTo(f32, f32, f32, f32, f32, f32),
+	Close,
+}
+
+// A vector path in logical pixels, built up from move/line/cubic segments and tessellated into
+// triangle geometry for DrawCommand; the editor's Bézier shapes all pass through here
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Path {
+	commands: Vec<PathCommand>,
+}
+
+impl Path {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	// Starts a new subpath at the given point
+	pub fn move_to(&mut self, x: f32, y: f32) -> &mut Self {
+		self.commands.push(PathCommand::MoveTo(x, y));
+		self
+	}
+
+	pub fn line_to(&mut self, x: f32, y: f32) -> &mut Self {
+		self.commands.push(PathCommand::LineTo(x, y));
+		self
+	}
+
+	// A cubic Bézier segment through the two control points to the destination
+	pub fn cubic_to(&mut self, ctrl1_x: f32, ctrl1_y: f32, ctrl2_x: f32, ctrl2_y: f32, x: f32, y: f32) -> &mut Self {
+		self.commands.push(PathCommand::CubicTo(ctrl1_x, ctrl1_y, ctrl2_x, ctrl2_y, x, y));
+		self
+	}
+
+	// Closes the current subpath with a straight segment back to its starting point
+	pub fn close(&mut self) -> &mut Self {
+		self.commands.push(PathCommand::Close);
+		self
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.commands.is_empty()
+	}
+
+	// Fills the path's interior (non-zero winding) into triangles; tolerance is the maximum
+	// distance in logical pixels the flattened curves may deviate from the true Béziers
+	pub fn tessellate_fill(&self, tolerance: f32) -> Result<Mesh, TessellationError> {
+		let mut buffers: VertexBuffers<Vertex, u16> = VertexBuffers::new();
+		FillTessellator::new().tessellate_path(
+			&self.to_lyon(),
+			&FillOptions::tolerance(tolerance),
+			&mut BuffersBuilder::new(&mut buffers, |position: lyon::math::Point, _: FillAttributes| Vertex { position: position.to_array() }),
+		)?;
+		Ok(Mesh {
+			vertices: buffers.vertices,
+			indices: buffers.indices,
+		})
+	}
+
+	// Outlines the path at the style's width into triangles, with its joins between segments and
+	// caps on open subpath ends
+	pub fn tessellate_stroke(&self, style: StrokeStyle, tolerance: f32) -> Result<Mesh, TessellationError> {
+		let options = StrokeOptions::tolerance(tolerance).with_line_width(style.width).with_line_join(style.join).with_start_cap(style.cap).with_end_cap(style.cap);
+
+		let mut buffers: VertexBuffers<Vertex, u16> = VertexBuffers::new();
+		StrokeTessellator::new().tessellate_path(
+			&self.to_lyon(),
+			&options,
+			&mut BuffersBuilder::new(&mut buffers, |position: lyon::math::Point, _: StrokeAttributes| Vertex { position: position.to_array() }),
+		)?;
+		Ok(Mesh {
+			vertices: buffers.vertices,
+			indices: buffers.indices,
+		})
+	}
+
+	// Replays the recorded commands into lyon's builder, dropping segments that arrive before any
+	// subpath has started so malformed input degenerates to an empty path instead of panicking
+	fn to_lyon(&self) -> LyonPath {
+		let mut builder = LyonPath::builder();
+		let mut open = false;
+		for command in &self.commands {
+			match *command {
+				PathCommand::MoveTo(x, y) => {
+					builder.move_to(point(x, y));
+					open = true;
+				}
+				PathCommand::LineTo(x, y) if open => {
+					builder.line_to(point(x, y));
+				}
+				PathCommand::CubicTo(ctrl1_x, ctrl1_y, ctrl2_x, ctrl2_y, x, y) if open => {
+					builder.cubic_bezier_to(point(ctrl1_x, ctrl1_y), point(ctrl2_x, ctrl2_y), point(x, y));
+				}
+				PathCommand::Close if open => {
+					builder.close();
+					open = false;
+				}
+				_ => {}
+			}
+		}
+		builder.build()
+	}
+}
+
+// How an open or joined stroke outline is shaped at the given width
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StrokeStyle {
+	// The stroke's total width in logical pixels, centered on the path
+	pub width: f32,
+	pub join: LineJoin,
+	pub cap: LineCap,
+}
+
+impl Default for StrokeStyle {
+	fn default() -> Self {
+		Self {
+			width: 1.,
+			join: LineJoin::Miter,
+			cap: LineCap::Butt,
+		}
+	}
+}
+
+// Tessellated triangle geometry, ready to feed DrawCommand::new as vertices and indices
+pub struct Mesh {
+	pub vertices: Vec<Vertex>,
+	pub indices: Vec<u16>,
+}
+
+impl Mesh {
+	pub fn triangle_count(&self) -> usize {
+		self.indices.len() / 3
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.indices.is_empty()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	// Tessellation distances within a hundredth of a pixel are plenty for these assertions
+	const TOLERANCE: f32 = 0.01;
+
+	fn unit_square() -> Path {
+		let mut path = Path::new();
+		path.move_to(0., 0.).line_to(1., 0.).line_to(1., 1.).line_to(0., 1.).close();
+		path
+	}
+
+	#[test]
+	fn a_filled_square_tessellates_to_two_triangles() {
+		let mesh = unit_square().tessellate_fill(TOLERANCE).expect("A simple square should tessellate");
+
+		assert_eq!(mesh.triangle_count(), 2);
+		assert_eq!(mesh.indices.len() % 3, 0);
+		// Every index addresses a vertex that actually exists
+		assert!(mesh.indices.iter().all(|&index| (index as usize) < mesh.vertices.len()));
+		// All corners sit on the unit square
+		for vertex in &mesh.vertices {
+			assert!(vertex.position[0] >= 0. && vertex.position[0] <= 1.);
+			assert!(vertex.position[1] >= 0. && vertex.position[1] <= 1.);
+		}
+	}
+
+	#[test]
+	fn a_cubic_curve_flattens_into_many_triangles() {
+		let mut path = Path::new();
+		path.move_to(0., 0.).cubic_to(25., -50., 75., -50., 100., 0.).close();
+
+		let mesh = path.tessellate_fill(TOLERANCE).expect("A curved fill should tessellate");
+		// Flattening the Bézier at this tolerance needs far more than one segment
+		assert!(mesh.triangle_count() > 4);
+	}
+
+	#[test]
+	fn stroking_a_line_produces_a_band_of_the_requested_width() {
+		let mut path = Path::new();
+		path.move_to(0., 0.).line_to(10., 0.);
+
+		let style = StrokeStyle { width: 2., ..StrokeStyle::default() };
+		let mesh = path.tessellate_stroke(style, TOLERANCE).expect("A straight stroke should tessellate");
+
+		assert!(!mesh.is_empty());
+		// A horizontal stroke of width 2 with butt caps stays within one pixel of the centerline
+		// and does not extend past the endpoints
+		for vertex in &mesh.vertices {
+			assert!(vertex.position[1].abs() <= 1. + TOLERANCE);
+			assert!(vertex.position[0] >= -TOLERANCE && vertex.position[0] <= 10. + TOLERANCE);
+		}
+	}
+
+	#[test]
+	fn square_caps_extend_past_the_endpoints() {
+		let mut path = Path::new();
+		path.move_to(0., 0.).line_to(10., 0.);
+
+		let style = StrokeStyle {
+			width: 2.,
+			cap: LineCap::Square,
+			..StrokeStyle::default()
+		};
+		let mesh = path.tessellate_stroke(style, TOLERANCE).expect("A capped stroke should tessellate");
+
+		// Square caps stick out half the width beyond each end of the centerline
+		let min_x = mesh.vertices.iter().map(|vertex| vertex.position[0]).fold(f32::INFINITY, f32::min);
+		let max_x = mesh.vertices.iter().map(|vertex| vertex.position[0]).fold(f32::NEG_INFINITY, f32::max);
+		assert!((min_x + 1.).abs() <= TOLERANCE);
+		assert!((max_x - 11.).abs() <= TOLERANCE);
+	}
+
+	#[test]
+	fn segments_before_any_move_to_are_ignored() {
+		let mut path = Path::new();
+		path.line_to(10., 10.).close();
+
+		let mesh = path.tessellate_fill(TOLERANCE).expect("A degenerate path should tessellate to nothing");
+		assert!(mesh.is_empty());
+		assert!(Path::new().tessellate_fill(TOLERANCE).expect("An empty path should tessellate to nothing").is_empty());
+	}
+}
@@ -0,0 +1,545 @@
+use std::collections::HashMap;
+
+// How a pipeline's output is combined with the color already in the frame buffer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BlendMode {
+	Opaque,
+	AlphaBlend,
+	Additive,
+}
+
+impl BlendMode {
+	// The (color, alpha) blend descriptors implementing this mode
+	fn blend_descriptors(&self) -> (wgpu::BlendDescriptor, wgpu::BlendDescriptor) {
+		match self {
+			BlendMode::Opaque => (wgpu::BlendDescriptor::REPLACE, wgpu::BlendDescriptor::REPLACE),
+			BlendMode::AlphaBlend => (
+				wgpu::BlendDescriptor {
+					src_factor: wgpu::BlendFactor::SrcAlpha,
+					dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+					operation: wgpu::BlendOperation::Add,
+				},
+				wgpu::BlendDescriptor {
+					src_factor: wgpu::BlendFactor::One,
+					dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+					operation: wgpu::BlendOperation::Add,
+				},
+			),
+			BlendMode::Additive => (
+				wgpu::BlendDescriptor {
+					src_factor: wgpu::BlendFactor::One,
+					dst_factor: wgpu::BlendFactor::One,
+					operation: wgpu::BlendOperation::Add,
+				},
+				wgpu::BlendDescriptor {
+					src_factor: wgpu::BlendFactor::One,
+					dst_factor: wgpu::BlendFactor::One,
+					operation: wgpu::BlendOperation::Add,
+				},
+			),
+		}
+	}
+}
+
+pub struct Pipeline {
+	pub render_pipeline: wgpu::RenderPipeline,
+	// One layout per bind group slot; most pipelines use only slot 0 with the texture layout below
+	pub bind_group_layouts: Vec<wgpu::BindGroupLayout>,
+	pub index_format: wgpu::IndexFormat,
+}
+
+impl Pipeline {
+	// The common case: shaders compiled from GLSL always expose a single entry point named main
+	#[allow(clippy::too_many_arguments)]
+	pub fn new(
+		device: &wgpu::Device,
+		format: wgpu::TextureFormat,
+		vertex_shader: &wgpu::ShaderModule,
+		fragment_shader: &wgpu::ShaderModule,
+		vertex_buffer_descriptor: wgpu::VertexBufferDescriptor,
+		instance_buffer_descriptor: Option<wgpu::VertexBufferDescriptor>,
+		index_format: wgpu::IndexFormat,
+		blend_mode: BlendMode,
+		sample_count: u32,
+		topology: wgpu::PrimitiveTopology,
+	) -> Self {
+		Pipeline::new_with_entry_points(
+			device,
+			format,
+			vertex_shader,
+			"main",
+			fragment_shader,
+			"main",
+			vertex_buffer_descriptor,
+			instance_buffer_descriptor,
+			index_format,
+			blend_mode,
+			sample_count,
+			topology,
+		)
+	}
+
+	// Like new, but with explicit winding and culling, for 3D geometry or tessellator output whose
+	// triangle orientation actually matters; the other constructors never cull
+	#[allow(clippy::too_many_arguments)]
+	pub fn new_with_culling(
+		device: &wgpu::Device,
+		format: wgpu::TextureFormat,
+		vertex_shader: &wgpu::ShaderModule,
+		fragment_shader: &wgpu::ShaderModule,
+		vertex_buffer_descriptor: wgpu::VertexBufferDescriptor,
+		instance_buffer_descriptor: Option<wgpu::VertexBufferDescriptor>,
+		index_format: wgpu::IndexFormat,
+		blend_mode: BlendMode,
+		sample_count: u32,
+		topology: wgpu::PrimitiveTopology,
+		front_face: wgpu::FrontFace,
+		cull_mode: wgpu::CullMode,
+	) -> Self {
+		let bind_group_layout = Pipeline::texture_bind_group_layout(device);
+		Pipeline::with_bind_group_layouts(
+			device,
+			format,
+			vertex_shader,
+			"main",
+			fragment_shader,
+			"main",
+			vertex_buffer_descriptor,
+			instance_buffer_descriptor,
+			index_format,
+			blend_mode,
+			sample_count,
+			topology,
+			front_face,
+			cull_mode,
+			vec![bind_group_layout],
+		)
+	}
+
+	// Like new, but selecting which entry point to run in each shader module, for modules (e.g.
+	// WGSL) that declare several entry points alongside one another
+	#[allow(clippy::too_many_arguments)]
+	pub fn new_with_entry_points(
+		device: &wgpu::Device,
+		format: wgpu::TextureFormat,
+		vertex_shader: &wgpu::ShaderModule,
+		vertex_entry: &str,
+		fragment_shader: &wgpu::ShaderModule,
+		fragment_entry: &str,
+		vertex_buffer_descriptor: wgpu::VertexBufferDescriptor,
+		instance_buffer_descriptor: Option<wgpu::VertexBufferDescriptor>,
+		index_format: wgpu::IndexFormat,
+		blend_mode: BlendMode,
+		sample_count: u32,
+		topology: wgpu::PrimitiveTopology,
+	) -> Self {
+		let bind_group_layout = Pipeline::texture_bind_group_layout(device);
+		Pipeline::with_bind_group_layouts(
+			device,
+			format,
+			vertex_shader,
+			vertex_entry,
+			fragment_shader,
+			fragment_entry,
+			vertex_buffer_descriptor,
+			instance_buffer_descriptor,
+			index_format,
+			blend_mode,
+			sample_count,
+			topology,
+			// UI quads are authored in screen space, so culling would only ever lose geometry
+			wgpu::FrontFace::Ccw,
+			wgpu::CullMode::None,
+			vec![bind_group_layout],
+		)
+	}
+
+	// Like new, but with a uniform-only slot 0 layout for shaders that interpolate vertex colors
+	// instead of sampling a texture
+	#[allow(clippy::too_many_arguments)]
+	pub fn new_colored(
+		device: &wgpu::Device,
+		format: wgpu::TextureFormat,
+		vertex_shader: &wgpu::ShaderModule,
+		fragment_shader: &wgpu::ShaderModule,
+		vertex_buffer_descriptor: wgpu::VertexBufferDescriptor,
+		index_format: wgpu::IndexFormat,
+		blend_mode: BlendMode,
+		sample_count: u32,
+		topology: wgpu::PrimitiveTopology,
+	) -> Self {
+		let bind_group_layout = Pipeline::uniform_bind_group_layout(device);
+		Pipeline::with_bind_group_layouts(
+			device,
+			format,
+			vertex_shader,
+			"main",
+			fragment_shader,
+			"main",
+			vertex_buffer_descriptor,
+			None,
+			index_format,
+			blend_mode,
+			sample_count,
+			topology,
+			// UI quads are authored in screen space, so culling would only ever lose geometry
+			wgpu::FrontFace::Ccw,
+			wgpu::CullMode::None,
+			vec![bind_group_layout],
+		)
+	}
+
+	// The signed-distance-field shape pipeline: a single quad whose UVs parameterize the distance
+	// field, with one uniform block of shape parameters read by both stages. Always alpha-blended,
+	// since the field's anti-aliased edge coverage lands in the alpha channel
+	pub fn new_sdf(device: &wgpu::Device, format: wgpu::TextureFormat, vertex_shader: &wgpu::ShaderModule, fragment_shader: &wgpu::ShaderModule, sample_count: u32) -> Self {
+		let bind_group_layout = Pipeline::sdf_bind_group_layout(device);
+		Pipeline::with_bind_group_layouts(
+			device,
+			format,
+			vertex_shader,
+			"main",
+			fragment_shader,
+			"main",
+			crate::draw_command::Vertex2DTextured::buffer_descriptor(),
+			None,
+			wgpu::IndexFormat::Uint16,
+			BlendMode::AlphaBlend,
+			sample_count,
+			wgpu::PrimitiveTopology::TriangleList,
+			// UI quads are authored in screen space, so culling would only ever lose geometry
+			wgpu::FrontFace::Ccw,
+			wgpu::CullMode::None,
+			vec![bind_group_layout],
+		)
+	}
+
+	// Builds a pipeline whose shaders declare several bind group sets, e.g. a per-frame camera
+	// group in slot 0 and a per-object material group in slot 1
+	#[allow(clippy::too_many_arguments)]
+	pub fn with_bind_group_layouts(
+		device: &wgpu::Device,
+		format: wgpu::TextureFormat,
+		vertex_shader: &wgpu::ShaderModule,
+		vertex_entry: &str,
+		fragment_shader: &wgpu::ShaderModule,
+		fragment_entry: &str,
+		vertex_buffer_descriptor: wgpu::VertexBufferDescriptor,
+		instance_buffer_descriptor: Option<wgpu::VertexBufferDescriptor>,
+		index_format: wgpu::IndexFormat,
+		blend_mode: BlendMode,
+		sample_count: u32,
+		topology: wgpu::PrimitiveTopology,
+		// Which winding counts as front-facing and what gets culled; 2D content should pass
+		// CullMode::None, since screen-space geometry has no meaningful back side to discard
+		front_face: wgpu::FrontFace,
+		cull_mode: wgpu::CullMode,
+		bind_group_layouts: Vec<wgpu::BindGroupLayout>,
+	) -> Self {
+		let layout_references: Vec<&wgpu::BindGroupLayout> = bind_group_layouts.iter().collect();
+		let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+			bind_group_layouts: &layout_references,
+		});
+
+		let (color_blend, alpha_blend) = blend_mode.blend_descriptors();
+
+		// Slot 0 steps per vertex; an optional slot 1 steps per instance for instanced draws
+		let mut vertex_buffers = vec![vertex_buffer_descriptor];
+		if let Some(instance_buffer_descriptor) = instance_buffer_descriptor {
+			debug_assert_eq!(instance_buffer_descriptor.step_mode, wgpu::InputStepMode::Instance);
+			vertex_buffers.push(instance_buffer_descriptor);
+		}
+
+		let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+			layout: &pipeline_layout,
+			vertex_stage: wgpu::ProgrammableStageDescriptor {
+				module: vertex_shader,
+				entry_point: vertex_entry,
+			},
+			fragment_stage: Some(wgpu::ProgrammableStageDescriptor {
+				module: fragment_shader,
+				entry_point: fragment_entry,
+			}),
+			rasterization_state: Some(wgpu::RasterizationStateDescriptor {
+				front_face,
+				cull_mode,
+				depth_bias: 0,
+				depth_bias_slope_scale: 0.,
+				depth_bias_clamp: 0.,
+			}),
+			primitive_topology: topology,
+			color_states: &[wgpu::ColorStateDescriptor {
+				format,
+				color_blend,
+				alpha_blend,
+				write_mask: wgpu::ColorWrite::ALL,
+			}],
+			depth_stencil_state: Some(wgpu::DepthStencilStateDescriptor {
+				format: crate::texture::DEPTH_FORMAT,
+				depth_write_enabled: true,
+				depth_compare: wgpu::CompareFunction::Less,
+				stencil_front: wgpu::StencilStateFaceDescriptor::IGNORE,
+				stencil_back: wgpu::StencilStateFaceDescriptor::IGNORE,
+				stencil_read_mask: 0,
+				stencil_write_mask: 0,
+			}),
+			vertex_state: wgpu::VertexStateDescriptor {
+				index_format,
+				vertex_buffers: &vertex_buffers,
+			},
+			sample_count,
+			sample_mask: !0,
+			alpha_to_coverage_enabled: false,
+		});
+
+		Self {
+			render_pipeline,
+			bind_group_layouts,
+			index_format,
+		}
+	}
+
+	// Describes the resources of the standard texture-and-uniforms bind group most pipelines use in slot 0
+	fn texture_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+		device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+			bindings: &[
+				wgpu::BindGroupLayoutEntry {
+					binding: 0,
+					visibility: wgpu::ShaderStage::FRAGMENT,
+					ty: wgpu::BindingType::SampledTexture {
+						multisampled: false,
+						dimension: wgpu::TextureViewDimension::D2,
+						component_type: wgpu::TextureComponentType::Float,
+					},
+				},
+				wgpu::BindGroupLayoutEntry {
+					binding: 1,
+					visibility: wgpu::ShaderStage::FRAGMENT,
+					ty: wgpu::BindingType::Sampler { comparison: false },
+				},
+				wgpu::BindGroupLayoutEntry {
+					binding: 2,
+					visibility: wgpu::ShaderStage::VERTEX,
+					ty: wgpu::BindingType::UniformBuffer { dynamic: false },
+				},
+			],
+			label: None,
+		})
+	}
+
+	// Describes the slot 0 resources of vertex-colored pipelines: just the transform uniform
+	fn uniform_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+		device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+			bindings: &[wgpu::BindGroupLayoutEntry {
+				binding: 0,
+				visibility: wgpu::ShaderStage::VERTEX,
+				ty: wgpu::BindingType::UniformBuffer { dynamic: false },
+			}],
+			label: None,
+		})
+	}
+
+	// Describes the SDF pipeline's slot 0: one block of shape parameters, which the vertex stage
+	// reads for the transform and the fragment stage for the color and distance field
+	fn sdf_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+		device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+			bindings: &[wgpu::BindGroupLayoutEntry {
+				binding: 0,
+				visibility: wgpu::ShaderStage::VERTEX | wgpu::ShaderStage::FRAGMENT,
+				ty: wgpu::BindingType::UniformBuffer { dynamic: false },
+			}],
+			label: None,
+		})
+	}
+
+	// Binds a buffer holding an SdfUniforms block against the SDF pipeline's slot 0 layout
+	pub fn create_sdf_bind_group(&self, device: &wgpu::Device, uniform_buffer: &crate::uniform_buffer::UniformBuffer) -> wgpu::BindGroup {
+		device.create_bind_group(&wgpu::BindGroupDescriptor {
+			layout: &self.bind_group_layouts[0],
+			bindings: &[wgpu::Binding {
+				binding: 0,
+				resource: wgpu::BindingResource::Buffer {
+					buffer: &uniform_buffer.buffer,
+					range: 0..crate::draw_command::SDF_UNIFORM_SIZE,
+				},
+			}],
+			label: None,
+		})
+	}
+
+	// Binds a per-draw uniform buffer against a uniform-only slot 0 layout, the counterpart of
+	// create_texture_bind_group for pipelines built with new_colored
+	pub fn create_uniform_bind_group(&self, device: &wgpu::Device, uniform_buffer: &crate::uniform_buffer::UniformBuffer) -> wgpu::BindGroup {
+		device.create_bind_group(&wgpu::BindGroupDescriptor {
+			layout: &self.bind_group_layouts[0],
+			bindings: &[wgpu::Binding {
+				binding: 0,
+				resource: wgpu::BindingResource::Buffer {
+					buffer: &uniform_buffer.buffer,
+					range: 0..crate::uniform_buffer::MATRIX_SIZE,
+				},
+			}],
+			label: None,
+		})
+	}
+
+	// Binds a texture and a per-draw uniform buffer against this pipeline's slot 0 layout, so callers
+	// don't hand-write a BindGroupDescriptor whose indices can drift out of sync with the layout above
+	pub fn create_texture_bind_group(&self, device: &wgpu::Device, texture: &crate::texture::Texture, uniform_buffer: &crate::uniform_buffer::UniformBuffer) -> wgpu::BindGroup {
+		device.create_bind_group(&wgpu::BindGroupDescriptor {
+			layout: &self.bind_group_layouts[0],
+			bindings: &[
+				wgpu::Binding {
+					binding: 0,
+					resource: wgpu::BindingResource::TextureView(&texture.view),
+				},
+				wgpu::Binding {
+					binding: 1,
+					resource: wgpu::BindingResource::Sampler(&texture.sampler),
+				},
+				wgpu::Binding {
+					binding: 2,
+					resource: wgpu::BindingResource::Buffer {
+						buffer: &uniform_buffer.buffer,
+						range: 0..crate::uniform_buffer::MATRIX_SIZE,
+					},
+				},
+			],
+			label: None,
+		})
+	}
+}
+
+// Identifies a pipeline configuration, so call sites requesting the same shaders and state share one
+// compiled pipeline. The shaders are referenced by path, matching the keys of the shader cache
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PipelineKey {
+	pub vertex: String,
+	pub fragment: String,
+	pub blend: BlendMode,
+	pub topology: wgpu::PrimitiveTopology,
+	pub sample_count: u32,
+}
+
+// Deduplicates pipelines by configuration: two call sites describing the same key get one pipeline,
+// where the name-keyed ResourceCache would compile a duplicate under each name
+// The name-keyed API on Application stays as a thin wrapper over its cache during the migration to keys
+pub struct PipelineManager {
+	pipelines: HashMap<PipelineKey, Pipeline>,
+}
+
+impl PipelineManager {
+	pub fn new() -> Self {
+		Self { pipelines: HashMap::new() }
+	}
+
+	// Returns the pipeline for this configuration, compiling it only on the first request
+	// Returns None while either shader is missing from the cache, e.g. before its first compile finishes
+	// An empty layouts Vec selects the standard slot-0 texture layout; state outside the key's identity
+	// (vertex layout, index format) is the standard GUI geometry every current pipeline shares
+	pub fn get_or_create(&mut self, device: &wgpu::Device, format: wgpu::TextureFormat, key: PipelineKey, shader_cache: &crate::resource_cache::ResourceCache<wgpu::ShaderModule>, layouts: Vec<wgpu::BindGroupLayout>) -> Option<&Pipeline> {
+		if !self.pipelines.contains_key(&key) {
+			let (vertex_shader, fragment_shader) = match (shader_cache.get(&key.vertex), shader_cache.get(&key.fragment)) {
+				(Some(vertex_shader), Some(fragment_shader)) => (vertex_shader, fragment_shader),
+				_ => return None,
+			};
+
+			let layouts = if layouts.is_empty() { vec![Pipeline::texture_bind_group_layout(device)] } else { layouts };
+			let pipeline = Pipeline::with_bind_group_layouts(
+				device,
+				format,
+				vertex_shader,
+				"main",
+				fragment_shader,
+				"main",
+				crate::draw_command::Vertex2DTextured::buffer_descriptor(),
+				None,
+				wgpu::IndexFormat::Uint16,
+				key.blend,
+				key.sample_count,
+				key.topology,
+				wgpu::FrontFace::Ccw,
+				wgpu::CullMode::None,
+				layouts,
+			);
+			self.pipelines.insert(key.clone(), pipeline);
+		}
+
+		self.pipelines.get(&key)
+	}
+
+	pub fn len(&self) -> usize {
+		self.pipelines.len()
+	}
+}
+
+// Wraps a compute pipeline for non-triangle GPU workloads like image effects
+pub struct ComputePipeline {
+	pub compute_pipeline: wgpu::ComputePipeline,
+	pub bind_group_layout: wgpu::BindGroupLayout,
+}
+
+impl ComputePipeline {
+	pub fn new(device: &wgpu::Device, compute_shader: &wgpu::ShaderModule, bindings: &[wgpu::BindGroupLayoutEntry]) -> Self {
+		// Compute shaders declare their own resources, so the caller describes the layout
+		let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor { bindings, label: None });
+
+		let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+			bind_group_layouts: &[&bind_group_layout],
+		});
+
+		let compute_pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+			layout: &pipeline_layout,
+			compute_stage: wgpu::ProgrammableStageDescriptor {
+				module: compute_shader,
+				entry_point: "main",
+			},
+		});
+
+		Self { compute_pipeline, bind_group_layout }
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::resource_cache::ResourceCache;
+	use crate::test_utils::create_test_device;
+
+	fn gui_key() -> PipelineKey {
+		PipelineKey {
+			vertex: String::from("shaders/shader.vert"),
+			fragment: String::from("shaders/shader.frag"),
+			blend: BlendMode::Opaque,
+			topology: wgpu::PrimitiveTopology::TriangleList,
+			sample_count: 1,
+		}
+	}
+
+	#[test]
+	fn identical_configurations_share_a_key() {
+		assert_eq!(gui_key(), gui_key());
+
+		// Any difference in blend, topology, or sampling is a different pipeline
+		let mut blended = gui_key();
+		blended.blend = BlendMode::AlphaBlend;
+		assert_ne!(gui_key(), blended);
+
+		let mut multisampled = gui_key();
+		multisampled.sample_count = 4;
+		assert_ne!(gui_key(), multisampled);
+	}
+
+	#[test]
+	fn missing_shaders_do_not_create_a_pipeline() {
+		let (device, _queue) = match create_test_device() {
+			Some(pair) => pair,
+			None => return,
+		};
+		let mut manager = PipelineManager::new();
+		let shader_cache: ResourceCache<wgpu::ShaderModule> = ResourceCache::new();
+
+		assert!(manager.get_or_create(&device, wgpu::TextureFormat::Bgra8UnormSrgb, gui_key(), &shader_cache, Vec::new()).is_none());
+		assert_eq!(manager.len(), 0);
+	}
+}
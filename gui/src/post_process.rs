@@ -0,0 +1,172 @@
+use crate::shader_stage::{self, ShaderCompileError};
+use crate::texture::Texture;
+
+// The format the scene renders into while post-processing is on: half-float channels keep
+// highlights above 1.0 alive for the tone mapper instead of clamping them at the frame buffer
+pub const HDR_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
+// The tone-mapping settings as the shader's uniform block lays them out, padded to 16 bytes
+const SETTINGS_SIZE: wgpu::BufferAddress = 16;
+
+// The optional tone-mapping stage: the frame's passes render into an HDR color target, then a
+// full-screen triangle samples it through the tonemap shader into the swap chain
+// The triangle's corners come from the vertex index alone, so the pass binds no geometry buffers
+pub struct PostProcess {
+	// The HDR scene target the frame's other passes render into
+	pub target: Texture,
+	pipeline: wgpu::RenderPipeline,
+	bind_group_layout: wgpu::BindGroupLayout,
+	bind_group: wgpu::BindGroup,
+	// Holds [exposure, gamma, 0, 0]; see write_settings
+	settings: wgpu::Buffer,
+}
+
+impl PostProcess {
+	// Builds the HDR target at the frame's dimensions and the tonemap pipeline writing to the
+	// swap chain's format; exposure and gamma start at their neutral 1.0 and display 2.2
+	pub fn new(device: &wgpu::Device, width: u32, height: u32, output_format: wgpu::TextureFormat) -> Result<Self, ShaderCompileError> {
+		let vertex_shader = shader_stage::compile_from_glsl(device, "shaders/post.vert", glsl_to_spirv::ShaderType::Vertex)?;
+		let fragment_shader = shader_stage::compile_from_glsl(device, "shaders/post.frag", glsl_to_spirv::ShaderType::Fragment)?;
+
+		let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+			bindings: &[
+				wgpu::BindGroupLayoutEntry {
+					binding: 0,
+					visibility: wgpu::ShaderStage::FRAGMENT,
+					ty: wgpu::BindingType::SampledTexture {
+						multisampled: false,
+						dimension: wgpu::TextureViewDimension::D2,
+						component_type: wgpu::TextureComponentType::Float,
+					},
+				},
+				wgpu::BindGroupLayoutEntry {
+					binding: 1,
+					visibility: wgpu::ShaderStage::FRAGMENT,
+					ty: wgpu::BindingType::Sampler { comparison: false },
+				},
+				wgpu::BindGroupLayoutEntry {
+					binding: 2,
+					visibility: wgpu::ShaderStage::FRAGMENT,
+					ty: wgpu::BindingType::UniformBuffer { dynamic: false },
+				},
+			],
+			label: Some("post_process_bind_group_layout"),
+		});
+
+		let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+			bind_group_layouts: &[&bind_group_layout],
+		});
+
+		// Built by hand rather than through Pipeline: the pass reads no vertex buffers and attaches
+		// no depth buffer, neither of which the shared constructors can express
+		let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+			layout: &pipeline_layout,
+			vertex_stage: wgpu::ProgrammableStageDescriptor {
+				module: &vertex_shader,
+				entry_point: "main",
+			},
+			fragment_stage: Some(wgpu::ProgrammableStageDescriptor {
+				module: &fragment_shader,
+				entry_point: "main",
+			}),
+			rasterization_state: Some(wgpu::RasterizationStateDescriptor {
+				front_face: wgpu::FrontFace::Ccw,
+				cull_mode: wgpu::CullMode::None,
+				depth_bias: 0,
+				depth_bias_slope_scale: 0.,
+				depth_bias_clamp: 0.,
+			}),
+			primitive_topology: wgpu::PrimitiveTopology::TriangleList,
+			color_states: &[wgpu::ColorStateDescriptor {
+				format: output_format,
+				color_blend: wgpu::BlendDescriptor::REPLACE,
+				alpha_blend: wgpu::BlendDescriptor::REPLACE,
+				write_mask: wgpu::ColorWrite::ALL,
+			}],
+			depth_stencil_state: None,
+			vertex_state: wgpu::VertexStateDescriptor {
+				index_format: wgpu::IndexFormat::Uint16,
+				vertex_buffers: &[],
+			},
+			sample_count: 1,
+			sample_mask: !0,
+			alpha_to_coverage_enabled: false,
+		});
+
+		let settings = device.create_buffer_with_data(bytemuck::cast_slice(&[1.0f32, 2.2, 0., 0.]), wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST);
+
+		let target = Texture::render_target(device, width, height, HDR_FORMAT);
+		let bind_group = Self::create_bind_group(device, &bind_group_layout, &target, &settings);
+
+		Ok(Self {
+			target,
+			pipeline,
+			bind_group_layout,
+			bind_group,
+			settings,
+		})
+	}
+
+	fn create_bind_group(device: &wgpu::Device, layout: &wgpu::BindGroupLayout, target: &Texture, settings: &wgpu::Buffer) -> wgpu::BindGroup {
+		device.create_bind_group(&wgpu::BindGroupDescriptor {
+			layout,
+			bindings: &[
+				wgpu::Binding {
+					binding: 0,
+					resource: wgpu::BindingResource::TextureView(&target.view),
+				},
+				wgpu::Binding {
+					binding: 1,
+					resource: wgpu::BindingResource::Sampler(&target.sampler),
+				},
+				wgpu::Binding {
+					binding: 2,
+					resource: wgpu::BindingResource::Buffer {
+						buffer: settings,
+						range: 0..SETTINGS_SIZE,
+					},
+				},
+			],
+			label: Some("post_process_bind_group"),
+		})
+	}
+
+	// Rebuilds the HDR target at the given dimensions when the frame's size changed; the bind
+	// group follows since it references the replaced texture view
+	pub fn resize(&mut self, device: &wgpu::Device, width: u32, height: u32) {
+		if self.target.size() == (width, height) {
+			return;
+		}
+		self.target = Texture::render_target(device, width, height, HDR_FORMAT);
+		self.bind_group = Self::create_bind_group(device, &self.bind_group_layout, &self.target, &self.settings);
+	}
+
+	// Writes new tone-mapping settings by staging the bytes and copying them on the GPU timeline,
+	// the same way UniformBuffer updates its matrix
+	pub fn write_settings(&self, device: &wgpu::Device, queue: &mut wgpu::Queue, exposure: f32, gamma: f32) {
+		let staging = device.create_buffer_with_data(bytemuck::cast_slice(&[exposure, gamma, 0., 0.]), wgpu::BufferUsage::COPY_SRC);
+
+		let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("post_settings_encoder") });
+		encoder.copy_buffer_to_buffer(&staging, 0, &self.settings, 0, SETTINGS_SIZE);
+		queue.submit(&[encoder.finish()]);
+	}
+
+	// Records the tone-mapping pass: the full-screen triangle samples the HDR target into the frame
+	pub fn record(&self, encoder: &mut wgpu::CommandEncoder, frame_view: &wgpu::TextureView) {
+		let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+			color_attachments: &[wgpu::RenderPassColorAttachmentDescriptor {
+				attachment: frame_view,
+				resolve_target: None,
+				// Every frame pixel gets overwritten, so the previous contents need no load
+				load_op: wgpu::LoadOp::Clear,
+				store_op: wgpu::StoreOp::Store,
+				clear_color: wgpu::Color::BLACK,
+			}],
+			depth_stencil_attachment: None,
+		});
+
+		render_pass.set_pipeline(&self.pipeline);
+		render_pass.set_bind_group(0, &self.bind_group, &[]);
+		render_pass.draw(0..3, 0..1);
+	}
+}
@@ -0,0 +1,109 @@
+use crate::application::Application;
+use std::collections::HashSet;
+
+// Records one pass's commands into the shared encoder; the view is the frame being rendered to
+pub type RecordFn = Box<dyn Fn(&Application, &mut wgpu::CommandEncoder, &wgpu::TextureView)>;
+
+// One pass in the frame: what it depends on and how to record it
+pub struct RenderNode {
+	pub name: String,
+	// Names of nodes that must record before this one, e.g. the UI overlay depends on the scene
+	pub dependencies: Vec<String>,
+	pub record: RecordFn,
+}
+
+// Orders the frame's passes explicitly instead of hardcoding them in render()
+// Each node records into one shared encoder, so the whole frame still submits as a single command buffer
+pub struct RenderGraph {
+	nodes: Vec<RenderNode>,
+}
+
+impl RenderGraph {
+	pub fn new() -> Self {
+		Self { nodes: Vec::new() }
+	}
+
+	pub fn add_node(&mut self, node: RenderNode) {
+		debug_assert!(self.nodes.iter().all(|existing| existing.name != node.name), "Render graph node names must be unique");
+		self.nodes.push(node);
+	}
+
+	// The indices of the nodes in execution order: every node after its dependencies, and
+	// insertion order preserved between nodes that nothing orders relative to each other
+	pub fn execution_order(&self) -> Vec<usize> {
+		let mut order = Vec::with_capacity(self.nodes.len());
+		let mut recorded: HashSet<&str> = HashSet::new();
+
+		// Repeatedly take the first node whose dependencies are all recorded; insertion order breaks ties
+		let mut remaining: Vec<usize> = (0..self.nodes.len()).collect();
+		while !remaining.is_empty() {
+			let next = remaining
+				.iter()
+				.position(|&index| self.nodes[index].dependencies.iter().all(|dependency| recorded.contains(dependency.as_str())));
+
+			match next {
+				Some(position) => {
+					let index = remaining.remove(position);
+					recorded.insert(&self.nodes[index].name);
+					order.push(index);
+				}
+				// A dependency cycle (or a dependency on a missing node) can never resolve;
+				// run what remains in insertion order rather than dropping passes
+				None => {
+					order.append(&mut remaining);
+				}
+			}
+		}
+		order
+	}
+
+	// Records every node into the encoder, in dependency order
+	pub fn execute(&self, app: &Application, encoder: &mut wgpu::CommandEncoder, frame_view: &wgpu::TextureView) {
+		for index in self.execution_order() {
+			(self.nodes[index].record)(app, encoder, frame_view);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn node(name: &str, dependencies: &[&str]) -> RenderNode {
+		RenderNode {
+			name: String::from(name),
+			dependencies: dependencies.iter().map(|&dependency| String::from(dependency)).collect(),
+			record: Box::new(|_, _, _| {}),
+		}
+	}
+
+	#[test]
+	fn dependencies_run_before_their_dependents() {
+		let mut graph = RenderGraph::new();
+		graph.add_node(node("ui", &["main"]));
+		graph.add_node(node("main", &[]));
+
+		assert_eq!(graph.execution_order(), vec![1, 0]);
+	}
+
+	#[test]
+	fn unordered_nodes_keep_their_insertion_order() {
+		let mut graph = RenderGraph::new();
+		graph.add_node(node("main", &[]));
+		graph.add_node(node("post", &["main"]));
+		graph.add_node(node("overlay", &["main"]));
+
+		// post and overlay both wait on main but not on each other, so insertion order decides
+		assert_eq!(graph.execution_order(), vec![0, 1, 2]);
+	}
+
+	#[test]
+	fn a_dependency_cycle_still_runs_every_node() {
+		let mut graph = RenderGraph::new();
+		graph.add_node(node("a", &["b"]));
+		graph.add_node(node("b", &["a"]));
+
+		let order = graph.execution_order();
+		assert_eq!(order.len(), 2);
+	}
+}
@@ -0,0 +1,277 @@
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+
+// Lets a cache account for how much GPU memory its entries hold, so it can be bounded by a byte
+// budget instead of an entry count; sizes are approximate and recorded at resource construction
+pub trait SizedResource {
+	fn byte_size(&self) -> u64;
+}
+
+// How a cache measures one entry's GPU footprint when a byte budget is set
+type SizeOf<T> = fn(&T) -> u64;
+
+// Stores GPU resources (shaders, pipelines, textures) by name so they are only built once
+// An optional capacity bounds the cache, evicting the least-recently-used entry when full
+pub struct ResourceCache<T> {
+	cache: HashMap<String, T>,
+	capacity: Option<usize>,
+	// The budget pairs with a size function captured at construction, where the SizedResource
+	// bound is known, so set stays available to caches of resources without a meaningful size
+	byte_budget: Option<(u64, SizeOf<T>)>,
+	access_counter: Cell<u64>,
+	last_used: RefCell<HashMap<String, u64>>,
+}
+
+impl<T> ResourceCache<T> {
+	pub fn new() -> Self {
+		Self {
+			cache: HashMap::new(),
+			capacity: None,
+			byte_budget: None,
+			access_counter: Cell::new(0),
+			last_used: RefCell::new(HashMap::new()),
+		}
+	}
+
+	pub fn with_capacity(max: usize) -> Self {
+		assert!(max > 0, "A bounded resource cache must hold at least one entry");
+
+		Self {
+			cache: HashMap::new(),
+			capacity: Some(max),
+			byte_budget: None,
+			access_counter: Cell::new(0),
+			last_used: RefCell::new(HashMap::new()),
+		}
+	}
+
+	pub fn get(&self, key: &str) -> Option<&T> {
+		let resource = self.cache.get(key);
+
+		// Reading an entry marks it most-recently-used so eviction prefers stale entries
+		if resource.is_some() {
+			self.touch(key);
+		}
+
+		resource
+	}
+
+	pub fn set(&mut self, key: &str, resource: T) {
+		// When inserting a new key into a full cache, make room by evicting the least-recently-used entry
+		if let Some(capacity) = self.capacity {
+			if !self.cache.contains_key(key) && self.cache.len() >= capacity {
+				self.evict_least_recently_used();
+			}
+		}
+
+		self.cache.insert(String::from(key), resource);
+		self.touch(key);
+
+		// A byte budget evicts stale entries until the total fits again; the entry just inserted is
+		// most-recently-used and always survives, even when it alone exceeds the budget
+		if let Some((budget, byte_size)) = self.byte_budget {
+			while self.cache.len() > 1 && self.cache.values().map(byte_size).sum::<u64>() > budget {
+				self.evict_least_recently_used();
+			}
+		}
+	}
+
+	// Drops an entry from the cache, returning it so the caller can inspect or explicitly free it
+	pub fn remove(&mut self, key: &str) -> Option<T> {
+		self.last_used.borrow_mut().remove(key);
+		self.cache.remove(key)
+	}
+
+	// The keys of every cached entry, e.g. the source paths resources can be rebuilt from
+	// Listing the keys is not an access, so it leaves the recency order untouched
+	pub fn keys(&self) -> Vec<String> {
+		self.cache.keys().cloned().collect()
+	}
+
+	pub fn clear(&mut self) {
+		self.cache.clear();
+		self.last_used.borrow_mut().clear();
+	}
+
+	pub fn len(&self) -> usize {
+		self.cache.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.cache.is_empty()
+	}
+
+	fn touch(&self, key: &str) {
+		let next = self.access_counter.get() + 1;
+		self.access_counter.set(next);
+		self.last_used.borrow_mut().insert(String::from(key), next);
+	}
+
+	fn evict_least_recently_used(&mut self) {
+		let stalest = self.last_used.borrow().iter().filter(|(key, _)| self.cache.contains_key(*key)).min_by_key(|(_, used)| **used).map(|(key, _)| key.clone());
+
+		if let Some(key) = stalest {
+			self.cache.remove(&key);
+			self.last_used.borrow_mut().remove(&key);
+		}
+	}
+}
+
+impl<T: SizedResource> ResourceCache<T> {
+	// Bounds the cache by approximate GPU memory instead of entry count, which treats one large
+	// texture and many small ones fairly; inserting past the budget evicts least-recently-used
+	pub fn with_byte_budget(max_bytes: u64) -> Self {
+		Self {
+			cache: HashMap::new(),
+			capacity: None,
+			byte_budget: Some((max_bytes, T::byte_size)),
+			access_counter: Cell::new(0),
+			last_used: RefCell::new(HashMap::new()),
+		}
+	}
+
+	// The approximate GPU memory held by every cached entry
+	pub fn total_bytes(&self) -> u64 {
+		self.cache.values().map(T::byte_size).sum()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn unbounded_cache_keeps_everything() {
+		let mut cache = ResourceCache::new();
+		for i in 0..100 {
+			cache.set(&format!("key{}", i), i);
+		}
+
+		assert_eq!(cache.get("key0"), Some(&0));
+		assert_eq!(cache.get("key99"), Some(&99));
+	}
+
+	#[test]
+	fn bounded_cache_evicts_least_recently_used() {
+		let mut cache = ResourceCache::with_capacity(3);
+		cache.set("a", 1);
+		cache.set("b", 2);
+		cache.set("c", 3);
+
+		// Inserting a fourth entry evicts "a", the oldest never-accessed entry
+		cache.set("d", 4);
+
+		assert_eq!(cache.get("a"), None);
+		assert_eq!(cache.get("b"), Some(&2));
+		assert_eq!(cache.get("d"), Some(&4));
+	}
+
+	#[test]
+	fn remove_returns_the_entry() {
+		let mut cache = ResourceCache::new();
+		cache.set("a", 1);
+
+		assert_eq!(cache.remove("a"), Some(1));
+		assert_eq!(cache.remove("a"), None);
+		assert_eq!(cache.remove("missing"), None);
+		assert!(cache.is_empty());
+	}
+
+	#[test]
+	fn keys_list_every_entry_without_touching_recency() {
+		let mut cache = ResourceCache::with_capacity(2);
+		cache.set("a", 1);
+		cache.set("b", 2);
+
+		let mut keys = cache.keys();
+		keys.sort();
+		assert_eq!(keys, vec!["a", "b"]);
+
+		// Listing keys did not refresh "a", so it is still the eviction candidate
+		cache.set("c", 3);
+		assert_eq!(cache.get("a"), None);
+	}
+
+	#[test]
+	fn clear_empties_the_cache() {
+		let mut cache = ResourceCache::new();
+		cache.set("a", 1);
+		cache.set("b", 2);
+		assert_eq!(cache.len(), 2);
+
+		cache.clear();
+		assert!(cache.is_empty());
+		assert_eq!(cache.get("a"), None);
+
+		// Clearing an already-empty cache is a no-op
+		cache.clear();
+		assert!(cache.is_empty());
+	}
+
+	// A stand-in resource whose byte size is just its value, so budgets are easy to reason about
+	struct Blob(u64);
+
+	impl SizedResource for Blob {
+		fn byte_size(&self) -> u64 {
+			self.0
+		}
+	}
+
+	#[test]
+	fn total_bytes_sums_every_entry() {
+		let mut cache = ResourceCache::with_byte_budget(1000);
+		assert_eq!(cache.total_bytes(), 0);
+
+		cache.set("a", Blob(300));
+		cache.set("b", Blob(500));
+		assert_eq!(cache.total_bytes(), 800);
+
+		cache.remove("a");
+		assert_eq!(cache.total_bytes(), 500);
+	}
+
+	#[test]
+	fn a_byte_budget_evicts_least_recently_used_until_under_budget() {
+		let mut cache = ResourceCache::with_byte_budget(1000);
+		cache.set("a", Blob(400));
+		cache.set("b", Blob(400));
+
+		// Touch "a" so "b" is the stalest entry when the next insert overflows the budget
+		assert!(cache.get("a").is_some());
+		cache.set("c", Blob(400));
+
+		assert!(cache.get("b").is_none());
+		assert_eq!(cache.total_bytes(), 800);
+
+		// A single insert can evict several entries when the newcomer is large
+		cache.set("d", Blob(900));
+		assert_eq!(cache.len(), 1);
+		assert_eq!(cache.total_bytes(), 900);
+	}
+
+	#[test]
+	fn the_newest_entry_survives_even_when_it_alone_exceeds_the_budget() {
+		let mut cache = ResourceCache::with_byte_budget(100);
+		cache.set("small", Blob(50));
+		cache.set("huge", Blob(5000));
+
+		// Evicting everything else could not get under budget, so the insert still sticks
+		assert!(cache.get("huge").is_some());
+		assert_eq!(cache.len(), 1);
+	}
+
+	#[test]
+	fn get_refreshes_recency() {
+		let mut cache = ResourceCache::with_capacity(2);
+		cache.set("a", 1);
+		cache.set("b", 2);
+
+		// Touch "a" so that "b" becomes the eviction candidate
+		assert_eq!(cache.get("a"), Some(&1));
+		cache.set("c", 3);
+
+		assert_eq!(cache.get("a"), Some(&1));
+		assert_eq!(cache.get("b"), None);
+		assert_eq!(cache.get("c"), Some(&3));
+	}
+}
@@ -0,0 +1,256 @@
+use std::collections::hash_map::DefaultHasher;
+use std::fmt;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::Path;
+
+// A failed shader build, locating the error in the original source file where possible
+#[derive(Debug)]
+pub struct ShaderCompileError {
+	pub path: String,
+	pub shader_type: glsl_to_spirv::ShaderType,
+	pub line: Option<u32>,
+	pub column: Option<u32>,
+	pub message: String,
+}
+
+impl fmt::Display for ShaderCompileError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match (self.line, self.column) {
+			(Some(line), Some(column)) => write!(f, "{}:{}:{}: {}", self.path, line, column, self.message),
+			(Some(line), None) => write!(f, "{}:{}: {}", self.path, line, self.message),
+			_ => write!(f, "{}: {}", self.path, self.message),
+		}
+	}
+}
+
+impl std::error::Error for ShaderCompileError {}
+
+// Any failure to turn a shader source file into a shader module
+#[derive(Debug)]
+pub enum ShaderError {
+	Compile(ShaderCompileError),
+	Wgsl { path: String, message: String },
+	UnknownExtension(String),
+}
+
+impl fmt::Display for ShaderError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			ShaderError::Compile(error) => error.fmt(f),
+			ShaderError::Wgsl { path, message } => write!(f, "{}: {}", path, message),
+			ShaderError::UnknownExtension(path) => write!(f, "{}: unknown shader file extension", path),
+		}
+	}
+}
+
+impl std::error::Error for ShaderError {}
+
+impl From<ShaderCompileError> for ShaderError {
+	fn from(error: ShaderCompileError) -> Self {
+		ShaderError::Compile(error)
+	}
+}
+
+// Loads any supported shader source file, dispatching on its extension (.vert/.frag/.glsl compile through glsl_to_spirv, .wgsl through naga)
+pub fn load_shader(device: &wgpu::Device, path: &str) -> Result<wgpu::ShaderModule, ShaderError> {
+	match Path::new(path).extension().and_then(|extension| extension.to_str()) {
+		Some("vert") => Ok(compile_from_glsl(device, path, glsl_to_spirv::ShaderType::Vertex)?),
+		Some("frag") => Ok(compile_from_glsl(device, path, glsl_to_spirv::ShaderType::Fragment)?),
+		Some("comp") => Ok(compile_from_glsl(device, path, glsl_to_spirv::ShaderType::Compute)?),
+		// A bare .glsl file carries its stage in the preceding extension, e.g. "panel.vert.glsl"
+		Some("glsl") => match detect_glsl_stage(path) {
+			Some(shader_type) => Ok(compile_from_glsl(device, path, shader_type)?),
+			None => Err(ShaderError::UnknownExtension(String::from(path))),
+		},
+		Some("wgsl") => compile_from_wgsl(device, path),
+		_ => Err(ShaderError::UnknownExtension(String::from(path))),
+	}
+}
+
+// Compiles a WGSL shader through naga's SPIR-V back end, avoiding the external GLSL compiler entirely
+pub fn compile_from_wgsl(device: &wgpu::Device, path: &str) -> Result<wgpu::ShaderModule, ShaderError> {
+	let source = fs::read_to_string(path).map_err(|error| ShaderError::Wgsl {
+		path: String::from(path),
+		message: error.to_string(),
+	})?;
+
+	let module = naga::front::wgsl::parse_str(&source).map_err(|error| ShaderError::Wgsl {
+		path: String::from(path),
+		message: format!("{:?}", error),
+	})?;
+
+	let spirv = naga::back::spv::Writer::new(&module.header, naga::back::spv::WriterFlags::NONE).write(&module);
+
+	Ok(device.create_shader_module(&spirv))
+}
+
+fn detect_glsl_stage(path: &str) -> Option<glsl_to_spirv::ShaderType> {
+	let stem = Path::new(path).file_stem()?.to_str()?;
+	if stem.ends_with(".vert") {
+		Some(glsl_to_spirv::ShaderType::Vertex)
+	} else if stem.ends_with(".frag") {
+		Some(glsl_to_spirv::ShaderType::Fragment)
+	} else if stem.ends_with(".comp") {
+		Some(glsl_to_spirv::ShaderType::Compute)
+	} else {
+		None
+	}
+}
+
+// Where compiled SPIR-V bytecode is cached between runs unless the caller picks another directory
+pub const DEFAULT_SHADER_CACHE_DIR: &str = "target/shader-cache";
+
+// Compiles a GLSL shader from a source file on disk into a shader module on the GPU, using the default bytecode cache
+pub fn compile_from_glsl(device: &wgpu::Device, path: &str, shader_type: glsl_to_spirv::ShaderType) -> Result<wgpu::ShaderModule, ShaderCompileError> {
+	compile_from_glsl_cached(device, path, shader_type, Path::new(DEFAULT_SHADER_CACHE_DIR))
+}
+
+// Compiles a GLSL shader, loading previously compiled SPIR-V from the cache directory when the source is unchanged
+pub fn compile_from_glsl_cached(device: &wgpu::Device, path: &str, shader_type: glsl_to_spirv::ShaderType, cache_dir: &Path) -> Result<wgpu::ShaderModule, ShaderCompileError> {
+	// Read the GLSL source code from the file
+	let source = fs::read_to_string(path).map_err(|error| ShaderCompileError {
+		path: String::from(path),
+		shader_type: clone_shader_type(&shader_type),
+		line: None,
+		column: None,
+		message: error.to_string(),
+	})?;
+
+	// A matching cached .spv means the exact same source and stage were compiled before
+	let cached_path = cache_dir.join(format!("{:016x}.spv", hash_source(&source, &shader_type)));
+	if let Ok(bytes) = fs::read(&cached_path) {
+		if let Ok(spirv) = wgpu::read_spirv(std::io::Cursor::new(&bytes)) {
+			return Ok(device.create_shader_module(&spirv));
+		}
+	}
+
+	// Compile the GLSL source code into SPIR-V bytecode
+	let spirv_output = glsl_to_spirv::compile(&source, clone_shader_type(&shader_type)).map_err(|raw| {
+		let (line, column, message) = parse_diagnostic(&raw);
+		ShaderCompileError {
+			path: String::from(path),
+			shader_type: clone_shader_type(&shader_type),
+			line,
+			column,
+			message,
+		}
+	})?;
+
+	// Read the compiled bytecode into a word buffer that wgpu understands
+	let spirv = wgpu::read_spirv(spirv_output).map_err(|error| ShaderCompileError {
+		path: String::from(path),
+		shader_type,
+		line: None,
+		column: None,
+		message: error.to_string(),
+	})?;
+
+	// Persist the bytecode for the next run; failing to write the cache is not a compile failure
+	if fs::create_dir_all(cache_dir).is_ok() {
+		let mut bytes = Vec::with_capacity(spirv.len() * 4);
+		for word in &spirv {
+			bytes.extend_from_slice(&word.to_le_bytes());
+		}
+		let _ = fs::write(&cached_path, bytes);
+	}
+
+	// Hand the bytecode to the GPU driver to build a shader module
+	Ok(device.create_shader_module(&spirv))
+}
+
+// Hashes the source text together with the shader stage, so editing either invalidates the cache entry
+fn hash_source(source: &str, shader_type: &glsl_to_spirv::ShaderType) -> u64 {
+	let mut hasher = DefaultHasher::new();
+	source.hash(&mut hasher);
+	format!("{:?}", shader_type).hash(&mut hasher);
+	hasher.finish()
+}
+
+// glsl_to_spirv::ShaderType does not implement Clone, so rebuild the variant by hand
+fn clone_shader_type(shader_type: &glsl_to_spirv::ShaderType) -> glsl_to_spirv::ShaderType {
+	match shader_type {
+		glsl_to_spirv::ShaderType::Vertex => glsl_to_spirv::ShaderType::Vertex,
+		glsl_to_spirv::ShaderType::Fragment => glsl_to_spirv::ShaderType::Fragment,
+		glsl_to_spirv::ShaderType::Geometry => glsl_to_spirv::ShaderType::Geometry,
+		glsl_to_spirv::ShaderType::TessellationControl => glsl_to_spirv::ShaderType::TessellationControl,
+		glsl_to_spirv::ShaderType::TessellationEvaluation => glsl_to_spirv::ShaderType::TessellationEvaluation,
+		glsl_to_spirv::ShaderType::Compute => glsl_to_spirv::ShaderType::Compute,
+	}
+}
+
+// Pulls the line and column out of a glslang diagnostic such as "ERROR: 0:12: 'foo' : undeclared identifier"
+fn parse_diagnostic(raw: &str) -> (Option<u32>, Option<u32>, String) {
+	for diagnostic_line in raw.lines() {
+		let rest = match diagnostic_line.trim().strip_prefix("ERROR: ") {
+			Some(rest) => rest,
+			None => continue,
+		};
+
+		// glslang reports "<source string>:<line>:" where the source string index is useless here
+		let mut parts = rest.splitn(3, ':');
+		let column = parts.next().and_then(|part| part.trim().parse::<u32>().ok());
+		let line = parts.next().and_then(|part| part.trim().parse::<u32>().ok());
+		if let (Some(line), Some(message)) = (line, parts.next()) {
+			return (Some(line), column.filter(|&column| column > 0), String::from(message.trim()));
+		}
+
+		return (None, None, String::from(rest.trim()));
+	}
+
+	(None, None, String::from(raw.trim()))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parses_glslang_line_numbers() {
+		let (line, column, message) = parse_diagnostic("ERROR: 0:12: 'foo' : undeclared identifier");
+		assert_eq!(line, Some(12));
+		assert_eq!(column, None);
+		assert_eq!(message, "'foo' : undeclared identifier");
+	}
+
+	#[test]
+	fn passes_through_unstructured_errors() {
+		let (line, column, message) = parse_diagnostic("compilation failed for unknown reasons");
+		assert_eq!(line, None);
+		assert_eq!(column, None);
+		assert_eq!(message, "compilation failed for unknown reasons");
+	}
+
+	#[test]
+	fn second_compile_hits_the_disk_cache() {
+		let (device, _queue) = match crate::test_utils::create_test_device() {
+			Some(pair) => pair,
+			None => return,
+		};
+		let cache_dir = std::env::temp_dir().join("graphite-shader-cache-test");
+		let _ = fs::remove_dir_all(&cache_dir);
+
+		compile_from_glsl_cached(&device, "shaders/shader.vert", glsl_to_spirv::ShaderType::Vertex, &cache_dir).unwrap();
+
+		// Exactly one .spv should have been written for the compiled stage
+		let cached: Vec<_> = fs::read_dir(&cache_dir).unwrap().map(|entry| entry.unwrap().path()).collect();
+		assert_eq!(cached.len(), 1);
+		let modified_after_first = fs::metadata(&cached[0]).unwrap().modified().unwrap();
+
+		// The second compile must read the cached bytecode rather than rewriting it
+		compile_from_glsl_cached(&device, "shaders/shader.vert", glsl_to_spirv::ShaderType::Vertex, &cache_dir).unwrap();
+		assert_eq!(fs::metadata(&cached[0]).unwrap().modified().unwrap(), modified_after_first);
+	}
+
+	#[test]
+	fn display_reads_like_a_compiler_diagnostic() {
+		let error = ShaderCompileError {
+			path: String::from("shaders/shader.frag"),
+			shader_type: glsl_to_spirv::ShaderType::Fragment,
+			line: Some(12),
+			column: None,
+			message: String::from("'foo' undeclared"),
+		};
+		assert_eq!(error.to_string(), "shaders/shader.frag:12: 'foo' undeclared");
+	}
+}
@@ -0,0 +1,55 @@
+use notify::{DebouncedEvent, RecursiveMode, Watcher};
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::time::Duration;
+
+// Watches shader source files on disk and reports which cached shaders need recompiling
+pub struct ShaderWatcher {
+	watcher: notify::RecommendedWatcher,
+	receiver: mpsc::Receiver<DebouncedEvent>,
+	watched: Vec<(PathBuf, String)>,
+}
+
+impl ShaderWatcher {
+	pub fn new() -> Result<Self, notify::Error> {
+		let (sender, receiver) = mpsc::channel();
+		let watcher = notify::watcher(sender, Duration::from_millis(250))?;
+
+		Ok(Self {
+			watcher,
+			receiver,
+			watched: Vec::new(),
+		})
+	}
+
+	// Begin watching a shader source file, remembering the cache key it was loaded under
+	pub fn watch(&mut self, cache_key: &str) -> Result<(), notify::Error> {
+		self.watcher.watch(cache_key, RecursiveMode::NonRecursive)?;
+
+		// The watcher reports canonical paths, so remember the canonical form alongside the cache key
+		let canonical = std::fs::canonicalize(cache_key).unwrap_or_else(|_| PathBuf::from(cache_key));
+		self.watched.push((canonical, String::from(cache_key)));
+
+		Ok(())
+	}
+
+	// Drain pending file system events and translate them back into shader cache keys
+	pub fn poll_changed(&self) -> Vec<String> {
+		let mut changed = Vec::new();
+
+		while let Ok(event) = self.receiver.try_recv() {
+			let path = match event {
+				DebouncedEvent::Write(path) | DebouncedEvent::Create(path) => path,
+				_ => continue,
+			};
+
+			if let Some((_, key)) = self.watched.iter().find(|(watched_path, _)| *watched_path == path) {
+				if !changed.contains(key) {
+					changed.push(key.clone());
+				}
+			}
+		}
+
+		changed
+	}
+}
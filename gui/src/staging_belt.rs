@@ -0,0 +1,154 @@
+use futures::executor::block_on;
+
+// How large each staging chunk is unless a single upload needs more
+const DEFAULT_CHUNK_SIZE: wgpu::BufferAddress = 64 * 1024;
+
+// A reusable staging buffer the belt cycles through its lifecycle states
+struct Chunk {
+	buffer: wgpu::Buffer,
+	size: wgpu::BufferAddress,
+}
+
+// A ring of reusable staging buffers for streaming data into GPU buffers without allocating
+// a one-shot staging buffer per upload (this wgpu predates wgpu::util::StagingBelt)
+//
+// The lifecycle each frame is:
+//   1. write_buffer(..) any number of times while recording an encoder
+//   2. finish() once the encoder is done, retiring the used chunks
+//   3. submit the encoder to the queue
+//   4. recall() to make the retired chunks reusable for the next frame
+pub struct StagingBelt {
+	chunk_size: wgpu::BufferAddress,
+	// Chunks used since the last finish(), referenced by the encoder being recorded
+	active: Vec<Chunk>,
+	// Chunks referenced by submitted work, not yet safe to refill
+	in_flight: Vec<Chunk>,
+	// Chunks free to serve the next upload
+	free: Vec<Chunk>,
+}
+
+impl StagingBelt {
+	pub fn new() -> Self {
+		StagingBelt::with_chunk_size(DEFAULT_CHUNK_SIZE)
+	}
+
+	pub fn with_chunk_size(chunk_size: wgpu::BufferAddress) -> Self {
+		Self {
+			chunk_size,
+			active: Vec::new(),
+			in_flight: Vec::new(),
+			free: Vec::new(),
+		}
+	}
+
+	// Stages `data` and records a copy of it into `target` at `target_offset`
+	// The copy executes when the encoder's commands are submitted
+	pub fn write_buffer(&mut self, device: &wgpu::Device, encoder: &mut wgpu::CommandEncoder, target: &wgpu::Buffer, target_offset: wgpu::BufferAddress, data: &[u8]) {
+		let size = data.len() as wgpu::BufferAddress;
+
+		let chunk = match self.free.iter().position(|chunk| chunk.size >= size) {
+			Some(index) => {
+				// Refill a recycled chunk; remapping blocks, but only until the GPU finished the
+				// previously submitted frame that read from it
+				let chunk = self.free.swap_remove(index);
+				let mapping_future = chunk.buffer.map_write(0, size);
+				device.poll(wgpu::Maintain::Wait);
+				let mut mapping = block_on(mapping_future).expect("Failed to remap a staging belt chunk");
+				mapping.as_slice().copy_from_slice(data);
+				drop(mapping);
+				chunk.buffer.unmap();
+				chunk
+			}
+			None => {
+				// No free chunk is big enough; allocate one, oversized uploads get their own chunk
+				let alloc_size = self.chunk_size.max(size);
+				let mapped = device.create_buffer_mapped(&wgpu::BufferDescriptor {
+					label: Some("staging_belt_chunk"),
+					size: alloc_size,
+					usage: wgpu::BufferUsage::COPY_SRC | wgpu::BufferUsage::MAP_WRITE,
+				});
+				mapped.data[..data.len()].copy_from_slice(data);
+				Chunk {
+					buffer: mapped.finish(),
+					size: alloc_size,
+				}
+			}
+		};
+
+		encoder.copy_buffer_to_buffer(&chunk.buffer, 0, target, target_offset, size);
+		self.active.push(chunk);
+	}
+
+	// Retires the chunks the just-finished encoder references; call before submitting it
+	pub fn finish(&mut self) {
+		self.in_flight.append(&mut self.active);
+	}
+
+	// Makes retired chunks reusable again; call after the encoder has been submitted
+	pub fn recall(&mut self) {
+		self.free.append(&mut self.in_flight);
+	}
+
+	// How many chunks are free to serve the next upload
+	pub fn free_chunks(&self) -> usize {
+		self.free.len()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::test_utils::create_test_device;
+
+	#[test]
+	fn uploads_land_in_the_target_buffer() {
+		let (device, queue) = match create_test_device() {
+			Some(pair) => pair,
+			None => return,
+		};
+		let mut belt = StagingBelt::new();
+
+		let target = device.create_buffer(&wgpu::BufferDescriptor {
+			label: None,
+			size: 4,
+			usage: wgpu::BufferUsage::COPY_DST | wgpu::BufferUsage::MAP_READ,
+		});
+
+		let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+		belt.write_buffer(&device, &mut encoder, &target, 0, &[1, 2, 3, 4]);
+		belt.finish();
+		queue.submit(&[encoder.finish()]);
+		belt.recall();
+
+		let mapping_future = target.map_read(0, 4);
+		device.poll(wgpu::Maintain::Wait);
+		let mapping = block_on(mapping_future).expect("Failed to map the target buffer");
+		assert_eq!(mapping.as_slice(), &[1, 2, 3, 4]);
+	}
+
+	#[test]
+	fn recalled_chunks_are_reused_instead_of_allocating() {
+		let (device, queue) = match create_test_device() {
+			Some(pair) => pair,
+			None => return,
+		};
+		let mut belt = StagingBelt::with_chunk_size(64);
+
+		let target = device.create_buffer(&wgpu::BufferDescriptor {
+			label: None,
+			size: 64,
+			usage: wgpu::BufferUsage::COPY_DST,
+		});
+
+		for _ in 0..3 {
+			let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+			belt.write_buffer(&device, &mut encoder, &target, 0, &[7u8; 16]);
+			belt.finish();
+			queue.submit(&[encoder.finish()]);
+			belt.recall();
+		}
+
+		// The same single chunk cycled through all three frames
+		assert_eq!(belt.free_chunks(), 1);
+	}
+}
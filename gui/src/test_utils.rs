@@ -0,0 +1,19 @@
+use futures::executor::block_on;
+
+// Requests a headless device and queue for tests that exercise GPU resource creation
+// Returns None on machines without any compatible adapter (e.g. headless CI); callers should
+// return early so the test passes as a skip rather than failing on hardware it cannot control
+pub fn create_test_device() -> Option<(wgpu::Device, wgpu::Queue)> {
+	let adapter = block_on(wgpu::Adapter::request(
+		&wgpu::RequestAdapterOptions {
+			power_preference: wgpu::PowerPreference::Default,
+			compatible_surface: None,
+		},
+		wgpu::BackendBit::PRIMARY,
+	))?;
+
+	Some(block_on(adapter.request_device(&wgpu::DeviceDescriptor {
+		extensions: wgpu::Extensions { anisotropic_filtering: false },
+		limits: wgpu::Limits::default(),
+	})))
+}
@@ -0,0 +1,155 @@
+use crate::geometry::Rect;
+use crate::texture::Texture;
+use crate::texture_atlas::ShelfPacker;
+use rusttype::{point, Font, Scale};
+use std::collections::HashMap;
+use std::fmt;
+
+// The dimensions of the square glyph atlas texture
+pub const ATLAS_SIZE: u32 = 1024;
+
+#[derive(Debug)]
+pub enum TextError {
+	Io(std::io::Error),
+	InvalidFont,
+	AtlasFull,
+}
+
+impl fmt::Display for TextError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			TextError::Io(error) => write!(f, "Failed to read the font file: {}", error),
+			TextError::InvalidFont => write!(f, "The font data could not be parsed"),
+			TextError::AtlasFull => write!(f, "The glyph atlas is full; no space remains for new glyphs"),
+		}
+	}
+}
+
+impl std::error::Error for TextError {}
+
+// Identifies a rasterized glyph in the atlas: a character at an integer pixel size
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct GlyphKey {
+	character: char,
+	size: u32,
+}
+
+// Where a rasterized glyph lives in the atlas and how to place its quad relative to the pen
+#[derive(Debug, Clone, Copy)]
+struct AtlasGlyph {
+	uv_min: [f32; 2],
+	uv_max: [f32; 2],
+	width: f32,
+	height: f32,
+	// Offset from the pen position (on the baseline) to the quad's top-left corner
+	bearing: (f32, f32),
+}
+
+// A positioned glyph ready to be turned into vertices: its on-screen rectangle in logical
+// pixels and where to sample it from in the atlas
+#[derive(Debug, Clone, Copy)]
+pub struct GlyphQuad {
+	pub rect: Rect,
+	pub uv_min: [f32; 2],
+	pub uv_max: [f32; 2],
+}
+
+// Rasterizes glyphs on demand into a shared atlas texture and lays out strings as quads sampling it
+pub struct TextRenderer {
+	font: Font<'static>,
+	pub atlas: Texture,
+	packer: ShelfPacker,
+	glyphs: HashMap<GlyphKey, AtlasGlyph>,
+}
+
+impl TextRenderer {
+	pub fn from_file(device: &wgpu::Device, path: &str) -> Result<Self, TextError> {
+		let bytes = std::fs::read(path).map_err(TextError::Io)?;
+		TextRenderer::new(device, bytes)
+	}
+
+	pub fn new(device: &wgpu::Device, font_bytes: Vec<u8>) -> Result<Self, TextError> {
+		let font = Font::try_from_vec(font_bytes).ok_or(TextError::InvalidFont)?;
+		let atlas = Texture::empty(device, ATLAS_SIZE, ATLAS_SIZE, wgpu::TextureFormat::Rgba8UnormSrgb);
+		Ok(Self {
+			font,
+			atlas,
+			packer: ShelfPacker::new(ATLAS_SIZE, ATLAS_SIZE),
+			glyphs: HashMap::new(),
+		})
+	}
+
+	// Lays out a string with its baseline pen starting at (x, y) in logical pixels, rasterizing
+	// any glyphs not already in the atlas, and returns one quad per visible glyph
+	pub fn layout(&mut self, device: &wgpu::Device, queue: &mut wgpu::Queue, text: &str, x: f32, y: f32, size: f32) -> Result<Vec<GlyphQuad>, TextError> {
+		let scale = Scale::uniform(size);
+		let mut quads = Vec::new();
+		let mut pen_x = x;
+		let mut previous: Option<char> = None;
+
+		for character in text.chars() {
+			if let Some(previous) = previous {
+				pen_x += self.font.pair_kerning(scale, previous, character);
+			}
+
+			if let Some(glyph) = self.ensure_glyph(device, queue, character, size as u32)? {
+				quads.push(GlyphQuad {
+					rect: Rect::new(pen_x + glyph.bearing.0, y + glyph.bearing.1, glyph.width, glyph.height),
+					uv_min: glyph.uv_min,
+					uv_max: glyph.uv_max,
+				});
+			}
+
+			pen_x += self.font.glyph(character).scaled(scale).h_metrics().advance_width;
+			previous = Some(character);
+		}
+		Ok(quads)
+	}
+
+	// Returns the glyph's atlas placement, rasterizing and uploading it on the first request
+	// Whitespace and other glyphs without an outline yield None
+	fn ensure_glyph(&mut self, device: &wgpu::Device, queue: &mut wgpu::Queue, character: char, size: u32) -> Result<Option<AtlasGlyph>, TextError> {
+		let key = GlyphKey { character, size };
+		if let Some(&glyph) = self.glyphs.get(&key) {
+			return Ok(Some(glyph));
+		}
+
+		let positioned = self.font.glyph(character).scaled(Scale::uniform(size as f32)).positioned(point(0., 0.));
+		let bounds = match positioned.pixel_bounding_box() {
+			Some(bounds) => bounds,
+			None => return Ok(None),
+		};
+		let (width, height) = (bounds.width() as u32, bounds.height() as u32);
+
+		// Reserve an extra pixel on each axis as a gutter so sampling cannot bleed between glyphs
+		let (atlas_x, atlas_y) = self.packer.allocate(width + 1, height + 1).ok_or(TextError::AtlasFull)?;
+
+		// White RGB with the glyph's coverage in alpha, so alpha blending shapes the text cleanly
+		let mut pixels = vec![0u8; (width * height * 4) as usize];
+		positioned.draw(|glyph_x, glyph_y, coverage| {
+			let offset = ((glyph_y * width + glyph_x) * 4) as usize;
+			pixels[offset] = 255;
+			pixels[offset + 1] = 255;
+			pixels[offset + 2] = 255;
+			pixels[offset + 3] = (coverage * 255.) as u8;
+		});
+		self.upload_glyph(device, queue, atlas_x, atlas_y, width, height, &pixels);
+
+		let atlas_size = ATLAS_SIZE as f32;
+		let glyph = AtlasGlyph {
+			uv_min: [atlas_x as f32 / atlas_size, atlas_y as f32 / atlas_size],
+			uv_max: [(atlas_x + width) as f32 / atlas_size, (atlas_y + height) as f32 / atlas_size],
+			width: width as f32,
+			height: height as f32,
+			bearing: (bounds.min.x as f32, bounds.min.y as f32),
+		};
+		self.glyphs.insert(key, glyph);
+		Ok(Some(glyph))
+	}
+
+	// Copies rasterized glyph pixels into their reserved atlas region
+	#[allow(clippy::too_many_arguments)]
+	fn upload_glyph(&self, device: &wgpu::Device, queue: &mut wgpu::Queue, x: u32, y: u32, width: u32, height: u32, pixels: &[u8]) {
+		crate::texture_atlas::upload_region(device, queue, &self.atlas, x, y, width, height, pixels);
+	}
+}
@@ -0,0 +1,1075 @@
+use std::fmt;
+
+#[derive(Debug)]
+pub enum TextureError {
+	Io(std::io::Error),
+	Image(image::ImageError),
+	PixelDataSize { expected: usize, actual: usize },
+	UnsupportedFormat(wgpu::TextureFormat),
+	EmptySequence,
+	InvalidDds(&'static str),
+	UnsupportedDdsFormat(String),
+	CompressionUnsupported,
+}
+
+impl fmt::Display for TextureError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			TextureError::Io(error) => write!(f, "Failed to read texture file: {}", error),
+			TextureError::Image(error) => write!(f, "Failed to decode texture: {}", error),
+			TextureError::PixelDataSize { expected, actual } => write!(f, "Pixel data is {} bytes but the dimensions and format require {}", actual, expected),
+			TextureError::UnsupportedFormat(format) => write!(f, "Raw pixel upload does not support the {:?} format", format),
+			TextureError::EmptySequence => write!(f, "The animated image contains no frames"),
+			TextureError::InvalidDds(reason) => write!(f, "Not a valid DDS file: {}", reason),
+			TextureError::UnsupportedDdsFormat(description) => write!(f, "Unsupported DDS pixel format: {}", description),
+			TextureError::CompressionUnsupported => write!(f, "BC-compressed textures are not supported in this wgpu version"),
+		}
+	}
+}
+
+impl std::error::Error for TextureError {}
+
+// How a texture's sampler filters and wraps when shaders read from it
+#[derive(Debug, Clone, Copy)]
+pub struct SamplerOptions {
+	pub mag_filter: wgpu::FilterMode,
+	pub min_filter: wgpu::FilterMode,
+	// Linear here blends between mip levels (trilinear filtering); only useful on textures built with a mip chain
+	pub mipmap_filter: wgpu::FilterMode,
+	pub address_mode: wgpu::AddressMode,
+}
+
+impl Default for SamplerOptions {
+	fn default() -> Self {
+		Self {
+			mag_filter: wgpu::FilterMode::Linear,
+			min_filter: wgpu::FilterMode::Linear,
+			mipmap_filter: wgpu::FilterMode::Nearest,
+			address_mode: wgpu::AddressMode::ClampToEdge,
+		}
+	}
+}
+
+impl SamplerOptions {
+	// Nearest-neighbor in every direction, keeping hard pixel edges instead of smearing them
+	pub fn nearest() -> Self {
+		Self {
+			mag_filter: wgpu::FilterMode::Nearest,
+			min_filter: wgpu::FilterMode::Nearest,
+			..Self::default()
+		}
+	}
+
+	// The same filtering with a different wrap mode, e.g. Repeat for tiling background patterns
+	pub fn with_address_mode(mut self, address_mode: wgpu::AddressMode) -> Self {
+		self.address_mode = address_mode;
+		self
+	}
+}
+
+// How wide one pixel of the format is in the upload buffer, for the formats raw uploads accept
+// Single-channel masks, two-channel data, LDR color, and half-float HDR color cover current needs
+pub(crate) fn bytes_per_pixel(format: wgpu::TextureFormat) -> Option<u32> {
+	match format {
+		wgpu::TextureFormat::R8Unorm => Some(1),
+		wgpu::TextureFormat::Rg8Unorm => Some(2),
+		wgpu::TextureFormat::Rgba8Unorm | wgpu::TextureFormat::Rgba8UnormSrgb | wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb => Some(4),
+		wgpu::TextureFormat::Rgba16Float => Some(8),
+		_ => None,
+	}
+}
+
+// The BC compression family a DDS file's blocks use. This wgpu version exposes no compressed
+// texture formats, so the variants only describe the file; the blocks cannot be uploaded as-is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum BcFormat {
+	Bc1,
+	Bc1Srgb,
+	Bc3,
+	Bc3Srgb,
+	Bc7,
+	Bc7Srgb,
+}
+
+impl BcFormat {
+	// How many bytes one 4x4 block of this format occupies
+	pub(crate) fn block_bytes(self) -> u32 {
+		match self {
+			BcFormat::Bc1 | BcFormat::Bc1Srgb => 8,
+			BcFormat::Bc3 | BcFormat::Bc3Srgb | BcFormat::Bc7 | BcFormat::Bc7Srgb => 16,
+		}
+	}
+}
+
+// The fields of a DDS file's header that load_dds needs, decoded by parse_dds_header
+pub(crate) struct DdsHeader {
+	pub(crate) format: BcFormat,
+	pub(crate) width: u32,
+	pub(crate) height: u32,
+	pub(crate) mip_levels: u32,
+	// Where the first mip level's blocks start: right after the header, or after the DX10 extension
+	pub(crate) data_offset: usize,
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+	u32::from_le_bytes([bytes[offset], bytes[offset + 1], bytes[offset + 2], bytes[offset + 3]])
+}
+
+// Decodes the fixed-layout DDS header: the legacy fourCC route covers BC1 (DXT1) and BC3 (DXT5),
+// and the DX10 extension header carries BC1/BC3/BC7 with their sRGB variants by DXGI format number
+pub(crate) fn parse_dds_header(bytes: &[u8]) -> Result<DdsHeader, TextureError> {
+	if bytes.len() < 128 {
+		return Err(TextureError::InvalidDds("the file is shorter than the DDS header"));
+	}
+	if &bytes[0..4] != b"DDS " {
+		return Err(TextureError::InvalidDds("the file does not start with the DDS magic"));
+	}
+	if read_u32(bytes, 4) != 124 {
+		return Err(TextureError::InvalidDds("the header size field is not 124"));
+	}
+
+	let height = read_u32(bytes, 12);
+	let width = read_u32(bytes, 16);
+	// Files without the mip count flag leave the field at zero; either way at least the base level exists
+	let mip_levels = read_u32(bytes, 28).max(1);
+
+	// Only fourCC pixel formats describe compressed data; the bit mask layouts are uncompressed
+	const DDPF_FOURCC: u32 = 0x4;
+	if read_u32(bytes, 80) & DDPF_FOURCC == 0 {
+		return Err(TextureError::UnsupportedDdsFormat("an uncompressed bit-mask layout".to_string()));
+	}
+
+	let fourcc = [bytes[84], bytes[85], bytes[86], bytes[87]];
+	let (format, data_offset) = match &fourcc {
+		b"DXT1" => (BcFormat::Bc1, 128),
+		b"DXT5" => (BcFormat::Bc3, 128),
+		b"DX10" => {
+			if bytes.len() < 148 {
+				return Err(TextureError::InvalidDds("the DX10 extension header is truncated"));
+			}
+			let dxgi_format = read_u32(bytes, 128);
+			let format = match dxgi_format {
+				71 => BcFormat::Bc1,
+				72 => BcFormat::Bc1Srgb,
+				77 => BcFormat::Bc3,
+				78 => BcFormat::Bc3Srgb,
+				98 => BcFormat::Bc7,
+				99 => BcFormat::Bc7Srgb,
+				other => return Err(TextureError::UnsupportedDdsFormat(format!("DXGI format {}", other))),
+			};
+			(format, 148)
+		}
+		other => return Err(TextureError::UnsupportedDdsFormat(format!("fourCC '{}'", String::from_utf8_lossy(other)))),
+	};
+
+	Ok(DdsHeader {
+		format,
+		width,
+		height,
+		mip_levels,
+		data_offset,
+	})
+}
+
+// How many mip levels a full chain down to 1x1 needs for the given dimensions
+pub(crate) fn mip_level_count(width: u32, height: u32) -> u32 {
+	// floor(log2(max(width, height))) + 1
+	32 - width.max(height).leading_zeros()
+}
+
+// Averages each 2x2 block of a level into one pixel of the next level down
+// Odd dimensions clamp the sampling window at the edge, so non-power-of-two chains stay well defined
+fn downsample_rgba(pixels: &[u8], width: u32, height: u32) -> (Vec<u8>, u32, u32) {
+	let next_width = (width / 2).max(1);
+	let next_height = (height / 2).max(1);
+
+	let mut next = Vec::with_capacity((next_width * next_height * 4) as usize);
+	for y in 0..next_height {
+		for x in 0..next_width {
+			for channel in 0..4 {
+				let mut sum = 0;
+				for &(dx, dy) in &[(0, 0), (1, 0), (0, 1), (1, 1)] {
+					let source_x = (2 * x + dx).min(width - 1);
+					let source_y = (2 * y + dy).min(height - 1);
+					sum += u32::from(pixels[((source_y * width + source_x) * 4 + channel) as usize]);
+				}
+				next.push((sum / 4) as u8);
+			}
+		}
+	}
+	(next, next_width, next_height)
+}
+
+// The format used for the window's depth buffer
+pub const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+
+// The dimensions and cell pitch of the built-in fallback checkerboard
+const FALLBACK_SIZE: u32 = 64;
+const FALLBACK_CELL: u32 = 8;
+
+// The fallback's pixel rows: magenta and black cells alternating in both directions
+pub(crate) fn fallback_pixels() -> Vec<u8> {
+	let mut pixels = Vec::with_capacity((FALLBACK_SIZE * FALLBACK_SIZE * 4) as usize);
+	for y in 0..FALLBACK_SIZE {
+		for x in 0..FALLBACK_SIZE {
+			let magenta = ((x / FALLBACK_CELL) + (y / FALLBACK_CELL)).is_multiple_of(2);
+			pixels.extend_from_slice(if magenta { &[255, 0, 255, 255] } else { &[0, 0, 0, 255] });
+		}
+	}
+	pixels
+}
+
+// An image decoded to tightly packed RGBA rows off the main thread, awaiting its GPU upload
+pub struct DecodedImage {
+	pub pixels: Vec<u8>,
+	pub width: u32,
+	pub height: u32,
+}
+
+// The frames of an animated GIF as individual textures, played back on a loop by their stored delays
+pub struct TextureSequence {
+	frames: Vec<Texture>,
+	delays: Vec<std::time::Duration>,
+	// The sum of every frame delay, which playback time wraps around for looping
+	total_duration: std::time::Duration,
+}
+
+impl TextureSequence {
+	pub fn frame_count(&self) -> usize {
+		self.frames.len()
+	}
+
+	pub fn delays(&self) -> &[std::time::Duration] {
+		&self.delays
+	}
+
+	// The frame showing at the given time since playback began; the draw path binds this each render
+	pub fn frame_at(&self, time: std::time::Duration) -> &Texture {
+		&self.frames[frame_index_at(&self.delays, self.total_duration, time)]
+	}
+}
+
+// Which frame of a looping sequence is showing at the given time, by walking the accumulated delays
+fn frame_index_at(delays: &[std::time::Duration], total_duration: std::time::Duration, time: std::time::Duration) -> usize {
+	// A single frame, or delays that sum to zero, pin playback to the first frame
+	if delays.len() <= 1 || total_duration.as_nanos() == 0 {
+		return 0;
+	}
+
+	let mut remaining = std::time::Duration::from_nanos((time.as_nanos() % total_duration.as_nanos()) as u64);
+	for (index, delay) in delays.iter().enumerate() {
+		if remaining < *delay {
+			return index;
+		}
+		remaining -= *delay;
+	}
+	delays.len() - 1
+}
+
+// Decodes every frame of a GIF into tightly packed RGBA rows paired with its display delay
+fn decode_gif_frames(bytes: &[u8]) -> Result<Vec<(DecodedImage, std::time::Duration)>, TextureError> {
+	use image::AnimationDecoder;
+
+	let decoder = image::gif::GifDecoder::new(std::io::Cursor::new(bytes)).map_err(TextureError::Image)?;
+	let frames = decoder.into_frames().collect_frames().map_err(TextureError::Image)?;
+	if frames.is_empty() {
+		return Err(TextureError::EmptySequence);
+	}
+
+	Ok(frames
+		.into_iter()
+		.map(|frame| {
+			// Delays are stored as a millisecond ratio; convert through nanoseconds to keep exact thirds etc.
+			let (numerator, denominator) = frame.delay().numer_denom_ms();
+			let delay = if denominator == 0 {
+				std::time::Duration::from_millis(0)
+			} else {
+				std::time::Duration::from_nanos(u64::from(numerator) * 1_000_000 / u64::from(denominator))
+			};
+
+			let rgba = frame.into_buffer();
+			let (width, height) = rgba.dimensions();
+			let decoded = DecodedImage {
+				pixels: rgba.into_raw(),
+				width,
+				height,
+			};
+			(decoded, delay)
+		})
+		.collect())
+}
+
+pub struct Texture {
+	pub texture: wgpu::Texture,
+	pub view: wgpu::TextureView,
+	pub sampler: wgpu::Sampler,
+	pub(crate) size: wgpu::Extent3d,
+	// Recorded so offscreen passes and readbacks can interpret the texture without callers re-supplying it
+	pub(crate) format: wgpu::TextureFormat,
+	// Recorded for memory accounting; a chain deeper than one level adds a third on top of the base
+	pub(crate) mip_levels: u32,
+}
+
+// Lets texture caches be bounded by a byte budget; the size is the base level's footprint plus the
+// geometric-series third a full mip chain adds, which is close enough for budgeting purposes
+impl crate::resource_cache::SizedResource for Texture {
+	fn byte_size(&self) -> u64 {
+		// Formats outside the raw-upload set are all four bytes per pixel today (Depth32Float)
+		let bytes_per_pixel = bytes_per_pixel(self.format).unwrap_or(4);
+		let base = u64::from(self.size.width) * u64::from(self.size.height) * u64::from(bytes_per_pixel);
+		if self.mip_levels > 1 {
+			base * 4 / 3
+		} else {
+			base
+		}
+	}
+}
+
+impl Texture {
+	// The texture's dimensions in pixels, recorded at construction so callers never have to
+	// re-read the source image just to do UV math or layout
+	pub fn width(&self) -> u32 {
+		self.size.width
+	}
+
+	pub fn height(&self) -> u32 {
+		self.size.height
+	}
+
+	pub fn size(&self) -> (u32, u32) {
+		(self.size.width, self.size.height)
+	}
+
+	pub fn format(&self) -> wgpu::TextureFormat {
+		self.format
+	}
+
+	// Builds the depth buffer matching the current swap chain dimensions and multisample count
+	pub fn create_depth(device: &wgpu::Device, width: u32, height: u32, sample_count: u32) -> Texture {
+		let size = wgpu::Extent3d { width, height, depth: 1 };
+		let texture = device.create_texture(&wgpu::TextureDescriptor {
+			label: Some("depth_texture"),
+			size,
+			array_layer_count: 1,
+			mip_level_count: 1,
+			sample_count,
+			dimension: wgpu::TextureDimension::D2,
+			format: DEPTH_FORMAT,
+			usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT,
+		});
+		let view = texture.create_default_view();
+
+		Texture {
+			texture,
+			view,
+			sampler: nearest_sampler(device),
+			size,
+			format: DEPTH_FORMAT,
+			mip_levels: 1,
+		}
+	}
+
+	// Builds the multisampled color buffer that gets resolved into the swap chain frame each pass
+	pub fn create_msaa(device: &wgpu::Device, width: u32, height: u32, format: wgpu::TextureFormat, sample_count: u32) -> Texture {
+		let size = wgpu::Extent3d { width, height, depth: 1 };
+		let texture = device.create_texture(&wgpu::TextureDescriptor {
+			label: Some("msaa_texture"),
+			size,
+			array_layer_count: 1,
+			mip_level_count: 1,
+			sample_count,
+			dimension: wgpu::TextureDimension::D2,
+			format,
+			usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT,
+		});
+		let view = texture.create_default_view();
+
+		Texture {
+			texture,
+			view,
+			sampler: nearest_sampler(device),
+			size,
+			format,
+			mip_levels: 1,
+		}
+	}
+
+	// Builds an offscreen color target that can both be rendered into and sampled or read back afterwards
+	pub fn render_target(device: &wgpu::Device, width: u32, height: u32, format: wgpu::TextureFormat) -> Texture {
+		let size = wgpu::Extent3d { width, height, depth: 1 };
+		let texture = device.create_texture(&wgpu::TextureDescriptor {
+			label: Some("render_target"),
+			size,
+			array_layer_count: 1,
+			mip_level_count: 1,
+			sample_count: 1,
+			dimension: wgpu::TextureDimension::D2,
+			format,
+			usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT | wgpu::TextureUsage::SAMPLED | wgpu::TextureUsage::COPY_SRC,
+		});
+		let view = texture.create_default_view();
+
+		Texture {
+			texture,
+			view,
+			sampler: nearest_sampler(device),
+			size,
+			format,
+			mip_levels: 1,
+		}
+	}
+
+	// The built-in stand-in for textures that failed to load: a magenta/black checkerboard loud
+	// enough that a missing asset is obvious at a glance instead of crashing the editor
+	pub fn fallback(device: &wgpu::Device, queue: &mut wgpu::Queue) -> Texture {
+		let pixels = fallback_pixels();
+		// Nearest filtering keeps the cells' hard edges, so the pattern stays unmistakable scaled up
+		Texture::from_raw_pixels_with_sampler(device, queue, &pixels, FALLBACK_SIZE, FALLBACK_SIZE, wgpu::TextureFormat::Rgba8UnormSrgb, Some("fallback_checkerboard"), SamplerOptions::nearest())
+			.expect("The fallback checkerboard's dimensions and data always agree")
+	}
+
+	// Builds an empty texture that gets written into incrementally, e.g. a glyph atlas
+	pub fn empty(device: &wgpu::Device, width: u32, height: u32, format: wgpu::TextureFormat) -> Texture {
+		let size = wgpu::Extent3d { width, height, depth: 1 };
+		let texture = device.create_texture(&wgpu::TextureDescriptor {
+			label: Some("empty_texture"),
+			size,
+			array_layer_count: 1,
+			mip_level_count: 1,
+			sample_count: 1,
+			dimension: wgpu::TextureDimension::D2,
+			format,
+			usage: wgpu::TextureUsage::SAMPLED | wgpu::TextureUsage::COPY_DST,
+		});
+		let view = texture.create_default_view();
+
+		Texture {
+			texture,
+			view,
+			sampler: nearest_sampler(device),
+			size,
+			format,
+			mip_levels: 1,
+		}
+	}
+
+	// Uploads already-decoded pixel data in the given format, e.g. single-channel masks or half-float HDR buffers
+	// The data must be tightly packed rows of width * bytes-per-pixel; COPY_SRC is kept on so data textures can be read back
+	pub fn from_raw_pixels(device: &wgpu::Device, queue: &mut wgpu::Queue, pixels: &[u8], width: u32, height: u32, format: wgpu::TextureFormat, label: Option<&str>) -> Result<Texture, TextureError> {
+		let bytes_per_pixel = bytes_per_pixel(format).ok_or(TextureError::UnsupportedFormat(format))?;
+		let expected = (width * height * bytes_per_pixel) as usize;
+		if pixels.len() != expected {
+			return Err(TextureError::PixelDataSize { expected, actual: pixels.len() });
+		}
+
+		let size = wgpu::Extent3d { width, height, depth: 1 };
+		let texture = device.create_texture(&wgpu::TextureDescriptor {
+			label,
+			size,
+			array_layer_count: 1,
+			mip_level_count: 1,
+			sample_count: 1,
+			dimension: wgpu::TextureDimension::D2,
+			format,
+			usage: wgpu::TextureUsage::SAMPLED | wgpu::TextureUsage::COPY_DST | wgpu::TextureUsage::COPY_SRC,
+		});
+
+		// Stage the pixel data in a GPU buffer and record a copy of it into the texture
+		let buffer = device.create_buffer_with_data(pixels, wgpu::BufferUsage::COPY_SRC);
+
+		let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("texture_upload_encoder") });
+		encoder.copy_buffer_to_texture(
+			wgpu::BufferCopyView {
+				buffer: &buffer,
+				offset: 0,
+				bytes_per_row: bytes_per_pixel * width,
+				rows_per_image: height,
+			},
+			wgpu::TextureCopyView {
+				texture: &texture,
+				mip_level: 0,
+				array_layer: 0,
+				origin: wgpu::Origin3d::ZERO,
+			},
+			size,
+		);
+		queue.submit(&[encoder.finish()]);
+
+		let view = texture.create_default_view();
+
+		Ok(Texture {
+			texture,
+			view,
+			sampler: nearest_sampler(device),
+			size,
+			format,
+			mip_levels: 1,
+		})
+	}
+
+	// Like from_raw_pixels, but with the caller's filtering and wrapping; the plain variant keeps a
+	// nearest sampler since raw uploads are usually data textures rather than filtered imagery
+	#[allow(clippy::too_many_arguments)]
+	pub fn from_raw_pixels_with_sampler(device: &wgpu::Device, queue: &mut wgpu::Queue, pixels: &[u8], width: u32, height: u32, format: wgpu::TextureFormat, label: Option<&str>, sampler_options: SamplerOptions) -> Result<Texture, TextureError> {
+		let mut texture = Texture::from_raw_pixels(device, queue, pixels, width, height, format, label)?;
+		texture.sampler = sampler_from_options(device, sampler_options);
+		Ok(texture)
+	}
+
+	pub fn from_filepath(device: &wgpu::Device, queue: &mut wgpu::Queue, path: &str) -> Result<Texture, TextureError> {
+		// Read the image file from disk, then decode and upload it through the shared bytes path
+		let bytes = std::fs::read(path).map_err(TextureError::Io)?;
+		Texture::from_bytes(device, queue, &bytes, Some(path))
+	}
+
+	// Like from_filepath, but with the caller's filtering and wrapping instead of the linear default
+	pub fn from_filepath_with_sampler(device: &wgpu::Device, queue: &mut wgpu::Queue, path: &str, sampler_options: SamplerOptions) -> Result<Texture, TextureError> {
+		let bytes = std::fs::read(path).map_err(TextureError::Io)?;
+		Texture::from_bytes_with_sampler(device, queue, &bytes, Some(path), sampler_options)
+	}
+
+	// Loads pixel art: nearest-neighbor filtering and no mip chain, so icons keep their hard pixel
+	// edges instead of coming out blurry under the linear sampling photographic textures want
+	pub fn pixel_art(device: &wgpu::Device, queue: &mut wgpu::Queue, bytes: &[u8], label: Option<&str>) -> Result<Texture, TextureError> {
+		Texture::from_bytes_with_sampler(device, queue, bytes, label, SamplerOptions::nearest())
+	}
+
+	// Loads an animated GIF as one texture per frame, e.g. for loading spinners
+	pub fn sequence_from_filepath(device: &wgpu::Device, queue: &mut wgpu::Queue, path: &str) -> Result<TextureSequence, TextureError> {
+		let bytes = std::fs::read(path).map_err(TextureError::Io)?;
+		let decoded = decode_gif_frames(&bytes)?;
+
+		let mut frames = Vec::with_capacity(decoded.len());
+		let mut delays = Vec::with_capacity(decoded.len());
+		let mut total_duration = std::time::Duration::from_millis(0);
+		for (image, delay) in decoded {
+			frames.push(Texture::finalize_upload(device, queue, image)?);
+			delays.push(delay);
+			total_duration += delay;
+		}
+
+		Ok(TextureSequence { frames, delays, total_duration })
+	}
+
+	// Reads and decodes the image on a background thread, resolving once the pixels are ready
+	// The GPU upload still happens on the main thread via finalize_upload, so large decodes never stall the event loop
+	pub fn load_async(path: &str) -> impl std::future::Future<Output = Result<DecodedImage, TextureError>> {
+		let path = path.to_string();
+		let (sender, receiver) = futures::channel::oneshot::channel();
+		std::thread::spawn(move || {
+			let _ = sender.send(Texture::decode_file(&path));
+		});
+		async move {
+			// A dropped sender means the decode thread panicked; surface that like any other read failure
+			receiver
+				.await
+				.unwrap_or_else(|_| Err(TextureError::Io(std::io::Error::other("The decode thread exited without a result"))))
+		}
+	}
+
+	// The blocking half of load_async: reads the file and decodes it to tightly packed RGBA rows
+	fn decode_file(path: &str) -> Result<DecodedImage, TextureError> {
+		let bytes = std::fs::read(path).map_err(TextureError::Io)?;
+		let rgba = image::load_from_memory(&bytes).map_err(TextureError::Image)?.into_rgba8();
+		let (width, height) = rgba.dimensions();
+		Ok(DecodedImage {
+			pixels: rgba.into_raw(),
+			width,
+			height,
+		})
+	}
+
+	// Uploads pixels decoded by load_async; cheap enough to run on the main thread between frames
+	pub fn finalize_upload(device: &wgpu::Device, queue: &mut wgpu::Queue, decoded: DecodedImage) -> Result<Texture, TextureError> {
+		let DecodedImage { pixels, width, height } = decoded;
+		let mut texture = Texture::from_raw_pixels(device, queue, &pixels, width, height, wgpu::TextureFormat::Rgba8UnormSrgb, None)?;
+		// Match the linear filtering the synchronous decode path's default sampler uses
+		texture.sampler = sampler_from_options(device, SamplerOptions::default());
+		Ok(texture)
+	}
+
+	pub fn from_bytes(device: &wgpu::Device, queue: &mut wgpu::Queue, bytes: &[u8], label: Option<&str>) -> Result<Texture, TextureError> {
+		Texture::from_bytes_with_sampler(device, queue, bytes, label, SamplerOptions::default())
+	}
+
+	pub fn from_bytes_with_sampler(device: &wgpu::Device, queue: &mut wgpu::Queue, bytes: &[u8], label: Option<&str>, sampler_options: SamplerOptions) -> Result<Texture, TextureError> {
+		Texture::from_bytes_with_options(device, queue, bytes, label, sampler_options, false)
+	}
+
+	// The full decode-and-upload path; with generate_mips the whole chain down to 1x1 is box-filtered
+	// on the CPU and uploaded, so minifying samplers can filter across levels instead of shimmering
+	pub fn from_bytes_with_options(device: &wgpu::Device, queue: &mut wgpu::Queue, bytes: &[u8], label: Option<&str>, sampler_options: SamplerOptions, generate_mips: bool) -> Result<Texture, TextureError> {
+		// Decode the in-memory image data
+		let loaded_image = image::load_from_memory(bytes).map_err(TextureError::Image)?;
+		let rgba = loaded_image.into_rgba8();
+		let (width, height) = rgba.dimensions();
+
+		let size = wgpu::Extent3d { width, height, depth: 1 };
+		let format = wgpu::TextureFormat::Rgba8UnormSrgb;
+		let mip_levels = if generate_mips { mip_level_count(width, height) } else { 1 };
+
+		// Allocate the texture on the GPU
+		let texture = device.create_texture(&wgpu::TextureDescriptor {
+			label,
+			size,
+			array_layer_count: 1,
+			mip_level_count: mip_levels,
+			sample_count: 1,
+			dimension: wgpu::TextureDimension::D2,
+			format,
+			usage: wgpu::TextureUsage::SAMPLED | wgpu::TextureUsage::COPY_DST,
+		});
+
+		// Stage each level's pixel data in a GPU buffer and record a copy of it into the texture,
+		// halving the image down between levels
+		let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("texture_upload_encoder") });
+		let (mut level_pixels, mut level_width, mut level_height) = (rgba.into_raw(), width, height);
+		for mip_level in 0..mip_levels {
+			if mip_level > 0 {
+				let (next_pixels, next_width, next_height) = downsample_rgba(&level_pixels, level_width, level_height);
+				level_pixels = next_pixels;
+				level_width = next_width;
+				level_height = next_height;
+			}
+
+			let buffer = device.create_buffer_with_data(&level_pixels, wgpu::BufferUsage::COPY_SRC);
+			encoder.copy_buffer_to_texture(
+				wgpu::BufferCopyView {
+					buffer: &buffer,
+					offset: 0,
+					bytes_per_row: 4 * level_width,
+					rows_per_image: level_height,
+				},
+				wgpu::TextureCopyView {
+					texture: &texture,
+					mip_level,
+					array_layer: 0,
+					origin: wgpu::Origin3d::ZERO,
+				},
+				wgpu::Extent3d {
+					width: level_width,
+					height: level_height,
+					depth: 1,
+				},
+			);
+		}
+		queue.submit(&[encoder.finish()]);
+
+		let view = texture.create_default_view();
+
+		// Build the sampler that shaders use to read from the texture
+		let sampler = sampler_from_options(device, sampler_options);
+
+		Ok(Texture {
+			texture,
+			view,
+			sampler,
+			size,
+			format,
+			mip_levels,
+		})
+	}
+}
+
+// Builds the sampler the given options describe
+fn sampler_from_options(device: &wgpu::Device, sampler_options: SamplerOptions) -> wgpu::Sampler {
+	device.create_sampler(&wgpu::SamplerDescriptor {
+		address_mode_u: sampler_options.address_mode,
+		address_mode_v: sampler_options.address_mode,
+		address_mode_w: sampler_options.address_mode,
+		mag_filter: sampler_options.mag_filter,
+		min_filter: sampler_options.min_filter,
+		mipmap_filter: sampler_options.mipmap_filter,
+		lod_min_clamp: 0.,
+		lod_max_clamp: 100.,
+		compare: wgpu::CompareFunction::Undefined,
+	})
+}
+
+// Render targets and depth buffers are not meant to be filtered, but every Texture carries a sampler
+fn nearest_sampler(device: &wgpu::Device) -> wgpu::Sampler {
+	device.create_sampler(&wgpu::SamplerDescriptor {
+		address_mode_u: wgpu::AddressMode::ClampToEdge,
+		address_mode_v: wgpu::AddressMode::ClampToEdge,
+		address_mode_w: wgpu::AddressMode::ClampToEdge,
+		mag_filter: wgpu::FilterMode::Nearest,
+		min_filter: wgpu::FilterMode::Nearest,
+		mipmap_filter: wgpu::FilterMode::Nearest,
+		lod_min_clamp: 0.,
+		lod_max_clamp: 100.,
+		compare: wgpu::CompareFunction::Undefined,
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::test_utils::create_test_device;
+	use futures::executor::block_on;
+
+	const GRID_PNG: &[u8] = include_bytes!("../textures/grid.png");
+	// A 2x2 two-frame GIF: a red frame shown for 100ms, then a blue frame shown for 200ms
+	const SPINNER_GIF: &[u8] = include_bytes!("../textures/spinner.gif");
+
+	#[test]
+	fn from_bytes_uploads_embedded_png() {
+		let (device, mut queue) = match create_test_device() {
+			Some(pair) => pair,
+			None => return,
+		};
+
+		let texture = Texture::from_bytes(&device, &mut queue, GRID_PNG, Some("grid")).expect("Embedded PNG should decode and upload");
+
+		// The embedded grid texture is 16x16; confirm the decode agrees before and after the upload
+		let decoded = image::load_from_memory(GRID_PNG).unwrap().into_rgba8();
+		assert_eq!(decoded.dimensions(), (16, 16));
+		drop(texture);
+	}
+
+	#[test]
+	fn dimensions_and_format_are_recorded_at_construction() {
+		let (device, mut queue) = match create_test_device() {
+			Some(pair) => pair,
+			None => return,
+		};
+
+		let texture = Texture::from_bytes(&device, &mut queue, GRID_PNG, Some("grid")).expect("Embedded PNG should decode and upload");
+		assert_eq!(texture.size(), (16, 16));
+		assert_eq!((texture.width(), texture.height()), (16, 16));
+		assert_eq!(texture.format(), wgpu::TextureFormat::Rgba8UnormSrgb);
+	}
+
+	#[test]
+	fn gif_frames_decode_with_their_delays() {
+		let frames = decode_gif_frames(SPINNER_GIF).expect("Embedded GIF should decode");
+
+		assert_eq!(frames.len(), 2);
+		assert_eq!((frames[0].0.width, frames[0].0.height), (2, 2));
+		assert_eq!(frames[0].1, std::time::Duration::from_millis(100));
+		assert_eq!(frames[1].1, std::time::Duration::from_millis(200));
+		// The first frame is solid red, the second solid blue
+		assert_eq!(&frames[0].0.pixels[0..4], &[255, 0, 0, 255]);
+		assert_eq!(&frames[1].0.pixels[0..4], &[0, 0, 255, 255]);
+	}
+
+	#[test]
+	fn frame_selection_loops_over_the_accumulated_delays() {
+		let ms = std::time::Duration::from_millis;
+		let delays = [ms(100), ms(200)];
+		let total = ms(300);
+
+		assert_eq!(frame_index_at(&delays, total, ms(0)), 0);
+		assert_eq!(frame_index_at(&delays, total, ms(99)), 0);
+		assert_eq!(frame_index_at(&delays, total, ms(100)), 1);
+		assert_eq!(frame_index_at(&delays, total, ms(299)), 1);
+		// Time wraps at the total, so the sequence loops forever
+		assert_eq!(frame_index_at(&delays, total, ms(300)), 0);
+		assert_eq!(frame_index_at(&delays, total, ms(1050)), 1);
+
+		// Degenerate sequences pin to the first frame instead of dividing by zero
+		assert_eq!(frame_index_at(&[ms(0)], ms(0), ms(42)), 0);
+	}
+
+	#[test]
+	fn pixel_art_uploads_without_a_mip_chain() {
+		let (device, mut queue) = match create_test_device() {
+			Some(pair) => pair,
+			None => return,
+		};
+
+		let icon = Texture::pixel_art(&device, &mut queue, GRID_PNG, Some("icon")).expect("Embedded PNG should decode and upload");
+		assert_eq!(icon.size(), (16, 16));
+		assert_eq!(icon.mip_levels, 1);
+	}
+
+	#[test]
+	fn sampler_options_build_with_nearest_filtering_and_custom_wrapping() {
+		let nearest = SamplerOptions::nearest();
+		assert_eq!(nearest.mag_filter, wgpu::FilterMode::Nearest);
+		assert_eq!(nearest.min_filter, wgpu::FilterMode::Nearest);
+		assert_eq!(nearest.address_mode, wgpu::AddressMode::ClampToEdge);
+
+		let tiling = SamplerOptions::default().with_address_mode(wgpu::AddressMode::Repeat);
+		assert_eq!(tiling.address_mode, wgpu::AddressMode::Repeat);
+		assert_eq!(tiling.mag_filter, wgpu::FilterMode::Linear);
+	}
+
+	#[test]
+	fn the_fallback_checkerboard_alternates_magenta_and_black() {
+		let pixels = fallback_pixels();
+		assert_eq!(pixels.len(), (FALLBACK_SIZE * FALLBACK_SIZE * 4) as usize);
+
+		// One cell to the right or down flips the color; one diagonal step flips it back
+		let pixel_at = |x: u32, y: u32| &pixels[((y * FALLBACK_SIZE + x) * 4) as usize..((y * FALLBACK_SIZE + x) * 4 + 4) as usize];
+		assert_eq!(pixel_at(0, 0), &[255, 0, 255, 255]);
+		assert_eq!(pixel_at(FALLBACK_CELL, 0), &[0, 0, 0, 255]);
+		assert_eq!(pixel_at(0, FALLBACK_CELL), &[0, 0, 0, 255]);
+		assert_eq!(pixel_at(FALLBACK_CELL, FALLBACK_CELL), &[255, 0, 255, 255]);
+
+		let (device, mut queue) = match create_test_device() {
+			Some(pair) => pair,
+			None => return,
+		};
+		let texture = Texture::fallback(&device, &mut queue);
+		assert_eq!(texture.size(), (FALLBACK_SIZE, FALLBACK_SIZE));
+		assert_eq!(texture.format(), wgpu::TextureFormat::Rgba8UnormSrgb);
+	}
+
+	#[test]
+	fn byte_size_accounts_for_format_and_mip_chain() {
+		use crate::resource_cache::SizedResource;
+		let (device, mut queue) = match create_test_device() {
+			Some(pair) => pair,
+			None => return,
+		};
+
+		// 16x16 RGBA without mips is exactly the base level's 1024 bytes
+		let flat = Texture::from_bytes(&device, &mut queue, GRID_PNG, None).expect("Embedded PNG should decode and upload");
+		assert_eq!(flat.byte_size(), 16 * 16 * 4);
+
+		// A full mip chain adds the geometric-series third on top
+		let mipped = Texture::from_bytes_with_options(&device, &mut queue, GRID_PNG, None, SamplerOptions::default(), true).expect("Embedded PNG should decode and upload");
+		assert_eq!(mipped.byte_size(), 16 * 16 * 4 * 4 / 3);
+	}
+
+	#[test]
+	fn a_full_mip_chain_reaches_one_by_one() {
+		assert_eq!(mip_level_count(1, 1), 1);
+		assert_eq!(mip_level_count(16, 16), 5);
+		assert_eq!(mip_level_count(640, 480), 10);
+		// Non-power-of-two: floor(log2(5)) + 1
+		assert_eq!(mip_level_count(5, 4), 3);
+	}
+
+	#[test]
+	fn downsampling_averages_each_two_by_two_block() {
+		// A 2x2 image whose red channel holds 0, 40, 80, 120 collapses to their average
+		let pixels = [0, 0, 0, 255, 40, 0, 0, 255, 80, 0, 0, 255, 120, 0, 0, 255];
+		let (next, width, height) = downsample_rgba(&pixels, 2, 2);
+		assert_eq!((width, height), (1, 1));
+		assert_eq!(next, vec![60, 0, 0, 255]);
+	}
+
+	#[test]
+	fn odd_dimensions_clamp_at_the_edge() {
+		// A 3x1 image: the 2x2 window clamps to the single row and samples x = 0, 1, 0, 1
+		let pixels = [10, 0, 0, 255, 30, 0, 0, 255, 200, 0, 0, 255];
+		let (next, width, height) = downsample_rgba(&pixels[..], 3, 1);
+		assert_eq!((width, height), (1, 1));
+		assert_eq!(next[0], 20);
+	}
+
+	// Builds a minimal DDS file in memory: magic, 124-byte header, optional DX10 extension, then data
+	fn dds_bytes(fourcc: &[u8; 4], width: u32, height: u32, mip_levels: u32, dxgi_format: Option<u32>, data: &[u8]) -> Vec<u8> {
+		let mut bytes = Vec::new();
+		bytes.extend_from_slice(b"DDS ");
+		bytes.extend_from_slice(&124u32.to_le_bytes());
+		bytes.extend_from_slice(&0u32.to_le_bytes());
+		bytes.extend_from_slice(&height.to_le_bytes());
+		bytes.extend_from_slice(&width.to_le_bytes());
+		bytes.extend_from_slice(&0u32.to_le_bytes());
+		bytes.extend_from_slice(&0u32.to_le_bytes());
+		bytes.extend_from_slice(&mip_levels.to_le_bytes());
+		bytes.resize(76, 0);
+		bytes.extend_from_slice(&32u32.to_le_bytes());
+		// DDPF_FOURCC
+		bytes.extend_from_slice(&0x4u32.to_le_bytes());
+		bytes.extend_from_slice(fourcc);
+		bytes.resize(128, 0);
+		if let Some(dxgi_format) = dxgi_format {
+			bytes.extend_from_slice(&dxgi_format.to_le_bytes());
+			bytes.resize(148, 0);
+		}
+		bytes.extend_from_slice(data);
+		bytes
+	}
+
+	#[test]
+	fn legacy_fourcc_headers_parse_to_their_bc_formats() {
+		let header = parse_dds_header(&dds_bytes(b"DXT1", 8, 4, 1, None, &[0; 16])).expect("A DXT1 header should parse");
+		assert_eq!(header.format, BcFormat::Bc1);
+		assert_eq!((header.width, header.height, header.mip_levels), (8, 4, 1));
+		assert_eq!(header.data_offset, 128);
+
+		let header = parse_dds_header(&dds_bytes(b"DXT5", 4, 4, 1, None, &[0; 16])).expect("A DXT5 header should parse");
+		assert_eq!(header.format, BcFormat::Bc3);
+
+		// A zero mip count still means the base level exists
+		let header = parse_dds_header(&dds_bytes(b"DXT1", 4, 4, 0, None, &[0; 8])).expect("A mipless DXT1 header should parse");
+		assert_eq!(header.mip_levels, 1);
+	}
+
+	#[test]
+	fn dx10_headers_select_bc7_and_srgb_variants() {
+		let header = parse_dds_header(&dds_bytes(b"DX10", 4, 4, 1, Some(98), &[0; 16])).expect("A BC7 DX10 header should parse");
+		assert_eq!(header.format, BcFormat::Bc7);
+		assert_eq!(header.data_offset, 148);
+
+		let header = parse_dds_header(&dds_bytes(b"DX10", 4, 4, 1, Some(72), &[0; 8])).expect("An sRGB BC1 DX10 header should parse");
+		assert_eq!(header.format, BcFormat::Bc1Srgb);
+	}
+
+	#[test]
+	fn malformed_and_unsupported_dds_files_are_rejected() {
+		match parse_dds_header(b"not a dds file, just some bytes that are long enough to pass the length check before the magic comparison runs....") {
+			Err(TextureError::InvalidDds(_)) => {}
+			_ => panic!("A missing magic should be rejected"),
+		}
+
+		match parse_dds_header(&dds_bytes(b"DXT3", 4, 4, 1, None, &[0; 16])) {
+			Err(TextureError::UnsupportedDdsFormat(description)) => assert!(description.contains("DXT3")),
+			_ => panic!("An unmapped fourCC should be rejected"),
+		}
+
+		match parse_dds_header(&dds_bytes(b"DX10", 4, 4, 1, Some(42), &[0; 16])) {
+			Err(TextureError::UnsupportedDdsFormat(description)) => assert!(description.contains("42")),
+			_ => panic!("An unmapped DXGI format should be rejected"),
+		}
+	}
+
+	#[test]
+	fn bc_block_sizes_match_their_compression_family() {
+		assert_eq!(BcFormat::Bc1.block_bytes(), 8);
+		assert_eq!(BcFormat::Bc7Srgb.block_bytes(), 16);
+	}
+
+	#[test]
+	fn async_loads_decode_on_a_background_thread() {
+		let decoded = block_on(Texture::load_async("textures/grid.png")).expect("The grid texture should decode");
+		assert_eq!((decoded.width, decoded.height), (16, 16));
+		assert_eq!(decoded.pixels.len(), 16 * 16 * 4);
+	}
+
+	#[test]
+	fn async_loads_surface_missing_files_as_errors() {
+		match block_on(Texture::load_async("textures/missing.png")) {
+			Err(TextureError::Io(_)) => {}
+			_ => panic!("A missing file should surface an IO error"),
+		}
+	}
+
+	#[test]
+	fn from_bytes_rejects_garbage() {
+		let (device, mut queue) = match create_test_device() {
+			Some(pair) => pair,
+			None => return,
+		};
+
+		assert!(Texture::from_bytes(&device, &mut queue, &[0, 1, 2, 3], None).is_err());
+	}
+
+	#[test]
+	fn raw_uploads_reject_mismatched_pixel_data() {
+		let (device, mut queue) = match create_test_device() {
+			Some(pair) => pair,
+			None => return,
+		};
+
+		// 8x8 at one byte per pixel needs 64 bytes, not 63
+		let result = Texture::from_raw_pixels(&device, &mut queue, &[0; 63], 8, 8, wgpu::TextureFormat::R8Unorm, None);
+		match result {
+			Err(TextureError::PixelDataSize { expected, actual }) => {
+				assert_eq!(expected, 64);
+				assert_eq!(actual, 63);
+			}
+			_ => panic!("A short pixel buffer should be rejected"),
+		}
+
+		// Depth formats have no raw upload path
+		assert!(Texture::from_raw_pixels(&device, &mut queue, &[0; 64], 8, 8, DEPTH_FORMAT, None).is_err());
+	}
+
+	#[test]
+	fn an_r8_mask_uploads_and_reads_back_intact() {
+		let (device, mut queue) = match create_test_device() {
+			Some(pair) => pair,
+			None => return,
+		};
+
+		// One byte per pixel at width 256 keeps rows on wgpu's 256-byte copy alignment for the readback
+		let pixels: Vec<u8> = (0..512).map(|index| (index % 256) as u8).collect();
+		let mask = Texture::from_raw_pixels(&device, &mut queue, &pixels, 256, 2, wgpu::TextureFormat::R8Unorm, Some("mask")).expect("An R8 mask should upload");
+
+		let readback = device.create_buffer(&wgpu::BufferDescriptor {
+			label: None,
+			size: 512,
+			usage: wgpu::BufferUsage::COPY_DST | wgpu::BufferUsage::MAP_READ,
+		});
+		let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+		encoder.copy_texture_to_buffer(
+			wgpu::TextureCopyView {
+				texture: &mask.texture,
+				mip_level: 0,
+				array_layer: 0,
+				origin: wgpu::Origin3d::ZERO,
+			},
+			wgpu::BufferCopyView {
+				buffer: &readback,
+				offset: 0,
+				bytes_per_row: 256,
+				rows_per_image: 2,
+			},
+			wgpu::Extent3d { width: 256, height: 2, depth: 1 },
+		);
+		queue.submit(&[encoder.finish()]);
+
+		let mapped = readback.map_read(0, 512);
+		device.poll(wgpu::Maintain::Wait);
+		let mapped = block_on(mapped).expect("Failed to map the readback buffer");
+		assert_eq!(mapped.as_slice(), pixels.as_slice());
+	}
+
+	#[test]
+	fn render_target_can_be_cleared_and_read_back() {
+		let (device, queue) = match create_test_device() {
+			Some(pair) => pair,
+			None => return,
+		};
+		let target = Texture::render_target(&device, 16, 16, wgpu::TextureFormat::Rgba8Unorm);
+
+		// Clear the target to opaque red
+		let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+		{
+			encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+				color_attachments: &[wgpu::RenderPassColorAttachmentDescriptor {
+					attachment: &target.view,
+					resolve_target: None,
+					load_op: wgpu::LoadOp::Clear,
+					store_op: wgpu::StoreOp::Store,
+					clear_color: wgpu::Color { r: 1., g: 0., b: 0., a: 1. },
+				}],
+				depth_stencil_attachment: None,
+			});
+		}
+
+		// Read the pixels back; 16 pixels * 4 bytes = 64-byte rows need padding to wgpu's 256-byte alignment
+		let bytes_per_row = 256;
+		let readback = device.create_buffer(&wgpu::BufferDescriptor {
+			label: None,
+			size: (bytes_per_row * 16) as wgpu::BufferAddress,
+			usage: wgpu::BufferUsage::COPY_DST | wgpu::BufferUsage::MAP_READ,
+		});
+		encoder.copy_texture_to_buffer(
+			wgpu::TextureCopyView {
+				texture: &target.texture,
+				mip_level: 0,
+				array_layer: 0,
+				origin: wgpu::Origin3d::ZERO,
+			},
+			wgpu::BufferCopyView {
+				buffer: &readback,
+				offset: 0,
+				bytes_per_row,
+				rows_per_image: 16,
+			},
+			wgpu::Extent3d { width: 16, height: 16, depth: 1 },
+		);
+		queue.submit(&[encoder.finish()]);
+
+		let mapped = readback.map_read(0, (bytes_per_row * 16) as wgpu::BufferAddress);
+		device.poll(wgpu::Maintain::Wait);
+		let mapped = block_on(mapped).expect("Failed to map the readback buffer");
+		let data = mapped.as_slice();
+		assert_eq!(&data[0..4], &[255, 0, 0, 255]);
+	}
+}
@@ -0,0 +1,222 @@
+use crate::texture::Texture;
+use std::fmt;
+
+#[derive(Debug)]
+pub enum AtlasError {
+	// No free region large enough remains; callers can fall back to a standalone texture
+	AtlasFull,
+	Image(image::ImageError),
+}
+
+impl fmt::Display for AtlasError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			AtlasError::AtlasFull => write!(f, "The texture atlas is full; no space remains for the image"),
+			AtlasError::Image(error) => write!(f, "Failed to decode the image for the atlas: {}", error),
+		}
+	}
+}
+
+impl std::error::Error for AtlasError {}
+
+// Allocates rectangles left to right along horizontal shelves, opening a new shelf when a row fills up
+// Entries of similar heights pack tightly, so the wasted space above short entries stays small
+pub(crate) struct ShelfPacker {
+	width: u32,
+	height: u32,
+	cursor_x: u32,
+	shelf_y: u32,
+	shelf_height: u32,
+}
+
+impl ShelfPacker {
+	pub(crate) fn new(width: u32, height: u32) -> Self {
+		Self {
+			width,
+			height,
+			cursor_x: 0,
+			shelf_y: 0,
+			shelf_height: 0,
+		}
+	}
+
+	pub(crate) fn allocate(&mut self, width: u32, height: u32) -> Option<(u32, u32)> {
+		if width > self.width {
+			return None;
+		}
+
+		// Start a new shelf below the current one when this row is full
+		if self.cursor_x + width > self.width {
+			self.shelf_y += self.shelf_height;
+			self.cursor_x = 0;
+			self.shelf_height = 0;
+		}
+		if self.shelf_y + height > self.height {
+			return None;
+		}
+
+		let position = (self.cursor_x, self.shelf_y);
+		self.cursor_x += width;
+		self.shelf_height = self.shelf_height.max(height);
+		Some(position)
+	}
+}
+
+// Where an inserted image landed in the atlas: its placement in pixels and the normalized
+// UV rectangle that quads sampling the atlas should use
+#[derive(Debug, Clone, Copy)]
+pub struct AtlasRegion {
+	pub x: u32,
+	pub y: u32,
+	pub width: u32,
+	pub height: u32,
+	pub uv_min: [f32; 2],
+	pub uv_max: [f32; 2],
+}
+
+// Packs many small images (icons, thumbnails) into one texture so they can all be drawn from a
+// single bind group, with each quad selecting its image through UVs instead of a texture switch
+pub struct TextureAtlas {
+	pub texture: Texture,
+	size: u32,
+	packer: ShelfPacker,
+}
+
+impl TextureAtlas {
+	pub fn new(device: &wgpu::Device, size: u32) -> Self {
+		Self {
+			texture: Texture::empty(device, size, size, wgpu::TextureFormat::Rgba8UnormSrgb),
+			size,
+			packer: ShelfPacker::new(size, size),
+		}
+	}
+
+	// Decodes an encoded image (e.g. PNG bytes) and uploads it into a free region of the atlas
+	pub fn insert(&mut self, device: &wgpu::Device, queue: &mut wgpu::Queue, image_bytes: &[u8]) -> Result<AtlasRegion, AtlasError> {
+		let rgba = image::load_from_memory(image_bytes).map_err(AtlasError::Image)?.into_rgba8();
+		let (width, height) = rgba.dimensions();
+
+		// Reserve an extra pixel on each axis as a gutter so sampling cannot bleed between neighbors
+		let (x, y) = self.packer.allocate(width + 1, height + 1).ok_or(AtlasError::AtlasFull)?;
+		upload_region(device, queue, &self.texture, x, y, width, height, &rgba.into_raw());
+
+		let atlas_size = self.size as f32;
+		Ok(AtlasRegion {
+			x,
+			y,
+			width,
+			height,
+			uv_min: [x as f32 / atlas_size, y as f32 / atlas_size],
+			uv_max: [(x + width) as f32 / atlas_size, (y + height) as f32 / atlas_size],
+		})
+	}
+}
+
+// Copies tightly packed RGBA pixels into a sub-region of an atlas texture
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn upload_region(device: &wgpu::Device, queue: &mut wgpu::Queue, atlas: &Texture, x: u32, y: u32, width: u32, height: u32, pixels: &[u8]) {
+	// Buffer-to-texture copies require rows padded out to 256-byte alignment
+	let unpadded_bytes_per_row = 4 * width;
+	let padded_bytes_per_row = unpadded_bytes_per_row.div_ceil(256) * 256;
+	let mut padded = vec![0u8; (padded_bytes_per_row * height) as usize];
+	for row in 0..height as usize {
+		let source = row * unpadded_bytes_per_row as usize;
+		let destination = row * padded_bytes_per_row as usize;
+		padded[destination..destination + unpadded_bytes_per_row as usize].copy_from_slice(&pixels[source..source + unpadded_bytes_per_row as usize]);
+	}
+
+	let buffer = device.create_buffer_with_data(&padded, wgpu::BufferUsage::COPY_SRC);
+	let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("atlas_upload_encoder") });
+	encoder.copy_buffer_to_texture(
+		wgpu::BufferCopyView {
+			buffer: &buffer,
+			offset: 0,
+			bytes_per_row: padded_bytes_per_row,
+			rows_per_image: height,
+		},
+		wgpu::TextureCopyView {
+			texture: &atlas.texture,
+			mip_level: 0,
+			array_layer: 0,
+			origin: wgpu::Origin3d { x, y, z: 0 },
+		},
+		wgpu::Extent3d { width, height, depth: 1 },
+	);
+	queue.submit(&[encoder.finish()]);
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::test_utils::create_test_device;
+
+	// Encodes a solid-color PNG in memory, standing in for an icon file
+	fn png_bytes(width: u32, height: u32) -> Vec<u8> {
+		let image = image::DynamicImage::ImageRgba8(image::RgbaImage::from_pixel(width, height, image::Rgba([128, 64, 32, 255])));
+		let mut bytes = Vec::new();
+		image.write_to(&mut bytes, image::ImageOutputFormat::Png).expect("Encoding a PNG in memory should succeed");
+		bytes
+	}
+
+	fn regions_overlap(a: &AtlasRegion, b: &AtlasRegion) -> bool {
+		a.x < b.x + b.width && b.x < a.x + a.width && a.y < b.y + b.height && b.y < a.y + a.height
+	}
+
+	#[test]
+	fn the_packer_fills_a_shelf_before_opening_the_next() {
+		let mut packer = ShelfPacker::new(100, 100);
+
+		assert_eq!(packer.allocate(40, 10), Some((0, 0)));
+		assert_eq!(packer.allocate(40, 12), Some((40, 0)));
+		// Doesn't fit beside the first two, so a new shelf opens below the tallest entry
+		assert_eq!(packer.allocate(40, 10), Some((0, 12)));
+	}
+
+	#[test]
+	fn the_packer_rejects_what_cannot_fit() {
+		let mut packer = ShelfPacker::new(100, 20);
+
+		assert_eq!(packer.allocate(200, 10), None);
+		assert_eq!(packer.allocate(100, 15), Some((0, 0)));
+		// A second 15-tall shelf would overflow the 20-tall atlas
+		assert_eq!(packer.allocate(100, 15), None);
+	}
+
+	#[test]
+	fn inserted_images_get_disjoint_regions_and_uvs_inside_the_atlas() {
+		let (device, mut queue) = match create_test_device() {
+			Some(pair) => pair,
+			None => return,
+		};
+		let mut atlas = TextureAtlas::new(&device, 128);
+
+		let regions: Vec<AtlasRegion> = [(32, 32), (48, 16), (16, 48), (40, 40)]
+			.iter()
+			.map(|&(width, height)| atlas.insert(&device, &mut queue, &png_bytes(width, height)).expect("The atlas has room for all four images"))
+			.collect();
+
+		for (index, a) in regions.iter().enumerate() {
+			for b in &regions[index + 1..] {
+				assert!(!regions_overlap(a, b), "Regions {:?} and {:?} overlap", a, b);
+			}
+			// The UV rectangle stays normalized and inside the texture
+			assert!(a.uv_min[0] >= 0. && a.uv_min[1] >= 0. && a.uv_max[0] <= 1. && a.uv_max[1] <= 1.);
+		}
+	}
+
+	#[test]
+	fn a_full_atlas_reports_atlas_full() {
+		let (device, mut queue) = match create_test_device() {
+			Some(pair) => pair,
+			None => return,
+		};
+		let mut atlas = TextureAtlas::new(&device, 32);
+
+		// 31x31 plus its gutter fills the whole atlas, leaving no room for even a 16x16 follow-up
+		atlas.insert(&device, &mut queue, &png_bytes(31, 31)).expect("The first image fits");
+		match atlas.insert(&device, &mut queue, &png_bytes(16, 16)) {
+			Err(AtlasError::AtlasFull) => {}
+			_ => panic!("A full atlas should report AtlasFull"),
+		}
+	}
+}
@@ -0,0 +1,53 @@
+// A GPU-side 4x4 matrix, bound into a pipeline's bind group for transforms
+pub struct UniformBuffer {
+	pub buffer: wgpu::Buffer,
+}
+
+// Column-major 4x4 matrix occupying the std140-compatible 64 bytes
+pub type Matrix4 = [[f32; 4]; 4];
+
+pub const MATRIX_SIZE: wgpu::BufferAddress = std::mem::size_of::<Matrix4>() as wgpu::BufferAddress;
+
+pub const IDENTITY: Matrix4 = [[1., 0., 0., 0.], [0., 1., 0., 0.], [0., 0., 1., 0.], [0., 0., 0., 1.]];
+
+impl UniformBuffer {
+	pub fn new(device: &wgpu::Device, matrix: Matrix4) -> Self {
+		let buffer = device.create_buffer_with_data(bytemuck::cast_slice(&matrix), wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST);
+
+		Self { buffer }
+	}
+
+	// Writes a new matrix into the buffer by staging the data and copying it on the GPU timeline
+	pub fn update_uniform(&self, device: &wgpu::Device, queue: &mut wgpu::Queue, matrix: Matrix4) {
+		let staging = device.create_buffer_with_data(bytemuck::cast_slice(&matrix), wgpu::BufferUsage::COPY_SRC);
+
+		let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("uniform_update_encoder") });
+		encoder.copy_buffer_to_buffer(&staging, 0, &self.buffer, 0, MATRIX_SIZE);
+		queue.submit(&[encoder.finish()]);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::test_utils::create_test_device;
+
+	#[test]
+	fn matrix_occupies_sixty_four_bytes() {
+		assert_eq!(MATRIX_SIZE, 64);
+	}
+
+	#[test]
+	fn uniform_buffer_is_writable() {
+		let (device, mut queue) = match create_test_device() {
+			Some(pair) => pair,
+			None => return,
+		};
+
+		let uniform_buffer = UniformBuffer::new(&device, IDENTITY);
+
+		let mut translated = IDENTITY;
+		translated[3][0] = 0.5;
+		uniform_buffer.update_uniform(&device, &mut queue, translated);
+	}
+}
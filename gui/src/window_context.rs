@@ -0,0 +1,201 @@
+use crate::draw_command::DrawCommand;
+use crate::gui_tree::GuiTree;
+use crate::texture::Texture;
+
+// Everything tied to a single OS window: its presentation surface and frame buffers, the GUI tree
+// laid out inside it, and the draw commands queued for its next frame
+// The device, queue, and resource caches are shared across windows and stay on Application
+pub struct WindowContext {
+	// Both None when running headless; frames then render into offscreen_target
+	pub surface: Option<wgpu::Surface>,
+	pub swap_chain: Option<wgpu::SwapChain>,
+	pub swap_chain_descriptor: wgpu::SwapChainDescriptor,
+	offscreen_target: Option<Texture>,
+	pub depth_texture: Texture,
+	pub msaa_texture: Option<Texture>,
+	pub gui_tree: GuiTree,
+	// The front queue render replays: always the last fully-built set of commands
+	pub draw_command_queue: Vec<DrawCommand>,
+	// The back queue a rebuild accumulates into; commit_draw_commands promotes it to the front
+	// in one move, so a frame rendered mid-rebuild never shows a half-built queue
+	pub pending_draw_commands: Vec<DrawCommand>,
+	// Immediate-mode shapes queued through draw_line and friends, drawn over everything and
+	// drained after every frame rather than retained like the GUI's queue
+	pub overlay_draw_commands: Vec<DrawCommand>,
+	// The window's DPI scale factor; the GUI lays out in logical pixels divided out by this
+	pub scale_factor: f64,
+	// Whether this window's visible state changed since it last rendered
+	pub dirty: bool,
+	// Whether the OS reports this window as fully covered by other windows
+	pub occluded: bool,
+	// Whether this window is minimized, observed from the zero-size resize it reports
+	pub minimized: bool,
+	// Consecutive failed frame acquisitions; reaching MAX_ACQUISITION_FAILURES triggers device recovery
+	pub acquisition_failures: u32,
+}
+
+impl WindowContext {
+	// Builds the presentation state for one window on the shared device; passing no surface makes a
+	// headless context that renders into an offscreen color target instead
+	pub fn new(device: &wgpu::Device, surface: Option<wgpu::Surface>, width: u32, height: u32, format: wgpu::TextureFormat) -> Self {
+		// Properties describing the frame buffers that get rendered to the window surface
+		// Headless contexts keep the descriptor too, as the shared record of dimensions and format
+		let swap_chain_descriptor = wgpu::SwapChainDescriptor {
+			usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT,
+			format,
+			width,
+			height,
+			present_mode: wgpu::PresentMode::Fifo,
+		};
+
+		let swap_chain = surface.as_ref().map(|surface| device.create_swap_chain(surface, &swap_chain_descriptor));
+		let offscreen_target = match surface {
+			None => Some(Texture::render_target(device, width, height, format)),
+			Some(_) => None,
+		};
+
+		// Depth buffer shared by this window's render passes, matching the swap chain dimensions
+		let depth_texture = Texture::create_depth(device, width, height, 1);
+
+		Self {
+			surface,
+			swap_chain,
+			swap_chain_descriptor,
+			offscreen_target,
+			depth_texture,
+			// Multisampling is off by default; recreate_render_targets builds the buffer when it's on
+			msaa_texture: None,
+			gui_tree: GuiTree::new(),
+			draw_command_queue: Vec::new(),
+			pending_draw_commands: Vec::new(),
+			overlay_draw_commands: Vec::new(),
+			// Windowed contexts overwrite this from the window; headless ones stay at 1
+			scale_factor: 1.,
+			// Start dirty so the first frame gets drawn
+			dirty: true,
+			occluded: false,
+			minimized: false,
+			acquisition_failures: 0,
+		}
+	}
+
+	// Whether frames for this window would be invisible, so rendering can pause until a restore
+	pub fn is_render_paused(&self) -> bool {
+		self.occluded || self.minimized
+	}
+
+	// Rebuilds the swap chain from the current descriptor; a no-op when running headless
+	pub fn recreate_swap_chain(&mut self, device: &wgpu::Device) {
+		if let Some(surface) = &self.surface {
+			self.swap_chain = Some(device.create_swap_chain(surface, &self.swap_chain_descriptor));
+		}
+	}
+
+	// (Re)builds the depth buffer and, when multisampling is on, the intermediate color buffer
+	// The color format is the one the scene passes render in: the swap chain's ordinarily, or the
+	// HDR format while the post-process stage is interposed
+	pub fn recreate_render_targets(&mut self, device: &wgpu::Device, sample_count: u32, color_format: wgpu::TextureFormat) {
+		let (width, height) = (self.swap_chain_descriptor.width, self.swap_chain_descriptor.height);
+		self.depth_texture = Texture::create_depth(device, width, height, sample_count);
+		self.msaa_texture = match sample_count {
+			1 => None,
+			sample_count => Some(Texture::create_msaa(device, width, height, color_format, sample_count)),
+		};
+	}
+
+	// Swaps the finished back queue to the front, returning the retired front commands so their
+	// buffers can be recycled; until this runs, render keeps drawing the previous complete queue
+	pub fn commit_draw_commands(&mut self) -> Vec<DrawCommand> {
+		let pending = std::mem::take(&mut self.pending_draw_commands);
+		std::mem::replace(&mut self.draw_command_queue, pending)
+	}
+
+	// Rebuilds the headless color target at the descriptor's dimensions; a no-op for real windows
+	pub fn recreate_offscreen_target(&mut self, device: &wgpu::Device) {
+		if self.offscreen_target.is_some() {
+			self.offscreen_target = Some(Texture::render_target(
+				device,
+				self.swap_chain_descriptor.width,
+				self.swap_chain_descriptor.height,
+				self.swap_chain_descriptor.format,
+			));
+		}
+	}
+
+	// Lends out the offscreen target so a frame can render into it; put it back with return_offscreen_target
+	pub fn take_offscreen_target(&mut self) -> Option<Texture> {
+		self.offscreen_target.take()
+	}
+
+	pub fn return_offscreen_target(&mut self, target: Texture) {
+		self.offscreen_target = Some(target);
+	}
+
+	pub fn has_offscreen_target(&self) -> bool {
+		self.offscreen_target.is_some()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::test_utils::create_test_device;
+
+	#[test]
+	fn a_headless_context_renders_into_an_offscreen_target() {
+		let (device, _queue) = match create_test_device() {
+			Some(pair) => pair,
+			None => return,
+		};
+		let mut context = WindowContext::new(&device, None, 64, 32, wgpu::TextureFormat::Bgra8UnormSrgb);
+
+		assert!(context.swap_chain.is_none());
+		assert!(context.has_offscreen_target());
+		assert!(context.dirty);
+
+		// Multisampling adds the intermediate color buffer; turning it back off drops it again
+		context.recreate_render_targets(&device, 4, wgpu::TextureFormat::Bgra8UnormSrgb);
+		assert!(context.msaa_texture.is_some());
+		context.recreate_render_targets(&device, 1, wgpu::TextureFormat::Bgra8UnormSrgb);
+		assert!(context.msaa_texture.is_none());
+	}
+
+	#[test]
+	fn a_half_built_back_queue_stays_off_the_front_until_commit() {
+		use crate::color_palette::ColorPalette;
+		use crate::draw_command::Quad;
+		use crate::geometry::Rect;
+
+		let (device, _queue) = match create_test_device() {
+			Some(pair) => pair,
+			None => return,
+		};
+		let mut context = WindowContext::new(&device, None, 64, 32, wgpu::TextureFormat::Bgra8UnormSrgb);
+
+		let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor { bindings: &[], label: None });
+		let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+			layout: &layout,
+			bindings: &[],
+			label: None,
+		});
+		let quad = Quad::new(Rect::new(0., 0., 8., 8.), ColorPalette::Accent);
+		let command = DrawCommand::new(&device, String::from("test"), &quad.vertices(), &Quad::INDICES, bind_group);
+
+		// Mid-rebuild, the command sits on the back queue; render replays only the front, so a
+		// frame drawn now shows the previous complete queue rather than this half-built one
+		context.pending_draw_commands.push(command);
+		assert!(context.draw_command_queue.is_empty());
+		assert_eq!(context.pending_draw_commands.len(), 1);
+
+		// The commit promotes the finished queue in one move and hands back the retired front
+		let retired = context.commit_draw_commands();
+		assert!(retired.is_empty());
+		assert_eq!(context.draw_command_queue.len(), 1);
+		assert!(context.pending_draw_commands.is_empty());
+
+		// The next commit retires it in favor of the (empty) queue built since
+		let retired = context.commit_draw_commands();
+		assert_eq!(retired.len(), 1);
+		assert!(context.draw_command_queue.is_empty());
+	}
+}
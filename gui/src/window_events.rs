@@ -0,0 +1,205 @@
+use crate::application::Application;
+use crate::gui_tree::{ClipboardEvent, FileDropEvent, KeyEvent, PointerEvent, ScrollEvent, SCROLL_PIXELS_PER_LINE};
+use std::collections::HashMap;
+use winit::event::{ElementState, Event, KeyboardInput, MouseButton, MouseScrollDelta, VirtualKeyCode, WindowEvent};
+use winit::event_loop::ControlFlow;
+use winit::window::{Window, WindowId};
+
+// Dispatches an event-loop event to the context owning its window, rendering each window on its
+// own RedrawRequested; `windows` holds every open window keyed by the id winit reports
+pub fn window_event(app: &mut Application, windows: &HashMap<WindowId, Window>, event: Event<()>, control_flow: &mut ControlFlow) {
+	match event {
+		Event::WindowEvent { ref event, window_id } => {
+			// Events for windows that never got a context (or were closed) are dropped
+			let window = match windows.get(&window_id) {
+				Some(window) => window,
+				None => return,
+			};
+			if !app.set_active_window(window_id) {
+				return;
+			}
+
+			match event {
+				WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
+				WindowEvent::Resized(physical_size) => {
+					app.resize(*physical_size);
+				}
+				WindowEvent::ScaleFactorChanged { scale_factor, new_inner_size } => {
+					// The window moved to a display with a different DPI; relayout in the new logical space
+					app.context_mut().scale_factor = *scale_factor;
+					app.resize(**new_inner_size);
+				}
+				WindowEvent::CursorMoved { position, .. } => {
+					// Track the cursor in logical pixels so GUI hit-testing is DPI independent
+					let logical = position.to_logical::<f32>(app.context().scale_factor);
+					app.cursor_position = Some((logical.x, logical.y));
+
+					// A held slider or color picker follows the cursor, even outside its bounds, until
+					// the button releases
+					if app.context_mut().gui_tree.handle_cursor_drag(logical.x, logical.y) {
+						app.mark_dirty();
+					}
+
+					// Crossing a button's edge updates its hover or pressed visuals
+					if app.context_mut().gui_tree.handle_cursor_move(logical.x, logical.y) {
+						app.mark_dirty();
+					}
+
+					// Show the hovered node's cursor, only calling into winit when the resolved icon changes
+					let tree = &app.context().gui_tree;
+					let cursor = tree
+						.hit_test(logical.x, logical.y)
+						.and_then(|node| tree.get(node))
+						.map(|node| node.cursor)
+						.unwrap_or(winit::window::CursorIcon::Default);
+					if cursor != app.cursor_icon {
+						window.set_cursor_icon(cursor);
+						app.cursor_icon = cursor;
+					}
+				}
+				WindowEvent::MouseWheel { delta, .. } => {
+					// Wheels report lines and trackpads report pixels; normalize both into logical pixels
+					let (dx, dy) = match delta {
+						MouseScrollDelta::LineDelta(x, y) => (x * SCROLL_PIXELS_PER_LINE, y * SCROLL_PIXELS_PER_LINE),
+						// PixelDelta already arrives in logical pixels in this winit version
+						MouseScrollDelta::PixelDelta(position) => (position.x as f32, position.y as f32),
+					};
+					let hit = app.cursor_position.and_then(|(x, y)| app.context().gui_tree.hit_test(x, y));
+					if let Some(node) = hit {
+						app.context_mut().gui_tree.handle_scroll(node, ScrollEvent { dx, dy });
+					}
+				}
+				WindowEvent::HoveredFile(_) => {
+					// Lets the UI draw a drop-target highlight while the file is held over the window
+					app.file_hover = true;
+					app.mark_dirty();
+				}
+				WindowEvent::HoveredFileCancelled => {
+					app.file_hover = false;
+					app.mark_dirty();
+				}
+				WindowEvent::DroppedFile(path) => {
+					app.file_hover = false;
+					app.dropped_file = Some(path.clone());
+
+					// Deliver to the node under the cursor, falling back to the root when the cursor
+					// position is unknown (e.g. the drop arrived without the cursor ever moving)
+					let target = app.cursor_position.and_then(|(x, y)| app.context().gui_tree.hit_test(x, y)).unwrap_or_else(|| app.context().gui_tree.root());
+					app.context_mut().gui_tree.handle_file_drop(target, FileDropEvent { path: path.clone() });
+					app.mark_dirty();
+				}
+				WindowEvent::MouseInput { state, button: MouseButton::Left, .. } => {
+					// Resolve the node under the last known cursor position; clicks are synthesized by the tree
+					let hit = app.cursor_position.and_then(|(x, y)| app.context().gui_tree.hit_test(x, y));
+					match (state, hit) {
+						(ElementState::Pressed, Some(node)) => {
+							app.context_mut().gui_tree.handle_pointer(node, PointerEvent::Down);
+							// A press on a slider or color picker jumps it to the clicked position immediately
+							if let Some((x, y)) = app.cursor_position {
+								if app.context_mut().gui_tree.handle_cursor_drag(x, y) {
+									app.mark_dirty();
+								}
+							}
+						}
+						(ElementState::Released, Some(node)) => app.context_mut().gui_tree.handle_pointer(node, PointerEvent::Up),
+						// A release outside every node cancels the pending press so no click fires later
+						(ElementState::Released, None) => app.context_mut().gui_tree.clear_press(),
+						(ElementState::Pressed, None) => {}
+					}
+				}
+				WindowEvent::ModifiersChanged(state) => {
+					app.modifiers = *state;
+				}
+				WindowEvent::KeyboardInput { input, .. } => match input {
+					KeyboardInput {
+						state: ElementState::Pressed,
+						virtual_keycode: Some(VirtualKeyCode::Escape),
+						..
+					} => *control_flow = ControlFlow::Exit,
+					KeyboardInput {
+						state: ElementState::Pressed,
+						virtual_keycode: Some(VirtualKeyCode::P),
+						..
+					} => app.cycle_present_mode(),
+					KeyboardInput {
+						state: ElementState::Pressed,
+						virtual_keycode: Some(VirtualKeyCode::F11),
+						..
+					} => {
+						// Toggle borderless fullscreen on the current monitor
+						app.fullscreen = !app.fullscreen;
+						window.set_fullscreen(if app.fullscreen { Some(winit::window::Fullscreen::Borderless(window.current_monitor())) } else { None });
+
+						// Rebuild the swap chain at the new dimensions immediately, rather than stretching
+						// frames until the OS delivers the Resized event
+						app.resize(window.inner_size());
+					}
+					// Tab walks keyboard focus through the focusable controls; Shift walks it backwards
+					KeyboardInput {
+						state: ElementState::Pressed,
+						virtual_keycode: Some(VirtualKeyCode::Tab),
+						..
+					} => {
+						let backwards = app.modifiers.shift();
+						app.context_mut().gui_tree.advance_focus(backwards);
+						app.mark_dirty();
+					}
+					// Ctrl+C asks the focused widget for its selection; Ctrl+V hands it the clipboard text
+					KeyboardInput {
+						state: ElementState::Pressed,
+						virtual_keycode: Some(VirtualKeyCode::C),
+						..
+					} if app.modifiers.ctrl() => app.context_mut().gui_tree.handle_clipboard(ClipboardEvent::Copy),
+					KeyboardInput {
+						state: ElementState::Pressed,
+						virtual_keycode: Some(VirtualKeyCode::V),
+						..
+					} if app.modifiers.ctrl() => {
+						if let Some(text) = app.get_clipboard_text() {
+							app.context_mut().gui_tree.handle_clipboard(ClipboardEvent::Paste(text));
+						}
+					}
+					// Everything else is GUI input, delivered to whichever node holds keyboard focus
+					KeyboardInput {
+						state,
+						virtual_keycode: Some(key),
+						..
+					} => app.context_mut().gui_tree.handle_key(KeyEvent { key: *key, state: *state }),
+					_ => {}
+				},
+				_ => {}
+			}
+		}
+		Event::MainEventsCleared => {
+			app.poll_shader_changes();
+
+			// Redraw each window whose content changed, idling the CPU otherwise
+			if app.is_dirty() {
+				// An active frame cap sleeps until the next interval instead of redrawing immediately
+				match app.next_frame_deadline() {
+					Some(deadline) => *control_flow = ControlFlow::WaitUntil(deadline),
+					None => {
+						for (id, window) in windows {
+							// Paused windows keep their dirty flag but wait for the restore to redraw
+							if app.window_is_dirty(*id) && !app.window_is_render_paused(*id) {
+								window.request_redraw();
+							}
+						}
+					}
+				}
+			} else {
+				*control_flow = ControlFlow::Wait;
+			}
+		}
+		Event::RedrawRequested(id) if app.set_active_window(id) => {
+			app.render();
+		}
+		Event::LoopDestroyed => {
+			// Remember the primary window's geometry so the next launch opens where this session closed
+			if let Some(window) = app.primary_window_id().and_then(|id| windows.get(&id)) {
+				Application::save_window_state(window, crate::window_state::WINDOW_STATE_PATH);
+			}
+		}
+		_ => {}
+	}
+}
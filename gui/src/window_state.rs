@@ -0,0 +1,102 @@
+// Where the saved geometry lives, relative to the working directory like the shader and font assets
+pub const WINDOW_STATE_PATH: &str = "window-state.json";
+
+// The window geometry remembered across launches: inner size and outer position in physical pixels
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WindowState {
+	pub width: u32,
+	pub height: u32,
+	pub x: i32,
+	pub y: i32,
+}
+
+impl WindowState {
+	// Serializes to a flat JSON object, written by hand since nothing else here needs a serializer
+	pub fn to_json(self) -> String {
+		format!("{{\"width\": {}, \"height\": {}, \"x\": {}, \"y\": {}}}", self.width, self.height, self.x, self.y)
+	}
+
+	// Parses the saved JSON; None on any missing or malformed field so startup falls back to defaults
+	pub fn from_json(json: &str) -> Option<WindowState> {
+		Some(WindowState {
+			width: json_field(json, "width")?,
+			height: json_field(json, "height")?,
+			x: json_field(json, "x")?,
+			y: json_field(json, "y")?,
+		})
+	}
+
+	// Pulls the window back onto a monitor when its saved position no longer lands on any, e.g.
+	// because the monitor it was on last session has been disconnected
+	// Monitors are (x, y, width, height) rectangles in physical pixels, primary first
+	pub fn clamp_to_monitors(&mut self, monitors: &[(i32, i32, u32, u32)]) {
+		if monitors.is_empty() {
+			return;
+		}
+
+		// Still visible when the window's top-left corner (and so its title bar) is on some monitor
+		let visible = monitors
+			.iter()
+			.any(|&(x, y, width, height)| self.x >= x && self.x < x + width as i32 && self.y >= y && self.y < y + height as i32);
+		if visible {
+			return;
+		}
+
+		// Clamp onto the primary monitor, keeping the whole window inside it when it fits
+		let (x, y, width, height) = monitors[0];
+		self.x = self.x.max(x).min(x + (width as i32 - self.width as i32).max(0));
+		self.y = self.y.max(y).min(y + (height as i32 - self.height as i32).max(0));
+	}
+}
+
+// The integer following `"key":` in the JSON text, if present and parseable as T
+fn json_field<T: std::str::FromStr>(json: &str, key: &str) -> Option<T> {
+	let marker = format!("\"{}\":", key);
+	let start = json.find(&marker)? + marker.len();
+	let rest = json[start..].trim_start();
+	let end = rest.find(|character: char| !character.is_ascii_digit() && character != '-').unwrap_or(rest.len());
+	rest[..end].parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn the_state_round_trips_through_json() {
+		let state = WindowState { width: 1280, height: 720, x: -8, y: 31 };
+		assert_eq!(WindowState::from_json(&state.to_json()), Some(state));
+	}
+
+	#[test]
+	fn malformed_or_incomplete_json_is_rejected() {
+		assert_eq!(WindowState::from_json(""), None);
+		assert_eq!(WindowState::from_json("{\"width\": 1280, \"height\": 720}"), None);
+		assert_eq!(WindowState::from_json("{\"width\": \"wide\", \"height\": 720, \"x\": 0, \"y\": 0}"), None);
+	}
+
+	#[test]
+	fn an_off_screen_position_clamps_onto_the_primary_monitor() {
+		// Saved on a monitor to the right that has since been unplugged
+		let mut state = WindowState { width: 800, height: 600, x: 2500, y: 100 };
+		state.clamp_to_monitors(&[(0, 0, 1920, 1080)]);
+		assert_eq!((state.x, state.y), (1120, 100));
+
+		// A window larger than the monitor pins to the monitor's origin
+		let mut oversized = WindowState { width: 4000, height: 3000, x: -5000, y: -5000 };
+		oversized.clamp_to_monitors(&[(0, 0, 1920, 1080)]);
+		assert_eq!((oversized.x, oversized.y), (0, 0));
+	}
+
+	#[test]
+	fn a_position_on_any_monitor_is_left_alone() {
+		// On a secondary monitor arranged to the left of the primary, so its coordinates are negative
+		let mut state = WindowState { width: 800, height: 600, x: -1800, y: 50 };
+		state.clamp_to_monitors(&[(0, 0, 1920, 1080), (-1920, 0, 1920, 1080)]);
+		assert_eq!((state.x, state.y), (-1800, 50));
+
+		// With no monitors reported at all, the geometry is left untouched
+		state.clamp_to_monitors(&[]);
+		assert_eq!((state.x, state.y), (-1800, 50));
+	}
+}